@@ -271,3 +271,39 @@ pub trait ArgminMinMax {
     /// Select piecewise maximum
     fn max(x: &Self, y: &Self) -> Self;
 }
+
+/// Access to the individual elements of a vector, as required for instance for finite differences
+pub trait ArgminElements: Sized {
+    /// Type of the elements
+    type Element;
+    /// Number of elements
+    fn num_elements(&self) -> usize;
+    /// Returns element `i`
+    fn get_element(&self, i: usize) -> Self::Element;
+    /// Returns a copy of `self` where `delta` is added to element `i`
+    #[must_use]
+    fn add_to_element(&self, i: usize, delta: Self::Element) -> Self;
+    /// Constructs a vector from its elements
+    fn from_elements(elements: Vec<Self::Element>) -> Self;
+}
+
+/// Assembles a matrix from column vectors of type `Self`, one column per element of a vector of
+/// type `P` (such as a Jacobian or a Hessian)
+pub trait ArgminStackColumns<P>: Sized {
+    /// Type of the resulting matrix
+    type Matrix;
+    /// Returns the matrix whose `i`-th column is `columns[i]`
+    fn stack_columns(columns: Vec<Self>) -> Self::Matrix;
+}
+
+/// Conversion between complex values and their real and imaginary parts
+pub trait ArgminComplexParts {
+    /// Real counterpart of `Self`
+    type Real;
+    /// Constructs a complex value with real part `real` and imaginary part zero
+    fn from_real(real: &Self::Real) -> Self;
+    /// Returns the real part
+    fn real_part(&self) -> Self::Real;
+    /// Returns the imaginary part
+    fn imag_part(&self) -> Self::Real;
+}
@@ -0,0 +1,82 @@
+// Copyright 2018-2022 argmin developers
+//
+// Licensed under the Apache License, Version 2.0 <LICENSE-APACHE or
+// http://apache.org/licenses/LICENSE-2.0> or the MIT license <LICENSE-MIT or
+// http://opensource.org/licenses/MIT>, at your option. This file may not be
+// copied, modified, or distributed except according to those terms.
+
+use crate::ArgminComplexParts;
+
+use nalgebra::{
+    base::{allocator::Allocator, dimension::Dim},
+    DefaultAllocator, OVector, Scalar,
+};
+use num_complex::Complex;
+use num_traits::Zero;
+
+impl<N, D> ArgminComplexParts for OVector<Complex<N>, D>
+where
+    N: Scalar + Zero,
+    D: Dim,
+    DefaultAllocator: Allocator<N, D> + Allocator<Complex<N>, D>,
+{
+    type Real = OVector<N, D>;
+
+    #[inline]
+    fn from_real(real: &OVector<N, D>) -> OVector<Complex<N>, D> {
+        real.map(|x| Complex::new(x, N::zero()))
+    }
+
+    #[inline]
+    fn real_part(&self) -> OVector<N, D> {
+        self.map(|x| x.re)
+    }
+
+    #[inline]
+    fn imag_part(&self) -> OVector<N, D> {
+        self.map(|x| x.im)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use nalgebra::{DVector, Vector2};
+    use paste::item;
+
+    macro_rules! make_test {
+        ($t:ty) => {
+            item! {
+                #[test]
+                fn [<test_complexparts_ $t>]() {
+                    let a = Vector2::new(Complex::new(1 as $t, 4 as $t), Complex::new(-2 as $t, 3 as $t));
+                    let re = a.real_part();
+                    let im = a.imag_part();
+                    let target_re = Vector2::new(1 as $t, -2 as $t);
+                    let target_im = Vector2::new(4 as $t, 3 as $t);
+                    for i in 0..2 {
+                        assert!(((re[i] - target_re[i]) as f64).abs() < std::f64::EPSILON);
+                        assert!(((im[i] - target_im[i]) as f64).abs() < std::f64::EPSILON);
+                    }
+                    let b = <Vector2<Complex<$t>> as ArgminComplexParts>::from_real(&target_re);
+                    for i in 0..2 {
+                        assert!(((b[i].re - target_re[i]) as f64).abs() < std::f64::EPSILON);
+                        assert!((b[i].im as f64).abs() < std::f64::EPSILON);
+                    }
+                }
+            }
+
+            item! {
+                #[test]
+                fn [<test_complexparts_dynamic_ $t>]() {
+                    let a = DVector::from_vec(vec![Complex::new(1 as $t, 4 as $t)]);
+                    assert!(((a.real_part()[0] - 1 as $t) as f64).abs() < std::f64::EPSILON);
+                    assert!(((a.imag_part()[0] - 4 as $t) as f64).abs() < std::f64::EPSILON);
+                }
+            }
+        };
+    }
+
+    make_test!(f32);
+    make_test!(f64);
+}
@@ -6,29 +6,35 @@
 // copied, modified, or distributed except according to those terms.
 
 mod add;
+mod complexparts;
 mod conj;
 mod div;
 mod dot;
+mod elements;
 mod eye;
 mod inv;
 mod mul;
 mod norm;
 mod scaledadd;
 mod scaledsub;
+mod stackcolumns;
 mod sub;
 mod transpose;
 mod zero;
 
 pub use add::*;
+pub use complexparts::*;
 pub use conj::*;
 pub use div::*;
 pub use dot::*;
+pub use elements::*;
 pub use eye::*;
 pub use inv::*;
 pub use mul::*;
 pub use norm::*;
 pub use scaledadd::*;
 pub use scaledsub::*;
+pub use stackcolumns::*;
 pub use sub::*;
 pub use transpose::*;
 pub use zero::*;
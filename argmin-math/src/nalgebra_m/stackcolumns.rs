@@ -0,0 +1,89 @@
+// Copyright 2018-2022 argmin developers
+//
+// Licensed under the Apache License, Version 2.0 <LICENSE-APACHE or
+// http://apache.org/licenses/LICENSE-2.0> or the MIT license <LICENSE-MIT or
+// http://opensource.org/licenses/MIT>, at your option. This file may not be
+// copied, modified, or distributed except according to those terms.
+
+use crate::ArgminStackColumns;
+
+use nalgebra::{
+    base::{allocator::Allocator, dimension::Dim},
+    DefaultAllocator, OMatrix, OVector, Scalar,
+};
+
+impl<N, R, C> ArgminStackColumns<OVector<N, C>> for OVector<N, R>
+where
+    N: Scalar,
+    R: Dim,
+    C: Dim,
+    DefaultAllocator: Allocator<N, R> + Allocator<N, C> + Allocator<N, R, C>,
+{
+    type Matrix = OMatrix<N, R, C>;
+
+    #[inline]
+    fn stack_columns(columns: Vec<OVector<N, R>>) -> OMatrix<N, R, C> {
+        OMatrix::from_columns(&columns)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use nalgebra::{DMatrix, DVector, Matrix3x2, Vector2, Vector3};
+    use paste::item;
+
+    macro_rules! make_test {
+        ($t:ty) => {
+            item! {
+                #[test]
+                fn [<test_stack_columns_ $t>]() {
+                    let columns = vec![
+                        Vector3::new(1 as $t, 2 as $t, 3 as $t),
+                        Vector3::new(4 as $t, 5 as $t, 6 as $t),
+                    ];
+                    let m: Matrix3x2<$t> =
+                        <Vector3<$t> as ArgminStackColumns<Vector2<$t>>>::stack_columns(columns);
+                    let target = Matrix3x2::new(
+                        1 as $t, 4 as $t,
+                        2 as $t, 5 as $t,
+                        3 as $t, 6 as $t
+                    );
+                    for i in 0..3 {
+                        for j in 0..2 {
+                            assert!(((target[(i, j)] - m[(i, j)]) as f64).abs() < std::f64::EPSILON);
+                        }
+                    }
+                }
+            }
+
+            item! {
+                #[test]
+                fn [<test_stack_columns_dynamic_ $t>]() {
+                    let columns = vec![
+                        DVector::from_vec(vec![1 as $t, 2 as $t, 3 as $t]),
+                        DVector::from_vec(vec![4 as $t, 5 as $t, 6 as $t]),
+                    ];
+                    let m: DMatrix<$t> =
+                        <DVector<$t> as ArgminStackColumns<DVector<$t>>>::stack_columns(columns);
+                    assert_eq!(m.shape(), (3, 2));
+                    assert!(((m[(2, 1)] - 6 as $t) as f64).abs() < std::f64::EPSILON);
+                    assert!(((m[(0, 1)] - 4 as $t) as f64).abs() < std::f64::EPSILON);
+                }
+            }
+        };
+    }
+
+    make_test!(isize);
+    make_test!(usize);
+    make_test!(i8);
+    make_test!(u8);
+    make_test!(i16);
+    make_test!(u16);
+    make_test!(i32);
+    make_test!(u32);
+    make_test!(i64);
+    make_test!(u64);
+    make_test!(f32);
+    make_test!(f64);
+}
@@ -0,0 +1,70 @@
+// Copyright 2018-2022 argmin developers
+//
+// Licensed under the Apache License, Version 2.0 <LICENSE-APACHE or
+// http://apache.org/licenses/LICENSE-2.0> or the MIT license <LICENSE-MIT or
+// http://opensource.org/licenses/MIT>, at your option. This file may not be
+// copied, modified, or distributed except according to those terms.
+
+use crate::ArgminComplexParts;
+use ndarray::Array1;
+use num_complex::Complex;
+
+macro_rules! make_complexparts {
+    ($t:ty) => {
+        impl ArgminComplexParts for Array1<Complex<$t>> {
+            type Real = Array1<$t>;
+
+            #[inline]
+            fn from_real(real: &Array1<$t>) -> Array1<Complex<$t>> {
+                real.mapv(|x| Complex::new(x, 0 as $t))
+            }
+
+            #[inline]
+            fn real_part(&self) -> Array1<$t> {
+                self.mapv(|x| x.re)
+            }
+
+            #[inline]
+            fn imag_part(&self) -> Array1<$t> {
+                self.mapv(|x| x.im)
+            }
+        }
+    };
+}
+
+make_complexparts!(f32);
+make_complexparts!(f64);
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use ndarray::array;
+    use paste::item;
+
+    macro_rules! make_test {
+        ($t:ty) => {
+            item! {
+                #[test]
+                fn [<test_complexparts_ $t>]() {
+                    let a = array![Complex::new(1 as $t, 4 as $t), Complex::new(-2 as $t, 3 as $t)];
+                    let re = a.real_part();
+                    let im = a.imag_part();
+                    let target_re = array![1 as $t, -2 as $t];
+                    let target_im = array![4 as $t, 3 as $t];
+                    for i in 0..2 {
+                        assert!(((re[i] - target_re[i]) as f64).abs() < std::f64::EPSILON);
+                        assert!(((im[i] - target_im[i]) as f64).abs() < std::f64::EPSILON);
+                    }
+                    let b = <Array1<Complex<$t>> as ArgminComplexParts>::from_real(&target_re);
+                    for i in 0..2 {
+                        assert!(((b[i].re - target_re[i]) as f64).abs() < std::f64::EPSILON);
+                        assert!((b[i].im as f64).abs() < std::f64::EPSILON);
+                    }
+                }
+            }
+        };
+    }
+
+    make_test!(f32);
+    make_test!(f64);
+}
@@ -0,0 +1,76 @@
+// Copyright 2018-2022 argmin developers
+//
+// Licensed under the Apache License, Version 2.0 <LICENSE-APACHE or
+// http://apache.org/licenses/LICENSE-2.0> or the MIT license <LICENSE-MIT or
+// http://opensource.org/licenses/MIT>, at your option. This file may not be
+// copied, modified, or distributed except according to those terms.
+
+use crate::ArgminStackColumns;
+use ndarray::{Array1, Array2};
+
+impl<T, U> ArgminStackColumns<Array1<U>> for Array1<T>
+where
+    T: Clone,
+{
+    type Matrix = Array2<T>;
+
+    #[inline]
+    fn stack_columns(columns: Vec<Array1<T>>) -> Array2<T> {
+        let nrows = columns.first().map(Array1::len).unwrap_or(0);
+        Array2::from_shape_fn((nrows, columns.len()), |(i, j)| columns[j][i].clone())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use ndarray::array;
+    use paste::item;
+
+    macro_rules! make_test {
+        ($t:ty) => {
+            item! {
+                #[test]
+                fn [<test_stack_columns_ $t>]() {
+                    let columns = vec![
+                        array![1 as $t, 2 as $t, 3 as $t],
+                        array![4 as $t, 5 as $t, 6 as $t],
+                    ];
+                    let m = <Array1<$t> as ArgminStackColumns<Array1<$t>>>::stack_columns(columns);
+                    let target = array![
+                        [1 as $t, 4 as $t],
+                        [2 as $t, 5 as $t],
+                        [3 as $t, 6 as $t],
+                    ];
+                    assert_eq!(m.dim(), (3, 2));
+                    for i in 0..3 {
+                        for j in 0..2 {
+                            assert!(((target[(i, j)] - m[(i, j)]) as f64).abs() < std::f64::EPSILON);
+                        }
+                    }
+                }
+            }
+
+            item! {
+                #[test]
+                fn [<test_stack_columns_empty_ $t>]() {
+                    let m = <Array1<$t> as ArgminStackColumns<Array1<$t>>>::stack_columns(vec![]);
+                    assert_eq!(m.dim(), (0, 0));
+                }
+            }
+        };
+    }
+
+    make_test!(isize);
+    make_test!(usize);
+    make_test!(i8);
+    make_test!(u8);
+    make_test!(i16);
+    make_test!(u16);
+    make_test!(i32);
+    make_test!(u32);
+    make_test!(i64);
+    make_test!(u64);
+    make_test!(f32);
+    make_test!(f64);
+}
@@ -0,0 +1,60 @@
+// Copyright 2018-2022 argmin developers
+//
+// Licensed under the Apache License, Version 2.0 <LICENSE-APACHE or
+// http://apache.org/licenses/LICENSE-2.0> or the MIT license <LICENSE-MIT or
+// http://opensource.org/licenses/MIT>, at your option. This file may not be
+// copied, modified, or distributed except according to those terms.
+
+use crate::ArgminComplexParts;
+use num_complex::Complex;
+
+macro_rules! make_complexparts {
+    ($t:ty) => {
+        impl ArgminComplexParts for Complex<$t> {
+            type Real = $t;
+
+            #[inline]
+            fn from_real(real: &$t) -> Complex<$t> {
+                Complex::new(*real, 0 as $t)
+            }
+
+            #[inline]
+            fn real_part(&self) -> $t {
+                self.re
+            }
+
+            #[inline]
+            fn imag_part(&self) -> $t {
+                self.im
+            }
+        }
+    };
+}
+
+make_complexparts!(f32);
+make_complexparts!(f64);
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use paste::item;
+
+    macro_rules! make_test {
+        ($t:ty) => {
+            item! {
+                #[test]
+                fn [<test_complexparts_ $t>]() {
+                    let a = Complex::new(3 as $t, -2 as $t);
+                    assert!(((a.real_part() - 3 as $t) as f64).abs() < std::f64::EPSILON);
+                    assert!(((a.imag_part() + 2 as $t) as f64).abs() < std::f64::EPSILON);
+                    let b = <Complex<$t> as ArgminComplexParts>::from_real(&(5 as $t));
+                    assert!(((b.re - 5 as $t) as f64).abs() < std::f64::EPSILON);
+                    assert!((b.im as f64).abs() < std::f64::EPSILON);
+                }
+            }
+        };
+    }
+
+    make_test!(f32);
+    make_test!(f64);
+}
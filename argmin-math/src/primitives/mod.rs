@@ -6,6 +6,7 @@
 // copied, modified, or distributed except according to those terms.
 
 mod add;
+mod complexparts;
 mod conj;
 mod div;
 mod dot;
@@ -19,6 +20,7 @@ mod weighteddot;
 mod zero;
 
 pub use add::*;
+pub use complexparts::*;
 pub use conj::*;
 pub use div::*;
 pub use dot::*;
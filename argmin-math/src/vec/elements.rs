@@ -0,0 +1,82 @@
+// Copyright 2018-2022 argmin developers
+//
+// Licensed under the Apache License, Version 2.0 <LICENSE-APACHE or
+// http://apache.org/licenses/LICENSE-2.0> or the MIT license <LICENSE-MIT or
+// http://opensource.org/licenses/MIT>, at your option. This file may not be
+// copied, modified, or distributed except according to those terms.
+
+use crate::ArgminElements;
+use std::ops::Add;
+
+impl<T> ArgminElements for Vec<T>
+where
+    T: Clone + Add<Output = T>,
+{
+    type Element = T;
+
+    #[inline]
+    fn num_elements(&self) -> usize {
+        self.len()
+    }
+
+    #[inline]
+    fn get_element(&self, i: usize) -> T {
+        self[i].clone()
+    }
+
+    #[inline]
+    fn add_to_element(&self, i: usize, delta: T) -> Vec<T> {
+        let mut out = self.clone();
+        out[i] = out[i].clone() + delta;
+        out
+    }
+
+    #[inline]
+    fn from_elements(elements: Vec<T>) -> Vec<T> {
+        elements
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use num_complex::Complex;
+    use paste::item;
+
+    macro_rules! make_test {
+        ($t:ty) => {
+            item! {
+                #[test]
+                fn [<test_elements_ $t>]() {
+                    let a = <Vec<$t> as ArgminElements>::from_elements(vec![1 as $t, 4 as $t, 8 as $t]);
+                    assert_eq!(a.num_elements(), 3);
+                    let b = a.add_to_element(1, 2 as $t);
+                    let target = vec![1 as $t, 6 as $t, 8 as $t];
+                    for i in 0..3 {
+                        assert!(((a.get_element(i) - [1 as $t, 4 as $t, 8 as $t][i]) as f64).abs() < std::f64::EPSILON);
+                        assert!(((b.get_element(i) - target[i]) as f64).abs() < std::f64::EPSILON);
+                    }
+                }
+            }
+
+            item! {
+                #[test]
+                fn [<test_elements_complex_ $t>]() {
+                    let a = vec![Complex::new(1 as $t, 2 as $t), Complex::new(4 as $t, 3 as $t)];
+                    let b = a.add_to_element(0, Complex::new(0 as $t, 1 as $t));
+                    assert_eq!(b.num_elements(), 2);
+                    assert!(((b.get_element(0).re - 1 as $t) as f64).abs() < std::f64::EPSILON);
+                    assert!(((b.get_element(0).im - 3 as $t) as f64).abs() < std::f64::EPSILON);
+                    assert!(((b.get_element(1).im - 3 as $t) as f64).abs() < std::f64::EPSILON);
+                }
+            }
+        };
+    }
+
+    make_test!(i8);
+    make_test!(i16);
+    make_test!(i32);
+    make_test!(i64);
+    make_test!(f32);
+    make_test!(f64);
+}
@@ -6,9 +6,11 @@
 // copied, modified, or distributed except according to those terms.
 
 mod add;
+mod complexparts;
 mod conj;
 mod div;
 mod dot;
+mod elements;
 mod eye;
 mod minmax;
 mod mul;
@@ -16,14 +18,17 @@ mod norm;
 mod random;
 mod scaledadd;
 mod scaledsub;
+mod stackcolumns;
 mod sub;
 mod transpose;
 mod zero;
 
 pub use add::*;
+pub use complexparts::*;
 pub use conj::*;
 pub use div::*;
 pub use dot::*;
+pub use elements::*;
 pub use eye::*;
 pub use minmax::*;
 pub use mul::*;
@@ -31,6 +36,7 @@ pub use norm::*;
 pub use random::*;
 pub use scaledadd::*;
 pub use scaledsub::*;
+pub use stackcolumns::*;
 pub use sub::*;
 pub use transpose::*;
 pub use zero::*;
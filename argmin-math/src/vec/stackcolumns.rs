@@ -0,0 +1,77 @@
+// Copyright 2018-2022 argmin developers
+//
+// Licensed under the Apache License, Version 2.0 <LICENSE-APACHE or
+// http://apache.org/licenses/LICENSE-2.0> or the MIT license <LICENSE-MIT or
+// http://opensource.org/licenses/MIT>, at your option. This file may not be
+// copied, modified, or distributed except according to those terms.
+
+use crate::ArgminStackColumns;
+
+impl<T, U> ArgminStackColumns<Vec<U>> for Vec<T>
+where
+    T: Clone,
+{
+    type Matrix = Vec<Vec<T>>;
+
+    #[inline]
+    fn stack_columns(columns: Vec<Vec<T>>) -> Vec<Vec<T>> {
+        let nrows = columns.first().map(Vec::len).unwrap_or(0);
+        (0..nrows)
+            .map(|i| columns.iter().map(|col| col[i].clone()).collect())
+            .collect()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use paste::item;
+
+    macro_rules! make_test {
+        ($t:ty) => {
+            item! {
+                #[test]
+                fn [<test_stack_columns_ $t>]() {
+                    let columns = vec![
+                        vec![1 as $t, 2 as $t, 3 as $t],
+                        vec![4 as $t, 5 as $t, 6 as $t],
+                    ];
+                    let m = <Vec<$t> as ArgminStackColumns<Vec<$t>>>::stack_columns(columns);
+                    let target = vec![
+                        vec![1 as $t, 4 as $t],
+                        vec![2 as $t, 5 as $t],
+                        vec![3 as $t, 6 as $t],
+                    ];
+                    assert_eq!(m.len(), 3);
+                    for i in 0..3 {
+                        assert_eq!(m[i].len(), 2);
+                        for j in 0..2 {
+                            assert!(((target[i][j] - m[i][j]) as f64).abs() < std::f64::EPSILON);
+                        }
+                    }
+                }
+            }
+
+            item! {
+                #[test]
+                fn [<test_stack_columns_empty_ $t>]() {
+                    let m = <Vec<$t> as ArgminStackColumns<Vec<$t>>>::stack_columns(vec![]);
+                    assert!(m.is_empty());
+                }
+            }
+        };
+    }
+
+    make_test!(isize);
+    make_test!(usize);
+    make_test!(i8);
+    make_test!(u8);
+    make_test!(i16);
+    make_test!(u16);
+    make_test!(i32);
+    make_test!(u32);
+    make_test!(i64);
+    make_test!(u64);
+    make_test!(f32);
+    make_test!(f64);
+}
@@ -0,0 +1,495 @@
+// Copyright 2018-2022 argmin developers
+//
+// Licensed under the Apache License, Version 2.0 <LICENSE-APACHE or
+// http://apache.org/licenses/LICENSE-2.0> or the MIT license <LICENSE-MIT or
+// http://opensource.org/licenses/MIT>, at your option. This file may not be
+// copied, modified, or distributed except according to those terms.
+
+use crate::core::problem::report_cache_hit;
+use crate::core::{CostFunction, Error, Gradient, Hessian, Jacobian, Operator};
+use std::any::Any;
+use std::collections::{HashMap, VecDeque};
+use std::fmt;
+use std::sync::Mutex;
+
+/// Cached results of a single method, most recently used first
+type Entries = VecDeque<Box<dyn Any + Send>>;
+
+/// Caches the results of expensive problems
+///
+/// Wraps a problem and remembers the results of the last `capacity` evaluations of each of
+/// `cost`, `gradient`, `hessian`, `apply` and `jacobian` (as far as they are implemented by the
+/// wrapped problem). If a method is called again with a parameter vector which is equal to one of
+/// the remembered ones, the stored result is returned instead of evaluating the wrapped problem.
+/// This is transparent to solvers, which often evaluate the problem repeatedly at the same
+/// parameter vector, for instance when a line search hands its final iterate back to the solver.
+///
+/// Parameter vectors are compared via exact equality ([`PartialEq`]). Therefore parameter vectors
+/// and results are required to implement `PartialEq`/`Clone` as well as `Send` and to be
+/// `'static`, which is the case for the types of all backends of `argmin-math`. Results are
+/// evicted in least recently used order.
+///
+/// Calls answered from the cache are not counted as evaluations in the function evaluation counts
+/// of [`Problem`](`crate::core::Problem`) (such as `cost_count`); instead they are counted as
+/// `cost_cache_hits`, `gradient_cache_hits`, `hessian_cache_hits`, `operator_cache_hits` or
+/// `jacobian_cache_hits`, respectively.
+///
+/// Cost function and gradient are cached independently. A call to
+/// [`cost_and_gradient`](`crate::core::Gradient::cost_and_gradient`) looks up both caches and
+/// only evaluates what is missing: if neither is cached, `cost_and_gradient` of the wrapped
+/// problem is called once and both results are stored. Caching the gradient therefore requires
+/// the wrapped problem to implement [`CostFunction`] as well.
+///
+/// # Example
+///
+/// ```
+/// use argmin::core::{CachedProblem, CostFunction, Error, Problem};
+///
+/// struct Expensive {}
+///
+/// impl CostFunction for Expensive {
+///     type Param = Vec<f64>;
+///     type Output = f64;
+///
+///     fn cost(&self, param: &Self::Param) -> Result<Self::Output, Error> {
+///         // Imagine a costly simulation here
+///         Ok(param[0].powi(2) + param[1].powi(2))
+///     }
+/// }
+///
+/// // Remember the last 10 results of each method
+/// let mut problem = Problem::new(CachedProblem::new(Expensive {}, 10));
+///
+/// let cost1 = problem.cost(&vec![1.0, 2.0])?;
+/// let cost2 = problem.cost(&vec![1.0, 2.0])?;
+///
+/// assert_eq!(cost1, cost2);
+/// assert_eq!(problem.counts["cost_count"], 1);
+/// assert_eq!(problem.counts["cost_cache_hits"], 1);
+/// # Ok::<(), Error>(())
+/// ```
+pub struct CachedProblem<O> {
+    /// Wrapped problem
+    problem: O,
+    /// Maximum number of cached results per method
+    capacity: usize,
+    /// Cached results, keyed by the counts string of the method
+    cache: Mutex<HashMap<&'static str, Entries>>,
+}
+
+impl<O> CachedProblem<O> {
+    /// Wraps `problem` and caches the results of the last `capacity` evaluations of each method.
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// # use argmin::core::test_utils::TestProblem;
+    /// use argmin::core::CachedProblem;
+    ///
+    /// let problem = CachedProblem::new(TestProblem::new(), 5);
+    /// ```
+    pub fn new(problem: O, capacity: usize) -> Self {
+        CachedProblem {
+            problem,
+            capacity,
+            cache: Mutex::new(HashMap::new()),
+        }
+    }
+
+    /// Returns a reference to the wrapped problem.
+    pub fn inner(&self) -> &O {
+        &self.problem
+    }
+
+    /// Returns the wrapped problem.
+    pub fn into_inner(self) -> O {
+        self.problem
+    }
+
+    /// Removes all cached results.
+    ///
+    /// This needs to be called if the wrapped problem changes in a way which affects its results.
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// # use argmin::core::test_utils::TestProblem;
+    /// use argmin::core::CachedProblem;
+    ///
+    /// let problem = CachedProblem::new(TestProblem::new(), 5);
+    /// problem.clear();
+    /// ```
+    pub fn clear(&self) {
+        self.cache.lock().unwrap().clear();
+    }
+
+    /// Returns the cached result of the method labelled `counts_string` at `param` or evaluates
+    /// `func` and caches its result.
+    fn cached<P, T, F>(
+        &self,
+        counts_string: &'static str,
+        hits_string: &'static str,
+        param: &P,
+        func: F,
+    ) -> Result<T, Error>
+    where
+        P: PartialEq + Clone + Send + 'static,
+        T: Clone + Send + 'static,
+        F: FnOnce(&O, &P) -> Result<T, Error>,
+    {
+        if let Some(result) = self.lookup(counts_string, hits_string, param) {
+            return Ok(result);
+        }
+        // The lock is not held during the evaluation such that the problem may be evaluated
+        // concurrently.
+        let result = func(&self.problem, param)?;
+        self.insert(counts_string, param, &result);
+        Ok(result)
+    }
+
+    /// Returns the cached result of the method labelled `counts_string` at `param` and reports the
+    /// cache hit, or returns `None` if the result is not cached.
+    fn lookup<P, T>(
+        &self,
+        counts_string: &'static str,
+        hits_string: &'static str,
+        param: &P,
+    ) -> Option<T>
+    where
+        P: PartialEq + 'static,
+        T: Clone + 'static,
+    {
+        let mut cache = self.cache.lock().unwrap();
+        let entries = cache.get_mut(counts_string)?;
+        let pos = entries.iter().position(
+            |entry| matches!(entry.downcast_ref::<(P, T)>(), Some((p, _)) if p == param),
+        )?;
+        let entry = entries.remove(pos).unwrap();
+        let result = entry.downcast_ref::<(P, T)>().unwrap().1.clone();
+        entries.push_front(entry);
+        report_cache_hit(counts_string, hits_string);
+        Some(result)
+    }
+
+    /// Caches `result` as the result of the method labelled `counts_string` at `param`.
+    fn insert<P, T>(&self, counts_string: &'static str, param: &P, result: &T)
+    where
+        P: Clone + Send + 'static,
+        T: Clone + Send + 'static,
+    {
+        if self.capacity > 0 {
+            let mut cache = self.cache.lock().unwrap();
+            let entries = cache.entry(counts_string).or_default();
+            entries.push_front(Box::new((param.clone(), result.clone())));
+            entries.truncate(self.capacity);
+        }
+    }
+}
+
+/// The cache is not cloned; the clone starts with an empty cache.
+impl<O: Clone> Clone for CachedProblem<O> {
+    fn clone(&self) -> Self {
+        CachedProblem::new(self.problem.clone(), self.capacity)
+    }
+}
+
+impl<O: fmt::Debug> fmt::Debug for CachedProblem<O> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("CachedProblem")
+            .field("problem", &self.problem)
+            .field("capacity", &self.capacity)
+            .finish()
+    }
+}
+
+impl<O> Operator for CachedProblem<O>
+where
+    O: Operator,
+    O::Param: PartialEq + Clone + Send + 'static,
+    O::Output: Clone + Send + 'static,
+{
+    type Param = O::Param;
+    type Output = O::Output;
+
+    fn apply(&self, param: &Self::Param) -> Result<Self::Output, Error> {
+        self.cached("operator_count", "operator_cache_hits", param, O::apply)
+    }
+}
+
+impl<O> CostFunction for CachedProblem<O>
+where
+    O: CostFunction,
+    O::Param: PartialEq + Clone + Send + 'static,
+    O::Output: Clone + Send + 'static,
+{
+    type Param = O::Param;
+    type Output = O::Output;
+
+    fn cost(&self, param: &Self::Param) -> Result<Self::Output, Error> {
+        self.cached("cost_count", "cost_cache_hits", param, O::cost)
+    }
+
+    fn parallelize(&self) -> bool {
+        self.problem.parallelize()
+    }
+}
+
+impl<O> Gradient for CachedProblem<O>
+where
+    O: Gradient + CostFunction<Param = <O as Gradient>::Param>,
+    <O as Gradient>::Param: PartialEq + Clone + Send + 'static,
+    <O as CostFunction>::Output: Clone + Send + 'static,
+    O::Gradient: Clone + Send + 'static,
+{
+    type Param = <O as Gradient>::Param;
+    type Gradient = O::Gradient;
+
+    fn gradient(&self, param: &Self::Param) -> Result<Self::Gradient, Error> {
+        self.cached("gradient_count", "gradient_cache_hits", param, O::gradient)
+    }
+
+    /// Looks up both caches and only evaluates what is missing. If neither the cost function value
+    /// nor the gradient is cached, `cost_and_gradient` of the wrapped problem is called once.
+    fn cost_and_gradient(
+        &self,
+        param: &Self::Param,
+    ) -> Result<(<Self as CostFunction>::Output, Self::Gradient), Error> {
+        let cost = self.lookup("cost_count", "cost_cache_hits", param);
+        let grad = self.lookup("gradient_count", "gradient_cache_hits", param);
+        let (cost, grad) = match (cost, grad) {
+            (Some(cost), Some(grad)) => return Ok((cost, grad)),
+            (Some(cost), None) => (cost, self.problem.gradient(param)?),
+            (None, Some(grad)) => (self.problem.cost(param)?, grad),
+            (None, None) => self.problem.cost_and_gradient(param)?,
+        };
+        self.insert("cost_count", param, &cost);
+        self.insert("gradient_count", param, &grad);
+        Ok((cost, grad))
+    }
+}
+
+impl<O> Hessian for CachedProblem<O>
+where
+    O: Hessian,
+    O::Param: PartialEq + Clone + Send + 'static,
+    O::Hessian: Clone + Send + 'static,
+{
+    type Param = O::Param;
+    type Hessian = O::Hessian;
+
+    fn hessian(&self, param: &Self::Param) -> Result<Self::Hessian, Error> {
+        self.cached("hessian_count", "hessian_cache_hits", param, O::hessian)
+    }
+}
+
+impl<O> Jacobian for CachedProblem<O>
+where
+    O: Jacobian,
+    O::Param: PartialEq + Clone + Send + 'static,
+    O::Jacobian: Clone + Send + 'static,
+{
+    type Param = O::Param;
+    type Jacobian = O::Jacobian;
+
+    fn jacobian(&self, param: &Self::Param) -> Result<Self::Jacobian, Error> {
+        self.cached("jacobian_count", "jacobian_cache_hits", param, O::jacobian)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::core::test_utils::{Rosenbrock, TestProblem};
+    use crate::core::{Executor, FiniteDiff, Problem, State};
+    use crate::solver::linesearch::MoreThuenteLineSearch;
+    use crate::solver::quasinewton::LBFGS;
+    use approx::assert_relative_eq;
+
+    send_sync_test!(cachedproblem, CachedProblem<TestProblem>);
+
+    #[test]
+    fn test_cache() {
+        let mut problem = Problem::new(CachedProblem::new(Rosenbrock::default(), 2));
+        let evaluations = |problem: &Problem<CachedProblem<Rosenbrock>>| {
+            problem.problem.as_ref().unwrap().inner().cost_count()
+        };
+
+        let p1 = vec![1.0, 2.0];
+        let p2 = vec![2.0, 3.0];
+        let p3 = vec![3.0, 4.0];
+
+        let c1 = problem.cost(&p1).unwrap();
+        assert_relative_eq!(problem.cost(&p1).unwrap(), c1);
+        assert_eq!(evaluations(&problem), 1);
+        assert_eq!(problem.counts["cost_count"], 1);
+        assert_eq!(problem.counts["cost_cache_hits"], 1);
+
+        problem.cost(&p2).unwrap();
+        // p1 was used most recently, therefore p2 is evicted
+        problem.cost(&p1).unwrap();
+        problem.cost(&p3).unwrap();
+        assert_eq!(evaluations(&problem), 3);
+        problem.cost(&p1).unwrap();
+        problem.cost(&p3).unwrap();
+        assert_eq!(evaluations(&problem), 3);
+        problem.cost(&p2).unwrap();
+        assert_eq!(evaluations(&problem), 4);
+        assert_eq!(problem.counts["cost_count"], 4);
+        assert_eq!(problem.counts["cost_cache_hits"], 4);
+
+        // Gradients are cached independently of the cost function
+        problem.gradient(&p2).unwrap();
+        problem.gradient(&p2).unwrap();
+        assert_eq!(problem.counts["gradient_count"], 1);
+        assert_eq!(problem.counts["gradient_cache_hits"], 1);
+
+        let (cost, grad) = problem.cost_and_gradient(&p2).unwrap();
+        assert_relative_eq!(cost, Rosenbrock::default().cost(&p2).unwrap());
+        assert_eq!(grad, Rosenbrock::default().gradient(&p2).unwrap());
+        assert_eq!(evaluations(&problem), 4);
+        assert_eq!(problem.counts["cost_count"], 4);
+        assert_eq!(problem.counts["cost_cache_hits"], 5);
+        assert_eq!(problem.counts["gradient_count"], 1);
+        assert_eq!(problem.counts["gradient_cache_hits"], 2);
+
+        problem.problem.as_ref().unwrap().clear();
+        problem.cost(&p2).unwrap();
+        assert_eq!(evaluations(&problem), 5);
+    }
+
+    #[test]
+    fn test_cost_and_gradient() {
+        let mut problem = Problem::new(CachedProblem::new(Rosenbrock::default(), 2));
+        let evaluations = |problem: &Problem<CachedProblem<Rosenbrock>>| {
+            let inner = problem.problem.as_ref().unwrap().inner();
+            (
+                inner.cost_count(),
+                inner.gradient_count(),
+                inner.cost_and_gradient_count(),
+            )
+        };
+
+        let p1 = vec![1.0, 2.0];
+        let p2 = vec![2.0, 3.0];
+
+        // Neither is cached: the wrapped `cost_and_gradient` is called once and both are stored
+        let (cost, grad) = problem.cost_and_gradient(&p1).unwrap();
+        assert_relative_eq!(cost, Rosenbrock::default().cost(&p1).unwrap());
+        assert_eq!(grad, Rosenbrock::default().gradient(&p1).unwrap());
+        assert_eq!(evaluations(&problem), (0, 0, 1));
+        problem.cost(&p1).unwrap();
+        problem.gradient(&p1).unwrap();
+        problem.cost_and_gradient(&p1).unwrap();
+        assert_eq!(evaluations(&problem), (0, 0, 1));
+        assert_eq!(problem.counts["cost_count"], 1);
+        assert_eq!(problem.counts["cost_cache_hits"], 2);
+        assert_eq!(problem.counts["gradient_count"], 1);
+        assert_eq!(problem.counts["gradient_cache_hits"], 2);
+
+        // Only the cost function value is cached: only the gradient is evaluated
+        problem.cost(&p2).unwrap();
+        let (cost, grad) = problem.cost_and_gradient(&p2).unwrap();
+        assert_relative_eq!(cost, Rosenbrock::default().cost(&p2).unwrap());
+        assert_eq!(grad, Rosenbrock::default().gradient(&p2).unwrap());
+        assert_eq!(evaluations(&problem), (1, 1, 1));
+        assert_eq!(problem.counts["cost_count"], 2);
+        assert_eq!(problem.counts["cost_cache_hits"], 3);
+        assert_eq!(problem.counts["gradient_count"], 2);
+        problem.gradient(&p2).unwrap();
+        assert_eq!(problem.counts["gradient_cache_hits"], 3);
+    }
+
+    #[test]
+    fn test_capacity_zero() {
+        let mut problem = Problem::new(CachedProblem::new(Rosenbrock::default(), 0));
+        problem.cost(&vec![1.0, 2.0]).unwrap();
+        problem.cost(&vec![1.0, 2.0]).unwrap();
+        assert_eq!(problem.counts["cost_count"], 2);
+        assert!(!problem.counts.contains_key("cost_cache_hits"));
+    }
+
+    #[test]
+    fn test_bulk_cost() {
+        let mut problem = Problem::new(CachedProblem::new(Rosenbrock::default(), 10));
+        let params = vec![
+            vec![1.0, 2.0],
+            vec![2.0, 3.0],
+            vec![1.0, 2.0],
+            vec![1.0, 2.0],
+        ];
+
+        problem.cost(&params[1]).unwrap();
+        let costs = problem.bulk_cost(&params).unwrap();
+
+        for (param, cost) in params.iter().zip(costs.iter()) {
+            assert_relative_eq!(*cost, Rosenbrock::default().cost(param).unwrap());
+        }
+        // Evaluations performed in parallel (with the `rayon` feature) are counted as well. The
+        // repeated parameter vectors may be evaluated concurrently before they are cached.
+        let evaluations = problem.problem.as_ref().unwrap().inner().cost_count();
+        assert!((2..=4).contains(&evaluations));
+        assert_eq!(problem.counts["cost_count"], evaluations);
+        assert_eq!(
+            problem.counts["cost_count"] + problem.counts["cost_cache_hits"],
+            5
+        );
+    }
+
+    #[test]
+    fn test_other_methods() {
+        let mut problem = Problem::new(CachedProblem::new(TestProblem::new(), 1));
+        let p = vec![1.0, 2.0];
+        for _ in 0..3 {
+            assert_eq!(problem.apply(&p).unwrap(), p);
+            problem.hessian(&p).unwrap();
+            problem.jacobian(&p).unwrap();
+        }
+        for method in ["operator", "hessian", "jacobian"] {
+            assert_eq!(problem.counts[format!("{method}_count").as_str()], 1);
+            assert_eq!(problem.counts[format!("{method}_cache_hits").as_str()], 2);
+        }
+    }
+
+    #[test]
+    fn test_nested() {
+        // Cost function evaluations of finite differences are cached as well
+        struct Cost {}
+
+        impl CostFunction for Cost {
+            type Param = Vec<f64>;
+            type Output = f64;
+
+            fn cost(&self, p: &Self::Param) -> Result<Self::Output, Error> {
+                Ok(p[0] * p[1])
+            }
+        }
+
+        let mut problem = Problem::new(FiniteDiff::forward(CachedProblem::new(Cost {}, 10)));
+        let p = vec![1.0, 2.0];
+        problem.cost(&p).unwrap();
+        problem.gradient(&p).unwrap();
+        assert_eq!(problem.counts["gradient_count"], 1);
+        assert_eq!(problem.counts["cost_count"], 3);
+        assert_eq!(problem.counts["cost_cache_hits"], 1);
+    }
+
+    #[test]
+    fn test_solver() {
+        let linesearch = MoreThuenteLineSearch::new();
+        let solver = LBFGS::new(linesearch, 7);
+        let res = Executor::new(CachedProblem::new(Rosenbrock::default(), 4), solver)
+            .configure(|state| state.param(vec![-1.2, 1.0]).max_iters(100))
+            .run()
+            .unwrap();
+
+        let best = res.state().get_best_param().unwrap();
+        assert_relative_eq!(best[0], 1.0, epsilon = 1e-4);
+        assert_relative_eq!(best[1], 1.0, epsilon = 1e-4);
+        let counts = res.state().get_func_counts();
+        let inner = res.problem.problem.as_ref().unwrap().inner();
+        assert_eq!(
+            counts["cost_count"],
+            inner.cost_count() + inner.cost_and_gradient_count()
+        );
+    }
+}
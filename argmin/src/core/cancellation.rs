@@ -0,0 +1,147 @@
+// Copyright 2018-2022 argmin developers
+//
+// Licensed under the Apache License, Version 2.0 <LICENSE-APACHE or
+// http://apache.org/licenses/LICENSE-2.0> or the MIT license <LICENSE-MIT or
+// http://opensource.org/licenses/MIT>, at your option. This file may not be
+// copied, modified, or distributed except according to those terms.
+
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::Arc;
+
+/// A handle for cooperatively cancelling a running optimization
+///
+/// A `CancellationToken` can be passed to an [`Executor`](`crate::core::Executor`) via
+/// [`cancellation_token`](`crate::core::Executor::cancellation_token`). Clones of a token share
+/// the same underlying flag, therefore calling [`cancel`](`CancellationToken::cancel`) on any of
+/// the clones (possibly from another thread) cancels the optimization. Cancellation is checked
+/// before every iteration; a cancelled run terminates with
+/// [`TerminationReason::Aborted`](`crate::core::TerminationReason::Aborted`) and returns a valid
+/// [`OptimizationResult`](`crate::core::OptimizationResult`).
+///
+/// A single token may be shared by several optimizations in order to cancel all of them at once.
+///
+/// # Example
+///
+/// ```
+/// use argmin::core::CancellationToken;
+///
+/// let token = CancellationToken::new();
+/// let handle = token.clone();
+///
+/// std::thread::spawn(move || handle.cancel()).join().unwrap();
+///
+/// assert!(token.is_cancelled());
+/// ```
+#[derive(Clone, Debug, Default)]
+pub struct CancellationToken {
+    /// Set to `true` once cancelled
+    cancelled: Arc<AtomicBool>,
+}
+
+impl CancellationToken {
+    /// Constructs a new token which is not cancelled.
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// use argmin::core::CancellationToken;
+    ///
+    /// let token = CancellationToken::new();
+    /// # assert!(!token.is_cancelled());
+    /// ```
+    pub fn new() -> Self {
+        CancellationToken {
+            cancelled: Arc::new(AtomicBool::new(false)),
+        }
+    }
+
+    /// Cancels all optimizations using this token (or any of its clones).
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// use argmin::core::CancellationToken;
+    ///
+    /// let token = CancellationToken::new();
+    /// token.cancel();
+    /// # assert!(token.is_cancelled());
+    /// ```
+    pub fn cancel(&self) {
+        self.cancelled.store(true, Ordering::SeqCst);
+    }
+
+    /// Returns `true` if the token has been cancelled.
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// use argmin::core::CancellationToken;
+    ///
+    /// let token = CancellationToken::new();
+    /// assert!(!token.is_cancelled());
+    /// token.cancel();
+    /// assert!(token.is_cancelled());
+    /// ```
+    pub fn is_cancelled(&self) -> bool {
+        self.cancelled.load(Ordering::SeqCst)
+    }
+}
+
+/// Cancels `token` once CTRL-C (SIGINT) is received.
+///
+/// The process-wide CTRL-C handler is installed only once; subsequent calls merely register
+/// additional tokens. Therefore, CTRL-C handling works for any number of consecutive or
+/// concurrent optimizations in the same process. If another CTRL-C handler was already installed
+/// outside of argmin, this silently does nothing.
+#[cfg(feature = "ctrlc")]
+pub(crate) fn cancel_on_ctrlc(token: &CancellationToken) -> Result<(), crate::core::Error> {
+    use std::sync::{Mutex, Weak};
+
+    /// Registered tokens; `None` as long as the handler is not installed.
+    static CTRLC_TOKENS: Mutex<Option<Vec<Weak<AtomicBool>>>> = Mutex::new(None);
+
+    let mut tokens = CTRLC_TOKENS.lock().unwrap();
+    let tokens = match tokens.as_mut() {
+        Some(tokens) => tokens,
+        None => {
+            match ctrlc::set_handler(|| {
+                if let Some(tokens) = CTRLC_TOKENS.lock().unwrap().as_mut() {
+                    for token in tokens.drain(..).filter_map(|t| t.upgrade()) {
+                        token.store(true, Ordering::SeqCst);
+                    }
+                }
+            }) {
+                Err(ctrlc::Error::MultipleHandlers) => Ok(()),
+                r => r,
+            }?;
+            tokens.insert(vec![])
+        }
+    };
+    // Forget about tokens which are not in use anymore
+    tokens.retain(|t| t.strong_count() > 0);
+    tokens.push(Arc::downgrade(&token.cancelled));
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    send_sync_test!(cancellation_token, CancellationToken);
+
+    #[test]
+    fn test_cancel() {
+        let token = CancellationToken::new();
+        let clone = token.clone();
+        let other = CancellationToken::new();
+
+        assert!(!token.is_cancelled());
+        assert!(!clone.is_cancelled());
+
+        clone.cancel();
+
+        assert!(token.is_cancelled());
+        assert!(clone.is_cancelled());
+        assert!(!other.is_cancelled());
+    }
+}
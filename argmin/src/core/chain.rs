@@ -0,0 +1,304 @@
+// Copyright 2018-2022 argmin developers
+//
+// Licensed under the Apache License, Version 2.0 <LICENSE-APACHE or
+// http://apache.org/licenses/LICENSE-2.0> or the MIT license <LICENSE-MIT or
+// http://opensource.org/licenses/MIT>, at your option. This file may not be
+// copied, modified, or distributed except according to those terms.
+
+use crate::core::{
+    ArgminFloat, DeserializeOwnedAlias, Error, Executor, IterState, OptimizationResult,
+    SerializeAlias, Solver, State, TerminationReason,
+};
+use std::collections::HashMap;
+
+/// Result of `Chain::then` for `IterState`
+type IterChainResult<O, S, P, G, J, H, F> = Result<Chain<O, S, IterState<P, G, J, H, F>>, Error>;
+
+/// Runs a sequence of solvers on a single problem
+///
+/// A typical use case is a coarse global search (for instance with Particle Swarm Optimization or
+/// Simulated Annealing) followed by a local solver such as L-BFGS or Nelder-Mead which polishes
+/// the result. The first stage is run via [`Chain::start`] from a fully configured [`Executor`].
+/// Each call to [`then`](`Chain::then`) runs another solver on the same problem, starting from
+/// the best parameter vector of the previous stage.
+///
+/// The function evaluation counts of all stages are merged: The problem of
+/// [`result`](`Chain::result`) holds the counts accumulated over all stages and the state of the
+/// final stage reports them via [`get_func_counts`](`crate::core::State::get_func_counts`). The
+/// counts of the individual stages are available in [`stages`](`Chain::stages`).
+///
+/// # Example
+///
+/// ```
+/// # use argmin::core::{Chain, Error, Executor, State};
+/// # use argmin::core::test_utils::{TestSolver, TestProblem};
+/// #
+/// # fn main() -> Result<(), Error> {
+/// # type ParticleSwarm = TestSolver;
+/// # type LBFGS = TestSolver;
+/// # let problem = TestProblem::new();
+/// #
+/// let chain = Chain::start(
+///     Executor::new(problem, ParticleSwarm::new())
+///         .configure(|state| state.param(vec![1.0, 2.0]).max_iters(100)),
+/// )?
+/// // Continue from the best parameter vector found by the first stage
+/// .then(LBFGS::new(), |executor, _previous| {
+///     executor.configure(|state| state.max_iters(50))
+/// })?;
+///
+/// // Result of the final stage
+/// let best_param = chain.result.state().get_best_param();
+///
+/// // Summary of all stages
+/// for stage in chain.stages.iter() {
+///     println!("{}: {} ({} iterations)", stage.solver, stage.best_cost, stage.iterations);
+/// }
+/// # assert_eq!(chain.stages.len(), 2);
+/// # assert_eq!(chain.stages[1].iterations, 50);
+/// # Ok(())
+/// # }
+/// ```
+pub struct Chain<O, S, I: State> {
+    /// Result of the final stage
+    pub result: OptimizationResult<O, S, I>,
+    /// Summary of all stages in the order they were run
+    pub stages: Vec<ChainStage<I::Param, I::Float>>,
+}
+
+impl<O, S, I> Chain<O, S, I>
+where
+    S: Solver<O, I>,
+    I: State + SerializeAlias + DeserializeOwnedAlias,
+    I::Param: Clone,
+{
+    /// Runs the first stage of a chain.
+    ///
+    /// The `executor` is run as configured, including its initial state, observers and
+    /// termination criteria.
+    pub fn start(executor: Executor<O, S, I>) -> Result<Self, Error> {
+        let result = executor.run()?;
+        let stages = vec![ChainStage::new(S::NAME, &result.state)];
+        Ok(Chain { result, stages })
+    }
+}
+
+impl<O, S, I, P, F> Chain<O, S, I>
+where
+    I: State<Param = P, Float = F>,
+    P: Clone,
+    F: ArgminFloat,
+{
+    /// Runs `solver` on the problem, starting from the best parameter vector of the previous
+    /// stage.
+    ///
+    /// The executor of the new stage is passed to `configure` together with the final state of
+    /// the previous stage. The initial parameter vector is already set at this point. `configure`
+    /// can be used to set the state of the new stage (such as the maximum number of iterations),
+    /// to add observers and termination criteria or to hand off further information from the
+    /// previous state, for instance gradient or Hessian estimates if the state types allow it.
+    ///
+    /// The stage runs with a fresh iteration counter and calls `init` of `solver`. Observers which
+    /// should observe several stages can be shared via `Arc<Mutex<_>>`.
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// # use argmin::core::{Chain, Error, Executor, State};
+    /// # use argmin::core::test_utils::{TestSolver, TestProblem};
+    /// #
+    /// # fn main() -> Result<(), Error> {
+    /// # let problem = TestProblem::new();
+    /// # let chain = Chain::start(
+    /// #     Executor::new(problem, TestSolver::new())
+    /// #         .configure(|state| state.param(vec![1.0, 2.0]).max_iters(10)),
+    /// # )?;
+    /// let chain = chain.then(TestSolver::new(), |executor, previous| {
+    ///     // Allow the second stage at most as many iterations as the first one needed
+    ///     let max_iters = previous.get_iter();
+    ///     executor.configure(|state| state.max_iters(max_iters))
+    /// })?;
+    /// # assert_eq!(chain.result.state().get_iter(), 10);
+    /// # Ok(())
+    /// # }
+    /// ```
+    pub fn then<S2, G2, J2, H2, C>(
+        self,
+        solver: S2,
+        configure: C,
+    ) -> IterChainResult<O, S2, P, G2, J2, H2, F>
+    where
+        S2: Solver<O, IterState<P, G2, J2, H2, F>>,
+        IterState<P, G2, J2, H2, F>: SerializeAlias + DeserializeOwnedAlias,
+        C: FnOnce(
+            Executor<O, S2, IterState<P, G2, J2, H2, F>>,
+            &I,
+        ) -> Executor<O, S2, IterState<P, G2, J2, H2, F>>,
+    {
+        let Chain { result, mut stages } = self;
+        let OptimizationResult {
+            mut problem, state, ..
+        } = result;
+
+        let param = state
+            .get_best_param()
+            .cloned()
+            .ok_or_else(argmin_error_closure!(
+                NotInitialized,
+                "`Chain`: Previous stage did not provide a best parameter vector."
+            ))?;
+        let inner = problem.take_problem().ok_or_else(argmin_error_closure!(
+            NotInitialized,
+            "`Chain`: Problem of previous stage is not available."
+        ))?;
+
+        let executor = Executor::new(inner, solver).configure(|s| s.param(param));
+        let mut result = configure(executor, &state).run()?;
+        stages.push(ChainStage::new(S2::NAME, &result.state));
+
+        result.problem.consume_func_counts(problem);
+        result.state.func_counts(&result.problem);
+
+        Ok(Chain { result, stages })
+    }
+}
+
+/// Summary of a single stage of a [`Chain`]
+#[derive(Clone, Debug)]
+pub struct ChainStage<P, F> {
+    /// Name of the solver
+    pub solver: &'static str,
+    /// Best parameter vector
+    pub best_param: Option<P>,
+    /// Best cost function value
+    pub best_cost: F,
+    /// Number of iterations
+    pub iterations: u64,
+    /// Reason for termination
+    pub termination_reason: TerminationReason,
+    /// Function evaluation counts of this stage
+    pub func_counts: HashMap<String, u64>,
+}
+
+impl<P: Clone, F> ChainStage<P, F> {
+    /// Summarizes the final state of a stage.
+    fn new<I: State<Param = P, Float = F>>(solver: &'static str, state: &I) -> Self {
+        ChainStage {
+            solver,
+            best_param: state.get_best_param().cloned(),
+            best_cost: state.get_best_cost(),
+            iterations: state.get_iter(),
+            termination_reason: state.get_termination_reason(),
+            func_counts: state.get_func_counts().clone(),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::core::observers::{Observe, ObserverMode};
+    use crate::core::test_utils::Rosenbrock;
+    use crate::core::KV;
+    use crate::solver::linesearch::MoreThuenteLineSearch;
+    use crate::solver::particleswarm::ParticleSwarm;
+    use crate::solver::quasinewton::LBFGS;
+    use approx::assert_relative_eq;
+    use std::sync::{Arc, Mutex};
+
+    /// Counts the calls of `observe_init` and `observe_iter`
+    #[derive(Default)]
+    struct Counter {
+        init: usize,
+        iter: usize,
+    }
+
+    impl<I> Observe<I> for Counter {
+        fn observe_init(&mut self, _name: &str, _kv: &KV) -> Result<(), Error> {
+            self.init += 1;
+            Ok(())
+        }
+
+        fn observe_iter(&mut self, _state: &I, _kv: &KV) -> Result<(), Error> {
+            self.iter += 1;
+            Ok(())
+        }
+    }
+
+    #[test]
+    fn test_chain() {
+        let counter = Arc::new(Mutex::new(Counter::default()));
+
+        let pso =
+            ParticleSwarm::new((vec![-2.0, -2.0], vec![2.0, 2.0]), 20, 0.5, 0.0, 0.5).unwrap();
+        let lbfgs: LBFGS<_, Vec<f64>, Vec<f64>, f64> = LBFGS::new(MoreThuenteLineSearch::new(), 5);
+
+        let chain = Chain::start(
+            Executor::new(Rosenbrock::new(), pso)
+                .configure(|state| state.max_iters(20))
+                .add_observer(counter.clone(), ObserverMode::Always),
+        )
+        .unwrap()
+        .then(lbfgs, |executor, previous| {
+            assert_eq!(previous.get_iter(), 20);
+            executor
+                .configure(|state| state.max_iters(100))
+                .add_observer(counter.clone(), ObserverMode::Always)
+        })
+        .unwrap();
+
+        assert_eq!(chain.stages.len(), 2);
+        assert_eq!(chain.stages[0].solver, "Particle Swarm Optimization");
+        assert_eq!(chain.stages[1].solver, "L-BFGS");
+        assert_eq!(chain.stages[0].iterations, 20);
+        assert!(chain.stages[1].best_cost <= chain.stages[0].best_cost);
+
+        let best_param = chain.result.state().get_best_param().unwrap();
+        assert_relative_eq!(best_param[0], 1.0, epsilon = 1e-4);
+        assert_relative_eq!(best_param[1], 1.0, epsilon = 1e-4);
+        assert_relative_eq!(
+            chain.stages[1].best_param.as_ref().unwrap()[0],
+            best_param[0]
+        );
+
+        // Function evaluation counts are accumulated over all stages
+        let cost_count = |stage: &ChainStage<Vec<f64>, f64>| stage.func_counts["cost_count"];
+        let total = cost_count(&chain.stages[0]) + cost_count(&chain.stages[1]);
+        assert_eq!(chain.result.problem().counts["cost_count"], total);
+        assert_eq!(chain.result.state().get_func_counts()["cost_count"], total);
+        assert_eq!(
+            chain.result.state().get_func_counts()["gradient_count"],
+            chain.stages[1].func_counts["gradient_count"]
+        );
+
+        // The shared observer observed both stages
+        let counter = counter.lock().unwrap();
+        assert_eq!(counter.init, 2);
+        assert_eq!(
+            counter.iter as u64,
+            chain.stages[0].iterations + chain.stages[1].iterations
+        );
+    }
+
+    #[test]
+    fn test_chain_without_best_param() {
+        let pso = ParticleSwarm::new((vec![-2.0, -2.0], vec![2.0, 2.0]), 5, 0.5, 0.0, 0.5).unwrap();
+        let lbfgs: LBFGS<_, Vec<f64>, Vec<f64>, f64> = LBFGS::new(MoreThuenteLineSearch::new(), 5);
+
+        // No iterations, therefore PSO does not provide a best parameter vector
+        let res = Chain::start(
+            Executor::new(Rosenbrock::new(), pso).configure(|state| state.max_iters(0)),
+        )
+        .unwrap()
+        .then(lbfgs, |executor, _| executor);
+
+        assert_eq!(
+            res.err()
+                .unwrap()
+                .downcast_ref::<crate::core::ArgminError>()
+                .unwrap()
+                .to_string(),
+            "Not initialized: \"`Chain`: Previous stage did not provide a best parameter vector.\""
+        );
+    }
+}
@@ -6,27 +6,50 @@
 // copied, modified, or distributed except according to those terms.
 
 use crate::core::checkpointing::{Checkpoint, CheckpointingFrequency};
-use crate::core::{DeserializeOwnedAlias, Error, SerializeAlias};
+use crate::core::{DeserializeOwnedAlias, Error, SerializeAlias, State};
 use std::default::Default;
 use std::fs::File;
-use std::io::{BufReader, BufWriter};
-use std::path::PathBuf;
+use std::io::{BufReader, Write};
+use std::path::{Path, PathBuf};
+
+/// Magic bytes at the start of each checkpoint file
+const MAGIC: &[u8; 8] = b"ARGMINCP";
+
+/// Length of the header: magic bytes, length of the payload (`u64`) and CRC-32 checksum (`u32`)
+const HEADER_LEN: usize = MAGIC.len() + 8 + 4;
 
 /// Handles saving a checkpoint to disk as a binary file.
+///
+/// Each checkpoint is written to its own file whose name contains the iteration number: With the
+/// filename `optimization.arg`, the checkpoint of iteration 42 is saved as `optimization.42.arg`.
+/// Only the newest [`keep`](`FileCheckpoint::keep`) checkpoints are retained (default: 3), older
+/// ones are deleted.
+///
+/// To avoid corrupting checkpoints when the program crashes while saving, each checkpoint is
+/// first written to a temporary file which is then atomically renamed. In addition, every file
+/// starts with a header containing a CRC-32 checksum of the serialized solver and state. When
+/// loading, the newest checkpoint which passes this check is used, corrupted files are skipped.
+///
+/// Checkpoints written by earlier versions of argmin (a single file `<name>.arg` without header)
+/// are still loaded if no intact checkpoint in the current format exists. New checkpoints are
+/// always written in the current format; the old file is neither overwritten nor deleted.
 #[derive(Clone, Eq, PartialEq, Debug, Hash)]
 pub struct FileCheckpoint {
     /// Indicates how often a checkpoint is created
     pub frequency: CheckpointingFrequency,
     /// Directory where the checkpoints are saved to
     pub directory: PathBuf,
-    /// Name of the checkpoint files
+    /// Name of the checkpoint files; the iteration number is inserted before the extension
     pub filename: PathBuf,
+    /// Number of checkpoints which are retained
+    pub keep: usize,
 }
 
 impl Default for FileCheckpoint {
     /// Create a default `FileCheckpoint` instance.
     ///
-    /// This will save the checkpoint in the file `.checkpoints/checkpoint.arg`.
+    /// This will save the checkpoints in the files `.checkpoints/checkpoint.<iter>.arg` and keep
+    /// the newest 3 of them.
     ///
     /// # Example
     ///
@@ -39,12 +62,14 @@ impl Default for FileCheckpoint {
     /// # assert_eq!(checkpoint.frequency, CheckpointingFrequency::default());
     /// # assert_eq!(checkpoint.directory, PathBuf::from(".checkpoints"));
     /// # assert_eq!(checkpoint.filename, PathBuf::from("checkpoint.arg"));
+    /// # assert_eq!(checkpoint.keep, 3);
     /// ```
     fn default() -> FileCheckpoint {
         FileCheckpoint {
             frequency: CheckpointingFrequency::default(),
             directory: PathBuf::from(".checkpoints"),
             filename: PathBuf::from("checkpoint.arg"),
+            keep: 3,
         }
     }
 }
@@ -62,104 +87,304 @@ impl FileCheckpoint {
     /// let filename = "optimization";
     ///
     /// // When passed to an `Executor`, this will save a checkpoint in the file
-    /// // `checkpoints/optimization.arg` in every iteration.
+    /// // `checkpoints/optimization.<iter>.arg` in every iteration.
     /// let checkpoint = FileCheckpoint::new(directory, filename, CheckpointingFrequency::Always);
     /// # assert_eq!(checkpoint.frequency, CheckpointingFrequency::Always);
     /// # assert_eq!(checkpoint.directory, PathBuf::from("checkpoints"));
     /// # assert_eq!(checkpoint.filename, PathBuf::from("optimization.arg"));
+    /// # assert_eq!(checkpoint.keep, 3);
     /// ```
     pub fn new<N: AsRef<str>>(directory: N, name: N, frequency: CheckpointingFrequency) -> Self {
         FileCheckpoint {
             frequency,
             directory: PathBuf::from(directory.as_ref()),
             filename: PathBuf::from(format!("{}.arg", name.as_ref())),
+            keep: 3,
         }
     }
+
+    /// Set the number of checkpoints which are retained (default: 3).
+    ///
+    /// Keeping more than one checkpoint allows to fall back to an older checkpoint if the newest
+    /// one is corrupted. Must be at least 1.
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// use argmin::core::checkpointing::{FileCheckpoint, CheckpointingFrequency};
+    /// # use argmin::core::Error;
+    ///
+    /// # fn main() -> Result<(), Error> {
+    /// let checkpoint =
+    ///     FileCheckpoint::new("checkpoints", "optimization", CheckpointingFrequency::Always)
+    ///         .keep(5)?;
+    /// # assert_eq!(checkpoint.keep, 5);
+    /// # assert!(FileCheckpoint::default().keep(0).is_err());
+    /// # Ok(())
+    /// # }
+    /// ```
+    pub fn keep(mut self, keep: usize) -> Result<Self, Error> {
+        if keep < 1 {
+            return Err(argmin_error!(
+                InvalidParameter,
+                "`FileCheckpoint`: Number of retained checkpoints must be at least 1."
+            ));
+        }
+        self.keep = keep;
+        Ok(self)
+    }
+
+    /// Returns stem and extension of `filename`
+    fn stem_and_extension(&self) -> (String, String) {
+        let stem = self
+            .filename
+            .file_stem()
+            .map(|s| s.to_string_lossy().into_owned())
+            .unwrap_or_default();
+        let extension = self
+            .filename
+            .extension()
+            .map(|s| s.to_string_lossy().into_owned())
+            .unwrap_or_default();
+        (stem, extension)
+    }
+
+    /// Returns the path of the checkpoint of iteration `iter`
+    fn path(&self, iter: u64) -> PathBuf {
+        let (stem, extension) = self.stem_and_extension();
+        self.directory
+            .join(format!("{}.{}.{}", stem, iter, extension))
+    }
+
+    /// Returns the iteration number if `name` is the name of a checkpoint file
+    fn iteration(&self, name: &str) -> Option<u64> {
+        let (stem, extension) = self.stem_and_extension();
+        name.strip_prefix(&format!("{}.", stem))
+            .and_then(|name| name.strip_suffix(&format!(".{}", extension)))
+            .and_then(|iter| iter.parse::<u64>().ok())
+    }
+
+    /// Returns the paths of all files in `directory` whose names satisfy `filter`
+    fn files<F: Fn(&str) -> bool>(&self, filter: F) -> Result<Vec<PathBuf>, Error> {
+        if !self.directory.is_dir() {
+            return Ok(vec![]);
+        }
+        let mut files = vec![];
+        for entry in std::fs::read_dir(&self.directory)? {
+            let path = entry?.path();
+            if path
+                .file_name()
+                .and_then(|name| name.to_str())
+                .filter(|name| filter(name))
+                .is_some()
+            {
+                files.push(path);
+            }
+        }
+        Ok(files)
+    }
+
+    /// Returns all checkpoint files in `directory` together with their iteration number, sorted
+    /// from newest to oldest.
+    fn checkpoints(&self) -> Result<Vec<(u64, PathBuf)>, Error> {
+        let mut checkpoints: Vec<(u64, PathBuf)> = self
+            .files(|name| self.iteration(name).is_some())?
+            .into_iter()
+            .filter_map(|path| {
+                let iter = self.iteration(path.file_name()?.to_str()?)?;
+                Some((iter, path))
+            })
+            .collect();
+        checkpoints.sort_by_key(|c| std::cmp::Reverse(c.0));
+        Ok(checkpoints)
+    }
+
+    /// Returns all temporary checkpoint files in `directory`, which are left behind if the program
+    /// crashed while saving a checkpoint.
+    fn temporary_files(&self) -> Result<Vec<PathBuf>, Error> {
+        self.files(|name| {
+            name.strip_suffix(".tmp")
+                .and_then(|name| self.iteration(name))
+                .is_some()
+        })
+    }
+}
+
+/// Computes the CRC-32 (IEEE) checksum of `data`
+fn crc32(data: &[u8]) -> u32 {
+    let mut crc = 0xffff_ffffu32;
+    for &byte in data {
+        crc ^= u32::from(byte);
+        for _ in 0..8 {
+            crc = (crc >> 1) ^ (0xedb8_8320 & 0u32.wrapping_sub(crc & 1));
+        }
+    }
+    !crc
+}
+
+/// Writes `payload` with header to `path` via a temporary file which is renamed afterwards
+fn write_atomic(path: &Path, payload: &[u8]) -> Result<(), Error> {
+    let mut tmp = path.as_os_str().to_owned();
+    tmp.push(".tmp");
+    let tmp = PathBuf::from(tmp);
+    {
+        let mut file = File::create(&tmp)?;
+        file.write_all(MAGIC)?;
+        file.write_all(&(payload.len() as u64).to_le_bytes())?;
+        file.write_all(&crc32(payload).to_le_bytes())?;
+        file.write_all(payload)?;
+        file.sync_all()?;
+    }
+    std::fs::rename(&tmp, path)?;
+    // The rename is only persisted once the directory itself is synced. Directories cannot be
+    // opened as files on all platforms, therefore this is limited to Unix.
+    #[cfg(unix)]
+    {
+        let directory = match path.parent() {
+            Some(directory) if !directory.as_os_str().is_empty() => directory,
+            _ => Path::new("."),
+        };
+        File::open(directory)?.sync_all()?;
+    }
+    Ok(())
+}
+
+/// Reads the payload of the checkpoint at `path` and verifies its checksum
+fn read_verified(path: &Path) -> Result<Vec<u8>, Error> {
+    let data = std::fs::read(path)?;
+    if data.len() < HEADER_LEN || &data[..MAGIC.len()] != MAGIC {
+        return Err(argmin_error!(
+            ConditionViolated,
+            format!("{:?} is not a checkpoint file", path)
+        ));
+    }
+    let (header, payload) = data.split_at(HEADER_LEN);
+    let len = u64::from_le_bytes(header[MAGIC.len()..MAGIC.len() + 8].try_into()?);
+    let checksum = u32::from_le_bytes(header[MAGIC.len() + 8..].try_into()?);
+    if payload.len() as u64 != len || crc32(payload) != checksum {
+        return Err(argmin_error!(
+            ConditionViolated,
+            format!("checkpoint {:?} is corrupted", path)
+        ));
+    }
+    Ok(payload.to_vec())
 }
 
 impl<S, I> Checkpoint<S, I> for FileCheckpoint
 where
     S: SerializeAlias + DeserializeOwnedAlias,
-    I: SerializeAlias + DeserializeOwnedAlias,
+    I: State + SerializeAlias + DeserializeOwnedAlias,
 {
     /// Writes checkpoint to disk.
     ///
     /// If the directory does not exist already, it will be created. It uses `bincode` to serialize
-    /// the data.
+    /// the data. The checkpoint is written to a temporary file which is then renamed to
+    /// `<name>.<iter>.arg`. Afterwards, all but the newest [`keep`](`FileCheckpoint::keep`)
+    /// checkpoints are deleted, as well as temporary files left behind by an earlier crash.
     /// It will return an error if creating the directory or file or serialization failed.
     ///
     /// # Example
     ///
     /// ```
     /// use argmin::core::checkpointing::{FileCheckpoint, CheckpointingFrequency, Checkpoint};
+    /// # use argmin::core::{IterState, State};
+    /// # use argmin::core::test_utils::TestSolver;
     ///
-    /// # use std::fs::File;
-    /// # use std::io::BufReader;
-    /// # let checkpoint = FileCheckpoint::new(".checkpoints", "save_test" , CheckpointingFrequency::Always);
-    /// # let solver: u64 = 12;
-    /// # let state: u64 = 21;
-    /// # let _ = std::fs::remove_file(".checkpoints/save_test.arg");
+    /// # let _ = std::fs::remove_dir_all(".checkpoints/save_test");
+    /// let checkpoint =
+    ///     FileCheckpoint::new(".checkpoints/save_test", "save_test", CheckpointingFrequency::Always);
+    /// # let solver = TestSolver::new();
+    /// let mut state: IterState<Vec<f64>, (), (), (), f64> = IterState::new();
+    /// # state.increment_iter();
+    /// # state.increment_iter();
     /// checkpoint.save(&solver, &state);
-    /// # let (f_solver, f_state): (u64, u64) = bincode::deserialize_from(
-    /// #     BufReader::new(File::open(".checkpoints/save_test.arg").unwrap())
-    /// # ).unwrap();
-    /// # assert_eq!(solver, f_solver);
-    /// # assert_eq!(state, f_state);
-    /// # let _ = std::fs::remove_file(".checkpoints/save_test.arg");
+    /// # assert!(std::path::Path::new(".checkpoints/save_test/save_test.2.arg").exists());
+    /// # let _ = std::fs::remove_dir_all(".checkpoints/save_test");
     /// ```
     fn save(&self, solver: &S, state: &I) -> Result<(), Error> {
         if !self.directory.exists() {
             std::fs::create_dir_all(&self.directory)?
         }
-        let fname = self.directory.join(&self.filename);
-        let f = BufWriter::new(File::create(fname)?);
-        bincode::serialize_into(f, &(solver, state))?;
+        let payload = bincode::serialize(&(solver, state))?;
+        write_atomic(&self.path(state.get_iter()), &payload)?;
+        for (_, path) in self.checkpoints()?.iter().skip(self.keep.max(1)) {
+            std::fs::remove_file(path)?;
+        }
+        for path in self.temporary_files()? {
+            std::fs::remove_file(path)?;
+        }
         Ok(())
     }
 
     /// Load a checkpoint from disk.
     ///
+    /// Loads the newest checkpoint whose checksum is valid; corrupted checkpoints are skipped.
+    /// Checkpoints written by earlier versions of argmin (a single file `<name>.arg` without
+    /// checksum) are loaded as a last resort if no intact checkpoint in the current format exists.
     ///
     /// If there is no checkpoint on disk, it will return `Ok(None)`.
-    /// Returns an error if opening the file or deserialization failed.
+    /// Returns an error if none of the checkpoints is intact.
     ///
     /// # Example
     ///
     /// ```
     /// use argmin::core::checkpointing::{FileCheckpoint, CheckpointingFrequency, Checkpoint};
-    /// # use argmin::core::Error;
+    /// # use argmin::core::{Error, IterState, State};
+    /// # use argmin::core::test_utils::TestSolver;
     ///
-    /// # use std::fs::File;
-    /// # use std::io::BufWriter;
     /// # fn main() -> Result<(), Error> {
-    /// # let f = BufWriter::new(File::create(".checkpoints/load_test.arg")?);
-    /// # let f_solver: u64 = 12;
-    /// # let f_state: u64 = 21;
-    /// # bincode::serialize_into(f, &(f_solver, f_state))?;
-    /// # let checkpoint = FileCheckpoint::new(".checkpoints", "load_test" , CheckpointingFrequency::Always);
-    /// let (solver, state) = checkpoint.load()?.unwrap();
-    /// # // Let the compiler know which types to expect.
-    /// # let blah1: u64 = solver;
-    /// # let blah2: u64 = state;
+    /// # let _ = std::fs::remove_dir_all(".checkpoints/load_test");
+    /// let checkpoint =
+    ///     FileCheckpoint::new(".checkpoints/load_test", "load_test", CheckpointingFrequency::Always);
+    /// # let f_solver = TestSolver::new();
+    /// # let f_state: IterState<Vec<f64>, (), (), (), f64> = IterState::new().param(vec![1.0]);
+    /// # checkpoint.save(&f_solver, &f_state)?;
+    /// let (solver, state): (TestSolver, IterState<Vec<f64>, (), (), (), f64>) =
+    ///     checkpoint.load()?.unwrap();
     /// # assert_eq!(solver, f_solver);
-    /// # assert_eq!(state, f_state);
-    /// # let _ = std::fs::remove_file(".checkpoints/load_test.arg");
+    /// # assert_eq!(state.get_param(), f_state.get_param());
+    /// # let _ = std::fs::remove_dir_all(".checkpoints/load_test");
     /// #
     /// # // Return none if File does not exist
     /// # let checkpoint = FileCheckpoint::new(".checkpoints", "certainly_does_not_exist" , CheckpointingFrequency::Always);
-    /// # let loaded: Option<(u64, u64)> = checkpoint.load()?;
+    /// # let loaded: Option<(TestSolver, IterState<Vec<f64>, (), (), (), f64>)> = checkpoint.load()?;
     /// # assert!(loaded.is_none());
     /// # Ok(())
     /// # }
     /// ```
     fn load(&self) -> Result<Option<(S, I)>, Error> {
-        let path = &self.directory.join(&self.filename);
-        if !path.exists() {
+        let checkpoints = self.checkpoints()?;
+        let legacy = self.directory.join(&self.filename);
+        if checkpoints.is_empty() && !legacy.exists() {
             return Ok(None);
         }
-        let file = File::open(path)?;
-        let reader = BufReader::new(file);
-        Ok(Some(bincode::deserialize_from(reader)?))
+
+        let mut errors = vec![];
+        for (_, path) in checkpoints {
+            match read_verified(&path)
+                .and_then(|payload| Ok(bincode::deserialize::<(S, I)>(&payload)?))
+            {
+                Ok(checkpoint) => return Ok(Some(checkpoint)),
+                Err(e) => errors.push(e.to_string()),
+            }
+        }
+        if legacy.exists() {
+            match File::open(&legacy)
+                .map_err(Error::from)
+                .and_then(|file| Ok(bincode::deserialize_from(BufReader::new(file))?))
+            {
+                Ok(checkpoint) => return Ok(Some(checkpoint)),
+                Err(e) => errors.push(format!("legacy checkpoint {:?}: {}", legacy, e)),
+            }
+        }
+        Err(argmin_error!(
+            CheckpointNotFound,
+            format!(
+                "No intact checkpoint found in {:?}: {}",
+                self.directory,
+                errors.join("; ")
+            )
+        ))
     }
 
     /// Returns the how often a checkpoint is to be saved.
@@ -175,17 +400,141 @@ where
 mod tests {
     use super::*;
     use crate::core::test_utils::TestSolver;
-    use crate::core::{IterState, State};
+    use crate::core::IterState;
+
+    type TestState = IterState<Vec<f64>, (), (), (), f64>;
+
+    /// Returns a checkpoint in a fresh directory
+    fn checkpoint(name: &str) -> FileCheckpoint {
+        let directory = std::env::temp_dir().join(format!("argmin_file_checkpoint_{}", name));
+        let _ = std::fs::remove_dir_all(&directory);
+        FileCheckpoint::new(
+            directory.to_str().unwrap(),
+            "solver",
+            CheckpointingFrequency::Always,
+        )
+    }
+
+    /// Saves checkpoints for iterations 1 to `iters`, with the iteration number as parameter
+    fn save(check: &FileCheckpoint, iters: u64) {
+        let mut state = TestState::new();
+        for iter in 1..=iters {
+            state.increment_iter();
+            state = state.param(vec![iter as f64]);
+            check.save_cond(&TestSolver::new(), &state, iter).unwrap();
+        }
+    }
+
+    fn load(check: &FileCheckpoint) -> Result<Option<(TestSolver, TestState)>, Error> {
+        check.load()
+    }
+
+    fn files(check: &FileCheckpoint) -> Vec<String> {
+        let mut files: Vec<String> = std::fs::read_dir(&check.directory)
+            .unwrap()
+            .map(|e| e.unwrap().file_name().to_string_lossy().into_owned())
+            .collect();
+        files.sort();
+        files
+    }
 
     #[test]
-    #[allow(clippy::type_complexity)]
     fn test_save() {
-        let solver = TestSolver::new();
-        let state: IterState<Vec<f64>, (), (), (), f64> = IterState::new().param(vec![1.0f64, 0.0]);
-        let check = FileCheckpoint::new("checkpoints", "solver", CheckpointingFrequency::Always);
-        check.save_cond(&solver, &state, 20).unwrap();
+        let check = checkpoint("save");
+        save(&check, 1);
+        assert_eq!(files(&check), vec!["solver.1.arg"]);
+
+        let (_, state) = load(&check).unwrap().unwrap();
+        assert_eq!(state.get_iter(), 1);
+        assert_eq!(state.get_param(), Some(&vec![1.0]));
+        std::fs::remove_dir_all(&check.directory).unwrap();
+    }
 
-        let _loaded: Option<(TestSolver, IterState<Vec<f64>, (), (), (), f64>)> =
-            check.load().unwrap();
+    #[test]
+    fn test_rotation() {
+        let check = checkpoint("rotation").keep(2).unwrap();
+        save(&check, 5);
+        assert_eq!(files(&check), vec!["solver.4.arg", "solver.5.arg"]);
+
+        let (_, state) = load(&check).unwrap().unwrap();
+        assert_eq!(state.get_iter(), 5);
+        std::fs::remove_dir_all(&check.directory).unwrap();
+    }
+
+    #[test]
+    fn test_fallback_to_intact_checkpoint() {
+        let check = checkpoint("fallback");
+        save(&check, 12);
+        assert_eq!(
+            files(&check),
+            vec!["solver.10.arg", "solver.11.arg", "solver.12.arg"]
+        );
+
+        // Flip a bit in the payload of the newest checkpoint
+        let newest = check.directory.join("solver.12.arg");
+        let mut data = std::fs::read(&newest).unwrap();
+        let last = data.len() - 1;
+        data[last] ^= 1;
+        std::fs::write(&newest, data).unwrap();
+        // Truncate the second newest checkpoint, as if writing it was interrupted
+        let second = check.directory.join("solver.11.arg");
+        let data = std::fs::read(&second).unwrap();
+        std::fs::write(&second, &data[..HEADER_LEN + 3]).unwrap();
+
+        let (_, state) = load(&check).unwrap().unwrap();
+        assert_eq!(state.get_iter(), 10);
+        assert_eq!(state.get_param(), Some(&vec![10.0]));
+
+        // No intact checkpoint left
+        std::fs::write(check.directory.join("solver.10.arg"), b"garbage").unwrap();
+        let err = load(&check).unwrap_err().to_string();
+        assert!(err.starts_with("Checkpoint not found"));
+        assert!(err.contains("corrupted"));
+
+        // An intact legacy checkpoint is used as a last resort
+        let state = TestState::new().param(vec![3.0]);
+        let file = File::create(check.directory.join("solver.arg")).unwrap();
+        bincode::serialize_into(file, &(TestSolver::new(), &state)).unwrap();
+        let (_, loaded) = load(&check).unwrap().unwrap();
+        assert_eq!(loaded.get_param(), Some(&vec![3.0]));
+        std::fs::remove_dir_all(&check.directory).unwrap();
+    }
+
+    #[test]
+    fn test_remove_temporary_files() {
+        let check = checkpoint("temporary");
+        std::fs::create_dir_all(&check.directory).unwrap();
+        // Left behind by a crash while saving
+        std::fs::write(check.directory.join("solver.7.arg.tmp"), b"partial").unwrap();
+        // Not created by this checkpoint
+        std::fs::write(check.directory.join("other.7.arg.tmp"), b"other").unwrap();
+        std::fs::write(check.directory.join("solver.arg.tmp"), b"other").unwrap();
+
+        save(&check, 1);
+        assert_eq!(
+            files(&check),
+            vec!["other.7.arg.tmp", "solver.1.arg", "solver.arg.tmp"]
+        );
+        std::fs::remove_dir_all(&check.directory).unwrap();
+    }
+
+    #[test]
+    fn test_load_legacy() {
+        let check = checkpoint("legacy");
+        assert!(load(&check).unwrap().is_none());
+
+        std::fs::create_dir_all(&check.directory).unwrap();
+        let state = TestState::new().param(vec![3.0]);
+        let file = File::create(check.directory.join("solver.arg")).unwrap();
+        bincode::serialize_into(file, &(TestSolver::new(), &state)).unwrap();
+        let (_, loaded) = load(&check).unwrap().unwrap();
+        assert_eq!(loaded.get_param(), Some(&vec![3.0]));
+        std::fs::remove_dir_all(&check.directory).unwrap();
+    }
+
+    #[test]
+    fn test_crc32() {
+        assert_eq!(crc32(b""), 0);
+        assert_eq!(crc32(b"123456789"), 0xcbf4_3926);
     }
 }
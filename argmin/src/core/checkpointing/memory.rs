@@ -0,0 +1,169 @@
+// Copyright 2018-2022 argmin developers
+//
+// Licensed under the Apache License, Version 2.0 <LICENSE-APACHE or
+// http://apache.org/licenses/LICENSE-2.0> or the MIT license <LICENSE-MIT or
+// http://opensource.org/licenses/MIT>, at your option. This file may not be
+// copied, modified, or distributed except according to those terms.
+
+use crate::core::checkpointing::{Checkpoint, CheckpointingFrequency};
+use crate::core::{DeserializeOwnedAlias, Error, SerializeAlias};
+use std::sync::{Arc, Mutex};
+
+/// Handles saving a checkpoint in memory.
+///
+/// The solver and the state are serialized with `bincode` and kept in memory instead of being
+/// written to disk. This is useful for tests and for environments without access to a file system.
+///
+/// Clones of a `MemoryCheckpoint` share the same storage. A clone can therefore be passed to an
+/// [`Executor`](`crate::core::Executor`) while the original is kept for resuming the optimization
+/// or for inspecting the saved checkpoint.
+///
+/// # Example
+///
+/// ```
+/// use argmin::core::checkpointing::{CheckpointingFrequency, MemoryCheckpoint};
+/// # use argmin::core::{Error, Executor, State};
+/// # use argmin::core::test_utils::{TestSolver, TestProblem};
+///
+/// # fn main() -> Result<(), Error> {
+/// let checkpoint = MemoryCheckpoint::new(CheckpointingFrequency::Always);
+///
+/// // Interrupt the optimization after 3 iterations
+/// let mut stepper = Executor::new(TestProblem::new(), TestSolver::new())
+///     .configure(|state| state.param(vec![1.0f64, 0.0]).max_iters(10))
+///     .checkpointing(checkpoint.clone())
+///     .stepper()?;
+/// for _ in 0..3 {
+///     stepper.step()?;
+/// }
+/// drop(stepper);
+///
+/// // Resume from the checkpoint
+/// let result = Executor::new(TestProblem::new(), TestSolver::new())
+///     .configure(|state| state.param(vec![1.0f64, 0.0]).max_iters(10))
+///     .checkpointing(checkpoint)
+///     .run()?;
+/// # assert_eq!(result.state().get_iter(), 10);
+/// # Ok(())
+/// # }
+/// ```
+#[derive(Clone, Debug, Default)]
+pub struct MemoryCheckpoint {
+    /// Indicates how often a checkpoint is created
+    pub frequency: CheckpointingFrequency,
+    /// Serialized solver and state of the latest checkpoint
+    data: Arc<Mutex<Option<Vec<u8>>>>,
+}
+
+impl MemoryCheckpoint {
+    /// Create a new `MemoryCheckpoint` instance
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// use argmin::core::checkpointing::{CheckpointingFrequency, MemoryCheckpoint};
+    ///
+    /// let checkpoint = MemoryCheckpoint::new(CheckpointingFrequency::Every(10));
+    /// # assert_eq!(checkpoint.frequency, CheckpointingFrequency::Every(10));
+    /// # assert!(checkpoint.bytes().is_none());
+    /// ```
+    pub fn new(frequency: CheckpointingFrequency) -> Self {
+        MemoryCheckpoint {
+            frequency,
+            data: Arc::new(Mutex::new(None)),
+        }
+    }
+
+    /// Returns a copy of the serialized solver and state of the latest checkpoint, or `None` if
+    /// no checkpoint was saved yet.
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// use argmin::core::checkpointing::{Checkpoint, CheckpointingFrequency, MemoryCheckpoint};
+    ///
+    /// let checkpoint = MemoryCheckpoint::new(CheckpointingFrequency::Always);
+    /// # let solver: u64 = 12;
+    /// # let state: u64 = 21;
+    /// checkpoint.save(&solver, &state);
+    /// let bytes = checkpoint.bytes().unwrap();
+    /// # let loaded: (u64, u64) = bincode::deserialize(&bytes).unwrap();
+    /// # assert_eq!(loaded, (12, 21));
+    /// ```
+    pub fn bytes(&self) -> Option<Vec<u8>> {
+        self.data.lock().unwrap().clone()
+    }
+
+    /// Removes the saved checkpoint.
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// use argmin::core::checkpointing::{Checkpoint, CheckpointingFrequency, MemoryCheckpoint};
+    ///
+    /// let checkpoint = MemoryCheckpoint::new(CheckpointingFrequency::Always);
+    /// # let solver: u64 = 12;
+    /// # let state: u64 = 21;
+    /// checkpoint.save(&solver, &state);
+    /// checkpoint.clear();
+    /// assert!(checkpoint.bytes().is_none());
+    /// ```
+    pub fn clear(&self) {
+        *self.data.lock().unwrap() = None;
+    }
+}
+
+impl<S, I> Checkpoint<S, I> for MemoryCheckpoint
+where
+    S: SerializeAlias + DeserializeOwnedAlias,
+    I: SerializeAlias + DeserializeOwnedAlias,
+{
+    /// Serializes `solver` and `state` with `bincode` and replaces the previous checkpoint.
+    fn save(&self, solver: &S, state: &I) -> Result<(), Error> {
+        let bytes = bincode::serialize(&(solver, state))?;
+        *self.data.lock().unwrap() = Some(bytes);
+        Ok(())
+    }
+
+    /// Deserializes the latest checkpoint.
+    ///
+    /// Returns `Ok(None)` if no checkpoint was saved yet.
+    fn load(&self) -> Result<Option<(S, I)>, Error> {
+        match self.data.lock().unwrap().as_ref() {
+            Some(bytes) => Ok(Some(bincode::deserialize(bytes)?)),
+            None => Ok(None),
+        }
+    }
+
+    fn frequency(&self) -> CheckpointingFrequency {
+        self.frequency
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    send_sync_test!(memory_checkpoint, MemoryCheckpoint);
+
+    #[test]
+    fn test_save_load() {
+        let checkpoint = MemoryCheckpoint::new(CheckpointingFrequency::Always);
+        let loaded: Option<(u64, Vec<f64>)> = checkpoint.load().unwrap();
+        assert!(loaded.is_none());
+
+        // Clones share the storage
+        let handle = checkpoint.clone();
+        checkpoint.save(&12u64, &vec![1.0f64, 2.0]).unwrap();
+        let loaded: Option<(u64, Vec<f64>)> = handle.load().unwrap();
+        assert_eq!(loaded, Some((12, vec![1.0, 2.0])));
+
+        checkpoint.save(&13u64, &vec![3.0f64]).unwrap();
+        let loaded: Option<(u64, Vec<f64>)> = handle.load().unwrap();
+        assert_eq!(loaded, Some((13, vec![3.0])));
+
+        handle.clear();
+        let loaded: Option<(u64, Vec<f64>)> = checkpoint.load().unwrap();
+        assert!(loaded.is_none());
+    }
+}
@@ -7,11 +7,16 @@
 
 #[cfg(feature = "serde1")]
 mod file;
+#[cfg(feature = "serde1")]
+mod memory;
 
 #[cfg(feature = "serde1")]
 pub use crate::core::checkpointing::file::FileCheckpoint;
+#[cfg(feature = "serde1")]
+pub use crate::core::checkpointing::memory::MemoryCheckpoint;
 
 use crate::core::Error;
+use instant::Duration;
 use std::default::Default;
 use std::fmt::Display;
 
@@ -77,11 +82,19 @@ pub trait Checkpoint<S, I> {
     /// Calls [`save`](`Checkpoint::save`) in each iteration (`CheckpointingFrequency::Always`),
     /// every X iterations (`CheckpointingFrequency::Every(X)`) or never
     /// (`CheckpointingFrequency::Never`).
+    ///
+    /// The event-based frequencies (`Interval`, `NewBest` and `OnTermination`) are handled by the
+    /// [`Executor`](`crate::core::Executor`), which calls `save` directly when the corresponding
+    /// event occurs.
     fn save_cond(&self, solver: &S, state: &I, iter: u64) -> Result<(), Error> {
         match self.frequency() {
             CheckpointingFrequency::Always => self.save(solver, state)?,
             CheckpointingFrequency::Every(it) if iter % it == 0 => self.save(solver, state)?,
-            CheckpointingFrequency::Never | CheckpointingFrequency::Every(_) => {}
+            CheckpointingFrequency::Never
+            | CheckpointingFrequency::Every(_)
+            | CheckpointingFrequency::Interval(_)
+            | CheckpointingFrequency::NewBest
+            | CheckpointingFrequency::OnTermination => {}
         };
         Ok(())
     }
@@ -99,10 +112,15 @@ pub trait Checkpoint<S, I> {
 
 /// Defines at which intervals a checkpoint is saved.
 ///
+/// Except for `Never`, all variants additionally save a checkpoint once the optimization
+/// terminated, including when it was aborted via CTRL-C. An aborted optimization resumed from
+/// such a checkpoint continues where it stopped.
+///
 /// # Example
 ///
 /// ```
 /// use argmin::core::checkpointing::CheckpointingFrequency;
+/// # use instant::Duration;
 ///
 /// // A checkpoint every 10 iterations
 /// let every_10 = CheckpointingFrequency::Every(10);
@@ -110,6 +128,15 @@ pub trait Checkpoint<S, I> {
 /// // A checkpoint in each iteration
 /// let always = CheckpointingFrequency::Always;
 ///
+/// // A checkpoint at most every 5 minutes
+/// let every_5_minutes = CheckpointingFrequency::Interval(Duration::from_secs(300));
+///
+/// // A checkpoint whenever a new best parameter vector was found
+/// let new_best = CheckpointingFrequency::NewBest;
+///
+/// // A checkpoint only after the optimization terminated
+/// let on_termination = CheckpointingFrequency::OnTermination;
+///
 /// // The default is `CheckpointingFrequency::Never`
 /// assert_eq!(CheckpointingFrequency::default(), CheckpointingFrequency::Never);
 /// ```
@@ -121,6 +148,13 @@ pub enum CheckpointingFrequency {
     Every(u64),
     /// Create checkpoint in every iteration
     Always,
+    /// Create checkpoint after an iteration if at least the given wall-clock time passed since the
+    /// last checkpoint (or the start of the optimization)
+    Interval(Duration),
+    /// Create checkpoint after every iteration which found a new best parameter vector
+    NewBest,
+    /// Create checkpoint only once the optimization terminated
+    OnTermination,
 }
 
 impl Default for CheckpointingFrequency {
@@ -135,6 +169,9 @@ impl Display for CheckpointingFrequency {
             CheckpointingFrequency::Never => write!(f, "Never"),
             CheckpointingFrequency::Every(i) => write!(f, "Every({})", i),
             CheckpointingFrequency::Always => write!(f, "Always"),
+            CheckpointingFrequency::Interval(d) => write!(f, "Interval({:?})", d),
+            CheckpointingFrequency::NewBest => write!(f, "NewBest"),
+            CheckpointingFrequency::OnTermination => write!(f, "OnTermination"),
         }
     }
 }
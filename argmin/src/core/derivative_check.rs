@@ -0,0 +1,591 @@
+// Copyright 2018-2022 argmin developers
+//
+// Licensed under the Apache License, Version 2.0 <LICENSE-APACHE or
+// http://apache.org/licenses/LICENSE-2.0> or the MIT license <LICENSE-MIT or
+// http://opensource.org/licenses/MIT>, at your option. This file may not be
+// copied, modified, or distributed except according to those terms.
+
+use crate::core::finitediff::{scaled_step, step_or_default};
+use crate::core::{ArgminFloat, CostFunction, Error, Gradient, Hessian, Jacobian, Operator, State};
+use argmin_math::{ArgminDot, ArgminElements};
+use std::fmt;
+
+/// Verifies analytic derivatives against finite differences
+///
+/// Compares the gradient of a [`CostFunction`], the Jacobian of an [`Operator`] or the Hessian
+/// of a [`Gradient`] with central finite difference approximations at one or more points and
+/// returns a [`DerivativeReport`] which lists the error of every single component.
+///
+/// For each component, the error is computed as `|analytic - approx| / max(1, |approx|)`, i.e.
+/// it is an absolute error for small and a relative error for large derivatives. A component
+/// passes the check if its error does not exceed the tolerance (default: `1e-6`).
+///
+/// The relative step size defaults to `eps^(1/3)`, with `eps` being the machine epsilon of the
+/// float type, and is scaled with the magnitude of each parameter as in
+/// [`FiniteDiff`](`crate::core::FiniteDiff`).
+///
+/// Besides being used directly (for instance in unit tests), checks can be performed by the
+/// [`Executor`](`crate::core::Executor`) at the initial parameter vector before the first
+/// iteration via [`check_gradient`](`crate::core::Executor::check_gradient`),
+/// [`check_jacobian`](`crate::core::Executor::check_jacobian`) and
+/// [`check_hessian`](`crate::core::Executor::check_hessian`).
+///
+/// # Example
+///
+/// ```
+/// use argmin::core::{CostFunction, DerivativeCheck, Error, Gradient};
+///
+/// struct Paraboloid {}
+///
+/// impl CostFunction for Paraboloid {
+///     type Param = Vec<f64>;
+///     type Output = f64;
+///
+///     fn cost(&self, param: &Self::Param) -> Result<Self::Output, Error> {
+///         Ok(param[0].powi(2) + 2.0 * param[1].powi(2))
+///     }
+/// }
+///
+/// impl Gradient for Paraboloid {
+///     type Param = Vec<f64>;
+///     type Gradient = Vec<f64>;
+///
+///     fn gradient(&self, param: &Self::Param) -> Result<Self::Gradient, Error> {
+///         // Wrong derivative with respect to the second parameter
+///         Ok(vec![2.0 * param[0], 2.0 * param[1]])
+///     }
+/// }
+///
+/// let report = DerivativeCheck::new()
+///     .check_gradient(&Paraboloid {}, &[vec![1.0, 1.0], vec![-2.0, 0.5]])?;
+///
+/// assert!(!report.passed());
+/// for failure in report.failures() {
+///     assert_eq!(failure.row, 1);
+/// }
+/// # assert_eq!(report.failures().count(), 2);
+/// # assert_eq!(report.components.len(), 4);
+/// # Ok::<(), Error>(())
+/// ```
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct DerivativeCheck {
+    /// Maximum admissible error per component
+    tolerance: f64,
+    /// Relative step size of the finite differences
+    step_size: Option<f64>,
+}
+
+impl Default for DerivativeCheck {
+    fn default() -> Self {
+        DerivativeCheck::new()
+    }
+}
+
+impl DerivativeCheck {
+    /// Constructs a new `DerivativeCheck` with a tolerance of `1e-6`.
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// use argmin::core::DerivativeCheck;
+    ///
+    /// let check = DerivativeCheck::new();
+    /// ```
+    pub fn new() -> Self {
+        DerivativeCheck {
+            tolerance: 1e-6,
+            step_size: None,
+        }
+    }
+
+    /// Sets the maximum admissible error per component.
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// use argmin::core::DerivativeCheck;
+    ///
+    /// let check = DerivativeCheck::new().with_tolerance(1e-4);
+    /// ```
+    #[must_use]
+    pub fn with_tolerance(mut self, tolerance: f64) -> Self {
+        self.tolerance = tolerance;
+        self
+    }
+
+    /// Sets a fixed relative step size for the finite differences.
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// use argmin::core::DerivativeCheck;
+    ///
+    /// let check = DerivativeCheck::new().with_step_size(1e-5);
+    /// ```
+    #[must_use]
+    pub fn with_step_size(mut self, step_size: f64) -> Self {
+        self.step_size = Some(step_size);
+        self
+    }
+
+    /// Compares the gradient of `problem` with finite differences of its cost function at all
+    /// `points`.
+    ///
+    /// Component `i` of the gradient at point `p` is reported with `point == p`, `row == i` and
+    /// `col == 0`.
+    pub fn check_gradient<O, P, F>(
+        &self,
+        problem: &O,
+        points: &[P],
+    ) -> Result<DerivativeReport, Error>
+    where
+        O: CostFunction<Param = P, Output = F> + Gradient<Param = P, Gradient = P>,
+        P: ArgminElements<Element = F>,
+        F: ArgminFloat,
+    {
+        let mut report = DerivativeReport::new(self.tolerance);
+        for (point, param) in points.iter().enumerate() {
+            let grad = problem.gradient(param)?;
+            for i in 0..param.num_elements() {
+                let h = self.step(param, i);
+                let fp = problem.cost(&param.add_to_element(i, h))?;
+                let fm = problem.cost(&param.add_to_element(i, -h))?;
+                let approx = (fp - fm) / (h + h);
+                report.push(point, i, 0, grad.get_element(i), approx);
+            }
+        }
+        Ok(report)
+    }
+
+    /// Compares the Jacobian of `problem` with finite differences of its operator at all
+    /// `points`.
+    ///
+    /// The derivative of output `i` with respect to parameter `j` at point `p` is reported with
+    /// `point == p`, `row == i` and `col == j`. The columns of the Jacobian are obtained by
+    /// multiplying it with unit vectors, therefore the Jacobian type is required to implement
+    /// [`ArgminDot`](`argmin_math::ArgminDot`).
+    pub fn check_jacobian<O, P, V, J, F>(
+        &self,
+        problem: &O,
+        points: &[P],
+    ) -> Result<DerivativeReport, Error>
+    where
+        O: Operator<Param = P, Output = V> + Jacobian<Param = P, Jacobian = J>,
+        P: ArgminElements<Element = F>,
+        V: ArgminElements<Element = F>,
+        J: ArgminDot<P, V>,
+        F: ArgminFloat,
+    {
+        let mut report = DerivativeReport::new(self.tolerance);
+        for (point, param) in points.iter().enumerate() {
+            let jacobian = problem.jacobian(param)?;
+            for j in 0..param.num_elements() {
+                let column = jacobian.dot(&unit_vector(param, j));
+                let h = self.step(param, j);
+                let rp = problem.apply(&param.add_to_element(j, h))?;
+                let rm = problem.apply(&param.add_to_element(j, -h))?;
+                for i in 0..column.num_elements() {
+                    let approx = (rp.get_element(i) - rm.get_element(i)) / (h + h);
+                    report.push(point, i, j, column.get_element(i), approx);
+                }
+            }
+        }
+        Ok(report)
+    }
+
+    /// Compares the Hessian of `problem` with finite differences of its gradient at all
+    /// `points`.
+    ///
+    /// The second derivative with respect to parameters `i` and `j` at point `p` is reported
+    /// with `point == p`, `row == i` and `col == j`. The columns of the Hessian are obtained by
+    /// multiplying it with unit vectors, therefore the Hessian type is required to implement
+    /// [`ArgminDot`](`argmin_math::ArgminDot`).
+    pub fn check_hessian<O, P, H, F>(
+        &self,
+        problem: &O,
+        points: &[P],
+    ) -> Result<DerivativeReport, Error>
+    where
+        O: Gradient<Param = P, Gradient = P> + Hessian<Param = P, Hessian = H>,
+        P: ArgminElements<Element = F>,
+        H: ArgminDot<P, P>,
+        F: ArgminFloat,
+    {
+        let mut report = DerivativeReport::new(self.tolerance);
+        for (point, param) in points.iter().enumerate() {
+            let hessian = problem.hessian(param)?;
+            for j in 0..param.num_elements() {
+                let column = hessian.dot(&unit_vector(param, j));
+                let h = self.step(param, j);
+                let gp = problem.gradient(&param.add_to_element(j, h))?;
+                let gm = problem.gradient(&param.add_to_element(j, -h))?;
+                for i in 0..column.num_elements() {
+                    let approx = (gp.get_element(i) - gm.get_element(i)) / (h + h);
+                    report.push(point, i, j, column.get_element(i), approx);
+                }
+            }
+        }
+        Ok(report)
+    }
+
+    /// Step size for parameter `i`.
+    fn step<P, F>(&self, param: &P, i: usize) -> F
+    where
+        P: ArgminElements<Element = F>,
+        F: ArgminFloat,
+    {
+        scaled_step(step_or_default(self.step_size, 3.0), param.get_element(i))
+    }
+
+    /// Checks the gradient at the parameter vector of `state`.
+    pub(crate) fn check_gradient_at<O, I, P, F>(&self, problem: &O, state: &I) -> Result<(), Error>
+    where
+        O: CostFunction<Param = P, Output = F> + Gradient<Param = P, Gradient = P>,
+        I: State<Param = P>,
+        P: ArgminElements<Element = F>,
+        F: ArgminFloat,
+    {
+        self.check_gradient(problem, std::slice::from_ref(initial_param(state)?))?
+            .ensure_passed()
+    }
+
+    /// Checks the Jacobian at the parameter vector of `state`.
+    pub(crate) fn check_jacobian_at<O, I, P, V, J, F>(
+        &self,
+        problem: &O,
+        state: &I,
+    ) -> Result<(), Error>
+    where
+        O: Operator<Param = P, Output = V> + Jacobian<Param = P, Jacobian = J>,
+        I: State<Param = P>,
+        P: ArgminElements<Element = F>,
+        V: ArgminElements<Element = F>,
+        J: ArgminDot<P, V>,
+        F: ArgminFloat,
+    {
+        self.check_jacobian(problem, std::slice::from_ref(initial_param(state)?))?
+            .ensure_passed()
+    }
+
+    /// Checks the Hessian at the parameter vector of `state`.
+    pub(crate) fn check_hessian_at<O, I, P, H, F>(
+        &self,
+        problem: &O,
+        state: &I,
+    ) -> Result<(), Error>
+    where
+        O: Gradient<Param = P, Gradient = P> + Hessian<Param = P, Hessian = H>,
+        I: State<Param = P>,
+        P: ArgminElements<Element = F>,
+        H: ArgminDot<P, P>,
+        F: ArgminFloat,
+    {
+        self.check_hessian(problem, std::slice::from_ref(initial_param(state)?))?
+            .ensure_passed()
+    }
+}
+
+/// Returns the parameter vector of `state` or an error if there is none.
+fn initial_param<I: State>(state: &I) -> Result<&I::Param, Error> {
+    state.get_param().ok_or_else(argmin_error_closure!(
+        NotInitialized,
+        "Derivative check requires an initial parameter vector."
+    ))
+}
+
+/// Returns the `i`th unit vector of the same dimension as `param`.
+fn unit_vector<P, F>(param: &P, i: usize) -> P
+where
+    P: ArgminElements<Element = F>,
+    F: ArgminFloat,
+{
+    P::from_elements(
+        (0..param.num_elements())
+            .map(|j| if i == j { F::one() } else { F::zero() })
+            .collect(),
+    )
+}
+
+/// Comparison of a single component of an analytic derivative with its approximation
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct ComponentError {
+    /// Index of the point in the slice of points the derivatives were checked at
+    pub point: usize,
+    /// Row of the component (index of the gradient element or of the output of an operator)
+    pub row: usize,
+    /// Column of the component (index of the parameter; always `0` for gradients)
+    pub col: usize,
+    /// Analytic derivative
+    pub analytic: f64,
+    /// Finite difference approximation
+    pub approx: f64,
+    /// Error `|analytic - approx| / max(1, |approx|)`
+    pub error: f64,
+    /// Whether the error is within the tolerance
+    pub passed: bool,
+}
+
+/// Result of a [`DerivativeCheck`]
+///
+/// Holds a [`ComponentError`] for every component of the derivatives at every point.
+#[derive(Clone, Debug, PartialEq)]
+pub struct DerivativeReport {
+    /// Errors of all checked components
+    pub components: Vec<ComponentError>,
+    /// Maximum admissible error per component
+    pub tolerance: f64,
+}
+
+impl DerivativeReport {
+    /// Constructs an empty report.
+    fn new(tolerance: f64) -> Self {
+        DerivativeReport {
+            components: vec![],
+            tolerance,
+        }
+    }
+
+    /// Adds a component to the report.
+    fn push<F: ArgminFloat>(
+        &mut self,
+        point: usize,
+        row: usize,
+        col: usize,
+        analytic: F,
+        approx: F,
+    ) {
+        let analytic = analytic.to_f64().unwrap();
+        let approx = approx.to_f64().unwrap();
+        let error = (analytic - approx).abs() / approx.abs().max(1.0);
+        self.components.push(ComponentError {
+            point,
+            row,
+            col,
+            analytic,
+            approx,
+            error,
+            // Written this way such that NaNs fail the check
+            passed: error <= self.tolerance,
+        });
+    }
+
+    /// Returns `true` if all components are within the tolerance.
+    pub fn passed(&self) -> bool {
+        self.components.iter().all(|c| c.passed)
+    }
+
+    /// Returns an iterator over all components which exceed the tolerance.
+    pub fn failures(&self) -> impl Iterator<Item = &ComponentError> {
+        self.components.iter().filter(|c| !c.passed)
+    }
+
+    /// Returns the component with the largest error (`None` if the report is empty).
+    pub fn max_error(&self) -> Option<&ComponentError> {
+        self.components
+            .iter()
+            .max_by(|a, b| a.error.total_cmp(&b.error))
+    }
+
+    /// Returns an error listing all failed components if the check did not pass.
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// # use argmin::core::{DerivativeCheck, Error};
+    /// # use argmin::core::test_utils::TestProblem;
+    /// #
+    /// // `TestProblem` returns wrong gradients
+    /// let report = DerivativeCheck::new().check_gradient(&TestProblem::new(), &[vec![1.0, 2.0]])?;
+    ///
+    /// assert!(report.ensure_passed().is_err());
+    /// # Ok::<(), Error>(())
+    /// ```
+    pub fn ensure_passed(&self) -> Result<(), Error> {
+        if self.passed() {
+            Ok(())
+        } else {
+            Err(argmin_error!(ConditionViolated, self.to_string()))
+        }
+    }
+}
+
+impl fmt::Display for DerivativeReport {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let failures = self.failures().count();
+        write!(
+            f,
+            "{} of {} derivative components exceed the tolerance of {}",
+            failures,
+            self.components.len(),
+            self.tolerance
+        )?;
+        for c in self.failures() {
+            write!(
+                f,
+                "\n  point {}, ({}, {}): analytic {}, approx {}, error {}",
+                c.point, c.row, c.col, c.analytic, c.approx, c.error
+            )?;
+        }
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use approx::assert_relative_eq;
+
+    /// `f(x) = x0^2 + 3 x0 x1 + 2 x1^3` with a configurable error in the derivatives
+    struct Polynomial {
+        /// Added to the derivatives with respect to `x1`
+        offset: f64,
+    }
+
+    impl CostFunction for Polynomial {
+        type Param = Vec<f64>;
+        type Output = f64;
+
+        fn cost(&self, p: &Self::Param) -> Result<Self::Output, Error> {
+            Ok(p[0].powi(2) + 3.0 * p[0] * p[1] + 2.0 * p[1].powi(3))
+        }
+    }
+
+    impl Gradient for Polynomial {
+        type Param = Vec<f64>;
+        type Gradient = Vec<f64>;
+
+        fn gradient(&self, p: &Self::Param) -> Result<Self::Gradient, Error> {
+            Ok(vec![
+                2.0 * p[0] + 3.0 * p[1],
+                3.0 * p[0] + 6.0 * p[1].powi(2) + self.offset,
+            ])
+        }
+    }
+
+    impl Hessian for Polynomial {
+        type Param = Vec<f64>;
+        type Hessian = Vec<Vec<f64>>;
+
+        fn hessian(&self, p: &Self::Param) -> Result<Self::Hessian, Error> {
+            Ok(vec![vec![2.0, 3.0], vec![3.0, 12.0 * p[1] + self.offset]])
+        }
+    }
+
+    impl Operator for Polynomial {
+        type Param = Vec<f64>;
+        type Output = Vec<f64>;
+
+        fn apply(&self, p: &Self::Param) -> Result<Self::Output, Error> {
+            Ok(vec![p[0] * p[1], p[0].powi(2) + p[1], 5.0])
+        }
+    }
+
+    impl Jacobian for Polynomial {
+        type Param = Vec<f64>;
+        type Jacobian = Vec<Vec<f64>>;
+
+        fn jacobian(&self, p: &Self::Param) -> Result<Self::Jacobian, Error> {
+            Ok(vec![
+                vec![p[1], p[0]],
+                vec![2.0 * p[0], 1.0 + self.offset],
+                vec![0.0, 0.0],
+            ])
+        }
+    }
+
+    fn points() -> Vec<Vec<f64>> {
+        vec![vec![1.5, -2.0], vec![0.0, 0.0], vec![-3.0, 10.0]]
+    }
+
+    send_sync_test!(derivative_check, DerivativeCheck);
+    send_sync_test!(derivative_report, DerivativeReport);
+
+    #[test]
+    fn test_correct_derivatives() {
+        let problem = Polynomial { offset: 0.0 };
+        let check = DerivativeCheck::new();
+
+        let report = check.check_gradient(&problem, &points()).unwrap();
+        assert!(report.passed());
+        assert_eq!(report.components.len(), 6);
+        assert!(report.ensure_passed().is_ok());
+        assert_eq!(report.failures().count(), 0);
+
+        let report = check.check_jacobian(&problem, &points()).unwrap();
+        assert!(report.passed());
+        assert_eq!(report.components.len(), 18);
+
+        let report = check.check_hessian(&problem, &points()).unwrap();
+        assert!(report.passed());
+        assert_eq!(report.components.len(), 12);
+    }
+
+    #[test]
+    fn test_wrong_derivatives() {
+        let problem = Polynomial { offset: 1e-2 };
+        let check = DerivativeCheck::new();
+
+        for report in [
+            check.check_gradient(&problem, &points()).unwrap(),
+            check.check_jacobian(&problem, &points()).unwrap(),
+            check.check_hessian(&problem, &points()).unwrap(),
+        ] {
+            assert!(!report.passed());
+            assert!(report.ensure_passed().is_err());
+            // one wrong component per point
+            assert_eq!(report.failures().count(), 3);
+            for (point, failure) in report.failures().enumerate() {
+                assert_eq!(failure.point, point);
+                assert_eq!(failure.row, 1);
+                assert_eq!(
+                    failure.col,
+                    if report.components.len() == 6 { 0 } else { 1 }
+                );
+                assert!(!failure.passed);
+                assert_relative_eq!(failure.analytic - failure.approx, 1e-2, epsilon = 1e-6);
+            }
+        }
+
+        // The error is relative for large derivatives
+        let report = check.check_gradient(&problem, &points()).unwrap();
+        assert_relative_eq!(
+            report.failures().next().unwrap().error,
+            1e-2 / 28.5,
+            epsilon = 1e-8
+        );
+        assert_relative_eq!(
+            report.failures().nth(1).unwrap().error,
+            1e-2,
+            epsilon = 1e-8
+        );
+        assert_eq!(report.max_error().unwrap().point, 1);
+
+        // A larger tolerance accepts the error
+        let report = check
+            .with_tolerance(1e-1)
+            .check_gradient(&problem, &points())
+            .unwrap();
+        assert!(report.passed());
+    }
+
+    #[test]
+    fn test_report_display() {
+        let problem = Polynomial { offset: 1.0 };
+        let report = DerivativeCheck::new()
+            .check_gradient(&problem, &[vec![0.0, 0.0]])
+            .unwrap();
+        let text = report.to_string();
+        assert!(text.starts_with("1 of 2 derivative components exceed the tolerance of 0.000001"));
+        assert!(text.contains("point 0, (1, 0): analytic 1, approx "));
+    }
+
+    #[test]
+    fn test_empty() {
+        let problem = Polynomial { offset: 1.0 };
+        let report = DerivativeCheck::new()
+            .check_gradient(&problem, &[])
+            .unwrap();
+        assert!(report.passed());
+        assert!(report.max_error().is_none());
+    }
+}
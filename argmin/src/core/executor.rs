@@ -5,15 +5,19 @@
 // http://opensource.org/licenses/MIT>, at your option. This file may not be
 // copied, modified, or distributed except according to those terms.
 
-use crate::core::checkpointing::Checkpoint;
-use crate::core::observers::{Observe, ObserverMode, Observers};
+use crate::core::checkpointing::{Checkpoint, CheckpointingFrequency};
+use crate::core::derivative_check::DerivativeCheck;
+use crate::core::history::{Record, Recorder};
+use crate::core::observers::{Observe, ObserverErrorPolicy, ObserverMode, Observers};
+use crate::core::termination_criteria::TerminationCriterion;
 use crate::core::{
-    DeserializeOwnedAlias, Error, OptimizationResult, Problem, SerializeAlias, Solver, State,
-    TerminationReason, KV,
+    ArgminFloat, CancellationToken, CostFunction, DeserializeOwnedAlias, Error, Gradient, Hessian,
+    History, Jacobian, Operator, OptimizationResult, Problem, SerializeAlias, Solver, State,
+    TerminationReason,
 };
+use argmin_math::{ArgminDot, ArgminElements};
 use instant;
-use std::sync::atomic::{AtomicBool, Ordering};
-use std::sync::Arc;
+use std::collections::HashMap;
 
 /// Solves an optimization problem with a solver
 pub struct Executor<O, S, I> {
@@ -27,12 +31,29 @@ pub struct Executor<O, S, I> {
     observers: Observers<I>,
     /// Checkpoint
     checkpoint: Option<Box<dyn Checkpoint<S, I>>>,
+    /// Time at which the last checkpoint was saved (or the optimization was started)
+    checkpoint_time: instant::Instant,
     /// Indicates whether Ctrl-C functionality should be active or not
     ctrlc: bool,
+    /// Token used to cancel the optimization
+    cancellation_token: CancellationToken,
     /// Indicates whether to time execution or not
     timer: bool,
+    /// Maximum wall-clock time the optimization is allowed to run
+    timeout: Option<instant::Duration>,
+    /// Maximum number of evaluations per function of the problem
+    max_func_counts: HashMap<String, u64>,
+    /// Additional termination criteria
+    termination_criteria: Vec<Box<dyn TerminationCriterion<I>>>,
+    /// Derivative checks performed before the first iteration
+    derivative_checks: Vec<(DerivativeCheck, DerivativeCheckFn<O, I>)>,
+    /// Convergence history (only if recording is enabled)
+    history: Option<Box<dyn Record<I>>>,
 }
 
+/// Performs a derivative check of a problem at the parameter vector of a state
+type DerivativeCheckFn<O, I> = fn(&DerivativeCheck, &O, &I) -> Result<(), Error>;
+
 impl<O, S, I> Executor<O, S, I>
 where
     S: Solver<O, I>,
@@ -60,15 +81,81 @@ where
     /// let executor = Executor::new(problem, solver);
     /// ```
     pub fn new(problem: O, solver: S) -> Self {
-        let state = Some(I::new());
-        Executor {
+        Executor::from_parts(Problem::new(problem), solver, I::new())
+    }
+
+    /// Constructs an `Executor` which continues the optimization of a previous run.
+    ///
+    /// Problem, solver and state are taken from `result`. The iteration counter and the function
+    /// evaluation counts continue from where the previous run stopped and the internal memory of
+    /// the solver (such as the history of L-BFGS, the inverse Hessian of BFGS or the simplex of
+    /// Nelder-Mead) is reused. The termination reason is reset and `init` of the solver is not
+    /// called again, unless the previous run stopped before the first iteration.
+    ///
+    /// If the history of the previous run was recorded, recording continues. Observers,
+    /// checkpointing and all other settings of the previous `Executor` are not part of the result
+    /// and need to be configured again. Typically the maximum number of iterations is
+    /// increased via [`configure`](`Executor::configure`), otherwise the optimization terminates
+    /// immediately. Solver-specific tolerances can be adapted via the public `solver` field of
+    /// `result` before calling this method, as far as the solver provides setters for them.
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// # use argmin::core::{Error, Executor, State};
+    /// # use argmin::core::test_utils::{TestSolver, TestProblem};
+    /// #
+    /// # fn main() -> Result<(), Error> {
+    /// # let solver = TestSolver::new();
+    /// # let problem = TestProblem::new();
+    /// #
+    /// let result = Executor::new(problem, solver)
+    ///     .configure(|state| state.param(vec![1.0f64, 0.0]).max_iters(10))
+    ///     .run()?;
+    ///
+    /// // Continue for another 10 iterations
+    /// let result = Executor::from_result(result)
+    ///     .configure(|state| state.max_iters(20))
+    ///     .run()?;
+    /// # assert_eq!(result.state().get_iter(), 20);
+    /// # Ok(())
+    /// # }
+    /// ```
+    pub fn from_result(result: OptimizationResult<O, S, I>) -> Self
+    where
+        I::Param: 'static,
+    {
+        let OptimizationResult {
+            problem,
             solver,
-            problem: Problem::new(problem),
             state,
+            history,
+            ..
+        } = result;
+        let state = state.termination_reason(TerminationReason::NotTerminated);
+        let mut executor = Executor::from_parts(problem, solver, state);
+        executor.history =
+            history.map(|history| -> Box<dyn Record<I>> { Box::new(Recorder::new(history)) });
+        executor
+    }
+
+    /// Constructs an `Executor` with default settings from a problem, a solver and a state.
+    fn from_parts(problem: Problem<O>, solver: S, state: I) -> Self {
+        Executor {
+            solver,
+            problem,
+            state: Some(state),
             observers: Observers::new(),
             checkpoint: None,
+            checkpoint_time: instant::Instant::now(),
             ctrlc: true,
+            cancellation_token: CancellationToken::new(),
             timer: true,
+            timeout: None,
+            max_func_counts: HashMap::new(),
+            termination_criteria: vec![],
+            derivative_checks: vec![],
+            history: None,
         }
     }
 
@@ -126,49 +213,91 @@ where
     /// # Ok(())
     /// # }
     /// ```
-    pub fn run(mut self) -> Result<OptimizationResult<O, S, I>, Error> {
+    pub fn run(self) -> Result<OptimizationResult<O, S, I>, Error> {
+        let mut stepper = self.stepper()?;
+        while stepper.step()? {}
+        stepper.finish()
+    }
+
+    /// Initializes the executor and returns a [`Stepper`] which allows one to perform the
+    /// optimization one iteration at a time.
+    ///
+    /// This loads the checkpoint (if configured), sets up CTRL-C handling (if enabled) and calls
+    /// the `init` method of the solver. Iterations are then performed via [`Stepper::step`] or by
+    /// using the `Stepper` as an [`Iterator`] over the intermediate states. Observers,
+    /// checkpointing and all termination criteria behave exactly as with [`Executor::run`].
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// # use argmin::core::{Error, Executor, State};
+    /// # use argmin::core::test_utils::{TestSolver, TestProblem};
+    /// #
+    /// # fn main() -> Result<(), Error> {
+    /// # let solver = TestSolver::new();
+    /// # let problem = TestProblem::new();
+    /// #
+    /// # let init_param = vec![1.0f64, 0.0];
+    /// #
+    /// let mut stepper = Executor::new(problem, solver)
+    ///     .configure(|state| state.param(init_param).max_iters(10))
+    ///     .stepper()?;
+    ///
+    /// // Perform a single iteration
+    /// stepper.step()?;
+    /// assert_eq!(stepper.state().get_iter(), 1);
+    ///
+    /// // Perform the remaining iterations
+    /// while stepper.step()? {
+    ///     // Inspect or log intermediate states here
+    /// }
+    ///
+    /// let result = stepper.finish()?;
+    /// assert_eq!(result.state().get_iter(), 10);
+    /// # Ok(())
+    /// # }
+    /// ```
+    pub fn stepper(mut self) -> Result<Stepper<O, S, I>, Error> {
         // First, load checkpoint if given.
         if let Some(checkpoint) = self.checkpoint.as_ref() {
             if let Some((solver, state)) = checkpoint.load()? {
+                // An optimization which was aborted or timed out continues where it stopped.
+                let state = match state.get_termination_reason() {
+                    TerminationReason::Aborted | TerminationReason::Timeout => {
+                        state.termination_reason(TerminationReason::NotTerminated)
+                    }
+                    _ => state,
+                };
                 self.state = Some(state);
                 self.solver = solver;
             }
         }
-        let total_time = if self.timer {
-            Some(instant::Instant::now())
-        } else {
-            None
-        };
+        let total_time = instant::Instant::now();
+        self.checkpoint_time = total_time;
 
         let state = self.state.take().unwrap();
 
-        let running = Arc::new(AtomicBool::new(true));
+        if let Some(history) = self.history.as_mut() {
+            history.start(&state);
+        }
 
         if self.ctrlc {
+            // Cancel the optimization on CTRL-C
             #[cfg(feature = "ctrlc")]
-            {
-                // Set up the Ctrl-C handler
-                let r = running.clone();
-                // This is currently a hack to allow checkpoints to be run again within the
-                // same program (usually not really a usecase anyway). Unfortunately, this
-                // means that any subsequent run started afterwards will have not Ctrl-C
-                // handling available... This should also be a problem in case one tries to run
-                // two consecutive optimizations. There is ongoing work in the ctrlc crate
-                // (channels and such) which may solve this problem. So far, we have to live
-                // with this.
-                match ctrlc::set_handler(move || {
-                    r.store(false, Ordering::SeqCst);
-                }) {
-                    Err(ctrlc::Error::MultipleHandlers) => Ok(()),
-                    r => r,
-                }?;
-            }
+            crate::core::cancellation::cancel_on_ctrlc(&self.cancellation_token)?;
         }
 
         // Only call `init` of `solver` if the current iteration number is 0. This avoids that
         // `init` is called when starting from a checkpoint (because `init` could change the state
         // of the `solver`, which would overwrite the state restored from the checkpoint).
-        let mut state = if state.get_iter() == 0 {
+        let state = if state.get_iter() == 0 {
+            // Verify the derivatives at the initial parameter vector
+            if let Some(problem) = self.problem.problem.as_ref() {
+                for (check, check_fn) in self.derivative_checks.iter() {
+                    check_fn(check, problem, &state)?;
+                }
+            }
+
             let (mut state, kv) = self.solver.init(&mut self.problem, state)?;
             state.update();
 
@@ -189,81 +318,121 @@ where
             state
         };
 
-        while running.load(Ordering::SeqCst) {
-            // check first if it has already terminated
-            // This should probably be solved better.
-            // First, check if it isn't already terminated. If it isn't, evaluate the
-            // stopping criteria. If `self.terminate()` is called without the checking
-            // whether it has terminated already, then it may overwrite a termination set
-            // within `next_iter()`!
-            state = if !state.terminated() {
-                let term = self.solver.terminate_internal(&state);
-                state.termination_reason(term)
-            } else {
-                state
-            };
-            // Now check once more if the algorithm has terminated. If yes, then break.
-            if state.terminated() {
-                break;
+        Ok(Stepper {
+            executor: self,
+            state: Some(state),
+            total_time,
+        })
+    }
+
+    /// Evaluates all stopping criteria and sets the termination reason of `state` accordingly.
+    fn check_termination(&mut self, mut state: I, total_time: instant::Instant) -> I {
+        // check first if it has already terminated
+        // This should probably be solved better.
+        // First, check if it isn't already terminated. If it isn't, evaluate the
+        // stopping criteria. If `self.terminate()` is called without the checking
+        // whether it has terminated already, then it may overwrite a termination set
+        // within `next_iter()`!
+        state = if !state.terminated() {
+            let term = self.solver.terminate_internal(&state);
+            state.termination_reason(term)
+        } else {
+            state
+        };
+        // Evaluate user-provided termination criteria. All criteria are evaluated in every
+        // iteration such that criteria which keep track of previous iterations stay up to
+        // date; the first one to terminate determines the termination reason.
+        for criterion in self.termination_criteria.iter_mut() {
+            let term = criterion.terminate(&state);
+            if !state.terminated() {
+                state = state.termination_reason(term);
+            }
+        }
+        // Stop if the wall-clock time budget is exhausted. This is only checked between
+        // iterations, therefore the actual runtime may exceed the timeout by the duration of
+        // a single iteration.
+        if !state.terminated() {
+            if let Some(timeout) = self.timeout {
+                if total_time.elapsed() >= timeout {
+                    state = state.termination_reason(TerminationReason::Timeout);
+                }
             }
+        }
+        // Stop if any of the function evaluation budgets is exhausted. The counts are
+        // updated after every iteration, therefore an iteration which performs several
+        // evaluations may exceed the budget.
+        if !state.terminated() && self.max_func_counts_reached(&state) {
+            state = state.termination_reason(TerminationReason::MaxFuncCountsReached);
+        }
+        state
+    }
 
-            // Start time measurement
-            let start = if self.timer {
-                Some(instant::Instant::now())
-            } else {
-                None
-            };
+    /// Performs a single iteration of the solver, including observing and checkpointing.
+    fn iterate(&mut self, state: I, total_time: instant::Instant) -> Result<I, Error> {
+        // Start time measurement
+        let start = if self.timer {
+            Some(instant::Instant::now())
+        } else {
+            None
+        };
 
-            let (state_t, kv) = self.solver.next_iter(&mut self.problem, state)?;
-            state = state_t;
+        let (mut state, kv) = self.solver.next_iter(&mut self.problem, state)?;
 
-            state.func_counts(&self.problem);
+        state.func_counts(&self.problem);
 
-            // End time measurement
-            let duration = if self.timer {
-                Some(start.unwrap().elapsed())
-            } else {
-                None
-            };
+        // End time measurement
+        let duration = if self.timer {
+            Some(start.unwrap().elapsed())
+        } else {
+            None
+        };
 
-            state.update();
+        state.update();
+        let new_best = state.is_best();
 
-            if !self.observers.is_empty() {
-                let mut log = if let Some(kv) = kv { kv } else { KV::new() };
-
-                if self.timer {
-                    let duration = duration.unwrap();
-                    let tmp = make_kv!(
-                        "time" => duration.as_secs() as f64 + f64::from(duration.subsec_nanos()) * 1e-9;
-                    );
-                    log = log.merge(tmp);
-                }
-                self.observers.observe_iter(&state, &log)?;
-            }
+        let kv = kv.unwrap_or_default();
 
-            // increment iteration number
-            state.increment_iter();
+        if let Some(history) = self.history.as_mut() {
+            history.push(&state, &kv, total_time.elapsed());
+        }
 
-            if let Some(checkpoint) = self.checkpoint.as_ref() {
-                checkpoint.save_cond(&self.solver, &state, state.get_iter())?;
-            }
+        if !self.observers.is_empty() {
+            let mut log = kv;
 
             if self.timer {
-                total_time.map(|total_time| state.time(Some(total_time.elapsed())));
+                let duration = duration.unwrap();
+                let tmp = make_kv!(
+                    "time" => duration.as_secs() as f64 + f64::from(duration.subsec_nanos()) * 1e-9;
+                );
+                log = log.merge(tmp);
             }
+            self.observers.observe_iter(&state, &log)?;
+        }
+
+        // increment iteration number
+        state.increment_iter();
 
-            // Check if termination occured inside next_iter()
-            if state.terminated() {
-                break;
+        if let Some(checkpoint) = self.checkpoint.as_ref() {
+            let save = match checkpoint.frequency() {
+                CheckpointingFrequency::Interval(interval) => {
+                    self.checkpoint_time.elapsed() >= interval
+                }
+                CheckpointingFrequency::NewBest => new_best,
+                _ => false,
+            };
+            if save {
+                checkpoint.save(&self.solver, &state)?;
+                self.checkpoint_time = instant::Instant::now();
+            } else {
+                checkpoint.save_cond(&self.solver, &state, state.get_iter())?;
             }
         }
 
-        // in case it stopped prematurely and `termination_reason` is still `NotTerminated`,
-        // someone must have pulled the handbrake
-        if state.get_iter() < state.get_max_iters() && !state.terminated() {
-            state = state.termination_reason(TerminationReason::Aborted);
+        if self.timer {
+            state.time(Some(total_time.elapsed()));
         }
-        Ok(OptimizationResult::new(self.problem, self.solver, state))
+
+        Ok(state)
     }
 
     /// Adds an observer to the executor. Observers are required to implement the
@@ -302,8 +471,55 @@ where
         self
     }
 
+    /// Adds an observer to the executor, like [`add_observer`](`Executor::add_observer`), with
+    /// an [`ObserverErrorPolicy`](`crate::core::observers::ObserverErrorPolicy`) which defines
+    /// how errors returned by the observer are handled.
+    ///
+    /// By default, an error returned by an observer stops the optimization. With
+    /// `ObserverErrorPolicy::Continue` or `ObserverErrorPolicy::DisableAfter(N)` the error is
+    /// recorded in [`OptimizationResult::observer_errors`] instead and the optimization continues.
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// # use argmin::core::{Error, Executor};
+    /// # use argmin::core::observers::{ObserverErrorPolicy, ObserverMode};
+    /// # use argmin::core::test_utils::{TestSolver, TestProblem};
+    /// # #[cfg(feature = "serde1")]
+    /// # use argmin::core::observers::{WriteToFile, WriteToFileSerializer};
+    /// #
+    /// # fn main() -> Result<(), Error> {
+    /// # let solver = TestSolver::new();
+    /// # let problem = TestProblem::new();
+    /// #
+    /// # #[cfg(feature = "serde1")]
+    /// let executor = Executor::new(problem, solver).add_observer_with_policy(
+    ///     WriteToFile::new("params", "param", WriteToFileSerializer::JSON),
+    ///     ObserverMode::Every(10),
+    ///     // Stop writing files after the third failure, but keep optimizing
+    ///     ObserverErrorPolicy::DisableAfter(3),
+    /// );
+    /// # Ok(())
+    /// # }
+    /// ```
+    #[must_use]
+    pub fn add_observer_with_policy<OBS: Observe<I> + 'static>(
+        mut self,
+        observer: OBS,
+        mode: ObserverMode,
+        policy: ObserverErrorPolicy,
+    ) -> Self {
+        self.observers.push_with_policy(observer, mode, policy);
+        self
+    }
+
     /// Configures checkpointing
     ///
+    /// Depending on the [`CheckpointingFrequency`] of `checkpoint`, a checkpoint is saved after
+    /// every Nth iteration, after a given wall-clock interval or whenever a new best parameter
+    /// vector was found. Unless the frequency is `Never`, a checkpoint is also saved once the
+    /// optimization terminated (including when it was aborted).
+    ///
     /// # Example
     ///
     /// ```
@@ -344,8 +560,10 @@ where
     /// stops the solver if it is cancelled via CTRL-C (SIGINT). Requires the optional `ctrlc`
     /// feature to be set.
     ///
-    /// Note that this does not work with nested `Executor`s. If a solver executes another solver
-    /// internally, the inner solver needs to disable CTRL-C handling.
+    /// CTRL-C cancels the [`CancellationToken`] of the `Executor` (see
+    /// [`cancellation_token`](`Executor::cancellation_token`)). The process-wide handler is only
+    /// installed once and cancels all runs which have CTRL-C handling enabled. If a solver
+    /// executes another solver internally, the inner solver should disable CTRL-C handling.
     ///
     /// # Example
     ///
@@ -368,6 +586,44 @@ where
         self
     }
 
+    /// Sets a token which allows one to cancel the optimization from anywhere, including other
+    /// threads (default: a new token which is not shared).
+    ///
+    /// Cancellation is checked before every iteration. A cancelled optimization terminates with
+    /// [`TerminationReason::Aborted`] and the result contains the state of the last completed
+    /// iteration. This works independently of the `ctrlc` feature.
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// # use argmin::core::{CancellationToken, Error, Executor, State, TerminationReason};
+    /// # use argmin::core::test_utils::{TestSolver, TestProblem};
+    /// #
+    /// # fn main() -> Result<(), Error> {
+    /// # let solver = TestSolver::new();
+    /// # let problem = TestProblem::new();
+    /// #
+    /// let token = CancellationToken::new();
+    ///
+    /// // Create instance of `Executor` with `problem` and `solver`
+    /// let executor = Executor::new(problem, solver)
+    ///     .configure(|state| state.param(vec![1.0f64, 0.0]).max_iters(10))
+    ///     .cancellation_token(token.clone());
+    ///
+    /// // Typically called from another thread
+    /// token.cancel();
+    ///
+    /// let result = executor.run()?;
+    /// assert_eq!(result.state().get_termination_reason(), TerminationReason::Aborted);
+    /// # Ok(())
+    /// # }
+    /// ```
+    #[must_use]
+    pub fn cancellation_token(mut self, token: CancellationToken) -> Self {
+        self.cancellation_token = token;
+        self
+    }
+
     /// Enables or disables timing of individual iterations (default: enabled).
     ///
     /// # Example
@@ -390,56 +646,444 @@ where
         self.timer = timer;
         self
     }
-}
-
-#[cfg(test)]
-mod tests {
-    use super::*;
-    use crate::core::test_utils::{TestProblem, TestSolver};
-    use crate::core::IterState;
-    use approx::assert_relative_eq;
 
-    #[test]
-    fn test_update() {
-        let problem = TestProblem::new();
-        let solver = TestSolver::new();
+    /// Sets the maximum wall-clock time the optimization is allowed to run (default: unlimited).
+    ///
+    /// The elapsed time is checked before every iteration. Once it exceeds `timeout`, the
+    /// optimization terminates with [`TerminationReason::Timeout`] and the best parameter vector
+    /// found so far is available in the returned [`OptimizationResult`]. Since a running
+    /// iteration is never interrupted, the total runtime may exceed `timeout` by the duration of
+    /// a single iteration. This works independently of [`timer`](`Executor::timer`).
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// # use argmin::core::{Error, Executor};
+    /// # use argmin::core::test_utils::{TestSolver, TestProblem};
+    /// #
+    /// # fn main() -> Result<(), Error> {
+    /// # let solver = TestSolver::new();
+    /// # let problem = TestProblem::new();
+    /// #
+    /// // Create instance of `Executor` with `problem` and `solver`
+    /// let executor = Executor::new(problem, solver).timeout(std::time::Duration::from_secs(30));
+    /// # Ok(())
+    /// # }
+    /// ```
+    #[must_use]
+    pub fn timeout(mut self, timeout: instant::Duration) -> Self {
+        self.timeout = Some(timeout);
+        self
+    }
 
-        let mut executor = Executor::new(problem, solver)
-            .configure(|config: IterState<Vec<f64>, (), (), (), f64>| config.param(vec![0.0, 0.0]));
+    /// Limits the number of evaluations of a function of the problem (default: unlimited).
+    ///
+    /// `counts_string` refers to the labels used for counting function evaluations in
+    /// [`Problem`], such as `cost_count`, `gradient_count`, `hessian_count`, `jacobian_count` or
+    /// `operator_count`. The function evaluation counts are checked after every iteration. Once
+    /// any of the counts reaches its limit, the optimization terminates with
+    /// [`TerminationReason::MaxFuncCountsReached`]. Since a single iteration may evaluate a
+    /// function several times, the final counts may exceed the limit. This method can be called
+    /// multiple times to limit several functions.
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// # use argmin::core::{Error, Executor};
+    /// # use argmin::core::test_utils::{TestSolver, TestProblem};
+    /// #
+    /// # fn main() -> Result<(), Error> {
+    /// # let solver = TestSolver::new();
+    /// # let problem = TestProblem::new();
+    /// #
+    /// // Create instance of `Executor` with `problem` and `solver`
+    /// let executor = Executor::new(problem, solver)
+    ///     // At most 500 cost function evaluations
+    ///     .max_func_count("cost_count", 500)
+    ///     // At most 100 gradient evaluations
+    ///     .max_func_count("gradient_count", 100);
+    /// # Ok(())
+    /// # }
+    /// ```
+    #[must_use]
+    pub fn max_func_count<N: AsRef<str>>(mut self, counts_string: N, max: u64) -> Self {
+        self.max_func_counts
+            .insert(counts_string.as_ref().to_string(), max);
+        self
+    }
 
-        // 1) Parameter vector changes, but not cost (continues to be `Inf`)
-        let new_param = vec![1.0, 1.0];
-        executor.state = Some(executor.state.take().unwrap().param(new_param.clone()));
-        executor.state.as_mut().unwrap().update();
-        assert_eq!(
-            *executor.state.as_ref().unwrap().get_best_param().unwrap(),
-            new_param
-        );
-        assert!(executor
-            .state
-            .as_ref()
-            .unwrap()
-            .get_best_cost()
-            .is_infinite());
-        assert!(executor
-            .state
-            .as_ref()
-            .unwrap()
-            .get_best_cost()
-            .is_sign_positive());
+    /// Adds a termination criterion which is evaluated before every iteration.
+    ///
+    /// Termination criteria are checked after the stopping criteria of the solver. This method
+    /// can be called multiple times to add several criteria; the optimization terminates as soon
+    /// as any of them terminates. See [`termination_criteria`](`crate::core::termination_criteria`)
+    /// for the available criteria.
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// # use argmin::core::{Error, Executor};
+    /// # use argmin::core::test_utils::{TestSolver, TestProblem};
+    /// use argmin::core::termination_criteria::CostChange;
+    /// #
+    /// # fn main() -> Result<(), Error> {
+    /// # let solver = TestSolver::new();
+    /// # let problem = TestProblem::new();
+    /// #
+    /// // Create instance of `Executor` with `problem` and `solver`
+    /// let executor = Executor::new(problem, solver)
+    ///     // Stop if the cost changed by less than 0.01% within the last 10 iterations
+    ///     .add_termination(CostChange::new(1e-4, 10));
+    /// # Ok(())
+    /// # }
+    /// ```
+    #[must_use]
+    pub fn add_termination<C: TerminationCriterion<I> + 'static>(mut self, criterion: C) -> Self {
+        self.termination_criteria.push(Box::new(criterion));
+        self
+    }
 
-        // 2) Parameter vector and cost changes to something better
-        let new_param = vec![2.0, 2.0];
-        let new_cost = 10.0;
-        executor.state = Some(
-            executor
-                .state
-                .take()
-                .unwrap()
-                .param(new_param.clone())
-                .cost(new_cost),
-        );
-        executor.state.as_mut().unwrap().update();
+    /// Records the convergence history of the optimization (default: disabled).
+    ///
+    /// After every iteration, the iteration number, the cost function value, the best cost
+    /// function value, the elapsed time, the function evaluation counts and the key-value pairs
+    /// returned by the solver are stored. If `params` is `true`, the parameter vector is stored as
+    /// well. The [`History`](`crate::core::History`) is available via
+    /// [`OptimizationResult::history`] and can be serialized with the `serde1` feature.
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// # use argmin::core::{Error, Executor};
+    /// # use argmin::core::test_utils::{TestSolver, TestProblem};
+    /// #
+    /// # fn main() -> Result<(), Error> {
+    /// # let solver = TestSolver::new();
+    /// # let problem = TestProblem::new();
+    /// #
+    /// let result = Executor::new(problem, solver)
+    ///     .configure(|state| state.param(vec![1.0f64, 0.0]).max_iters(10))
+    ///     // Record the history including the parameter vectors
+    ///     .record_history(true)
+    ///     .run()?;
+    ///
+    /// let history = result.history().unwrap();
+    /// # assert_eq!(history.entries.len(), 10);
+    /// # assert_eq!(history.entries[3].param, Some(vec![1.0, 0.0]));
+    /// # Ok(())
+    /// # }
+    /// ```
+    #[must_use]
+    pub fn record_history(mut self, params: bool) -> Self
+    where
+        I::Param: Clone + 'static,
+    {
+        self.history = Some(Box::new(Recorder::new(History::new(params))));
+        self
+    }
+
+    /// Returns `true` if any function evaluation count of `state` reached its limit.
+    fn max_func_counts_reached(&self, state: &I) -> bool {
+        state.get_func_counts().iter().any(|(k, count)| {
+            matches!(self.max_func_counts.get(k), Some(max_count) if count >= max_count)
+        })
+    }
+}
+
+impl<O, S, I, P, F> Executor<O, S, I>
+where
+    O: CostFunction<Param = P, Output = F> + Gradient<Param = P, Gradient = P>,
+    I: State<Param = P>,
+    P: ArgminElements<Element = F>,
+    F: ArgminFloat,
+{
+    /// Verifies the gradient of the problem at the initial parameter vector before the first
+    /// iteration.
+    ///
+    /// The analytic gradient is compared to finite differences of the cost function as described
+    /// in [`DerivativeCheck`](`crate::core::DerivativeCheck`). If any component exceeds the
+    /// tolerance, running the executor fails with an
+    /// [`ArgminError::ConditionViolated`](`crate::core::ArgminError::ConditionViolated`) error
+    /// which lists the offending components. The check is skipped when resuming from a
+    /// checkpoint. Evaluations performed by the check are not added to the function evaluation
+    /// counts.
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// # use argmin::core::{DerivativeCheck, Error, Executor};
+    /// # use argmin::core::test_utils::{TestSolver, TestProblem};
+    /// #
+    /// # fn main() -> Result<(), Error> {
+    /// # let solver = TestSolver::new();
+    /// # let problem = TestProblem::new();
+    /// #
+    /// let result = Executor::new(problem, solver)
+    ///     .configure(|state| state.param(vec![1.0, 2.0]).max_iters(10))
+    ///     .check_gradient(DerivativeCheck::new().with_tolerance(1e-4))
+    ///     .run();
+    ///
+    /// // `TestProblem` does not provide a correct gradient
+    /// assert!(result.is_err());
+    /// # Ok(())
+    /// # }
+    /// ```
+    #[must_use]
+    pub fn check_gradient(mut self, check: DerivativeCheck) -> Self {
+        self.derivative_checks
+            .push((check, DerivativeCheck::check_gradient_at::<O, I, P, F>));
+        self
+    }
+}
+
+impl<O, S, I, P, V, J, F> Executor<O, S, I>
+where
+    O: Operator<Param = P, Output = V> + Jacobian<Param = P, Jacobian = J>,
+    I: State<Param = P>,
+    P: ArgminElements<Element = F>,
+    V: ArgminElements<Element = F>,
+    J: ArgminDot<P, V>,
+    F: ArgminFloat,
+{
+    /// Verifies the Jacobian of the problem at the initial parameter vector before the first
+    /// iteration.
+    ///
+    /// Works like [`check_gradient`](`Executor::check_gradient`), but compares the Jacobian with
+    /// finite differences of the operator.
+    #[must_use]
+    pub fn check_jacobian(mut self, check: DerivativeCheck) -> Self {
+        self.derivative_checks.push((
+            check,
+            DerivativeCheck::check_jacobian_at::<O, I, P, V, J, F>,
+        ));
+        self
+    }
+}
+
+impl<O, S, I, P, H, F> Executor<O, S, I>
+where
+    O: Gradient<Param = P, Gradient = P> + Hessian<Param = P, Hessian = H>,
+    I: State<Param = P>,
+    P: ArgminElements<Element = F>,
+    H: ArgminDot<P, P>,
+    F: ArgminFloat,
+{
+    /// Verifies the Hessian of the problem at the initial parameter vector before the first
+    /// iteration.
+    ///
+    /// Works like [`check_gradient`](`Executor::check_gradient`), but compares the Hessian with
+    /// finite differences of the gradient.
+    #[must_use]
+    pub fn check_hessian(mut self, check: DerivativeCheck) -> Self {
+        self.derivative_checks
+            .push((check, DerivativeCheck::check_hessian_at::<O, I, P, H, F>));
+        self
+    }
+}
+
+/// Performs an optimization one iteration at a time.
+///
+/// Created via [`Executor::stepper`]. Each call to [`step`](`Stepper::step`) performs one
+/// iteration of the solver including observers and checkpointing, exactly as
+/// [`Executor::run`] does. The current state can be inspected between iterations via
+/// [`state`](`Stepper::state`), and [`finish`](`Stepper::finish`) returns the usual
+/// [`OptimizationResult`].
+///
+/// If the state implements `Clone`, `Stepper` can also be used as an [`Iterator`] which yields the
+/// state after each iteration and stops once the optimization has terminated.
+///
+/// # Example
+///
+/// ```
+/// # use argmin::core::{Error, Executor, State};
+/// # use argmin::core::test_utils::{TestSolver, TestProblem};
+/// #
+/// # fn main() -> Result<(), Error> {
+/// # let solver = TestSolver::new();
+/// # let problem = TestProblem::new();
+/// #
+/// let mut stepper = Executor::new(problem, solver)
+///     .configure(|state| state.param(vec![1.0f64, 0.0]).max_iters(10))
+///     .stepper()?;
+///
+/// for state in &mut stepper {
+///     let state = state?;
+///     println!("iter: {}, cost: {}", state.get_iter(), state.get_cost());
+/// }
+///
+/// let result = stepper.finish()?;
+/// # Ok(())
+/// # }
+/// ```
+pub struct Stepper<O, S, I> {
+    /// Executor holding problem, solver, observers and checkpointing
+    executor: Executor<O, S, I>,
+    /// Current state; `None` if an iteration failed
+    state: Option<I>,
+    /// Time at which the optimization was started
+    total_time: instant::Instant,
+}
+
+impl<O, S, I> Stepper<O, S, I>
+where
+    S: Solver<O, I>,
+    I: State + SerializeAlias + DeserializeOwnedAlias,
+{
+    /// Performs a single iteration unless the optimization has terminated.
+    ///
+    /// All stopping criteria are evaluated before the iteration. Returns `Ok(true)` if an
+    /// iteration was performed and `Ok(false)` if the optimization has terminated (or was
+    /// cancelled), in which case the termination reason is available from the state.
+    ///
+    /// If the solver, an observer or the checkpoint returns an error, the error is returned and
+    /// the state is lost. Every subsequent call to `step` or `finish` will return an error.
+    pub fn step(&mut self) -> Result<bool, Error> {
+        let state = self.state.take().ok_or_else(argmin_error_closure!(
+            NotInitialized,
+            "Stepper cannot continue after a failed iteration."
+        ))?;
+
+        if self.executor.cancellation_token.is_cancelled() {
+            self.state = Some(if state.terminated() {
+                state
+            } else {
+                state.termination_reason(TerminationReason::Aborted)
+            });
+            return Ok(false);
+        }
+
+        let state = self.executor.check_termination(state, self.total_time);
+        if state.terminated() {
+            self.state = Some(state);
+            return Ok(false);
+        }
+
+        self.state = Some(self.executor.iterate(state, self.total_time)?);
+        Ok(true)
+    }
+
+    /// Returns a reference to the current state.
+    ///
+    /// # Panics
+    ///
+    /// Panics if a previous call to [`step`](`Stepper::step`) returned an error.
+    pub fn state(&self) -> &I {
+        self.state
+            .as_ref()
+            .expect("Stepper: state is not available after a failed iteration")
+    }
+
+    /// Returns a reference to the solver.
+    pub fn solver(&self) -> &S {
+        &self.executor.solver
+    }
+
+    /// Returns a reference to the problem.
+    pub fn problem(&self) -> &Problem<O> {
+        &self.executor.problem
+    }
+
+    /// Stops the optimization and returns the [`OptimizationResult`].
+    ///
+    /// If the optimization has not terminated yet, the termination reason is set to
+    /// [`TerminationReason::Aborted`]. Unless the checkpointing frequency is
+    /// [`Never`](`CheckpointingFrequency::Never`), a checkpoint of the final state is saved. The
+    /// observers are then informed about the final state via
+    /// [`observe_final`](`crate::core::observers::Observe::observe_final`).
+    pub fn finish(mut self) -> Result<OptimizationResult<O, S, I>, Error> {
+        let mut state = self.state.take().ok_or_else(argmin_error_closure!(
+            NotInitialized,
+            "Stepper cannot finish after a failed iteration."
+        ))?;
+        // in case it stopped prematurely and `termination_reason` is still `NotTerminated`,
+        // someone must have pulled the handbrake
+        if state.get_iter() < state.get_max_iters() && !state.terminated() {
+            state = state.termination_reason(TerminationReason::Aborted);
+        }
+        if let Some(checkpoint) = self.executor.checkpoint.as_ref() {
+            if checkpoint.frequency() != CheckpointingFrequency::Never {
+                checkpoint.save(&self.executor.solver, &state)?;
+            }
+        }
+        if !self.executor.observers.is_empty() {
+            let reason = state.get_termination_reason();
+            self.executor.observers.observe_final(&state, &reason)?;
+        }
+        let mut result =
+            OptimizationResult::new(self.executor.problem, self.executor.solver, state);
+        result.history = self.executor.history.map(|history| history.into_history());
+        result.observer_errors = self.executor.observers.take_errors();
+        Ok(result)
+    }
+}
+
+impl<O, S, I> Iterator for Stepper<O, S, I>
+where
+    S: Solver<O, I>,
+    I: State + SerializeAlias + DeserializeOwnedAlias + Clone,
+{
+    type Item = Result<I, Error>;
+
+    /// Performs a single iteration and returns a copy of the resulting state. Returns `None` once
+    /// the optimization has terminated or after an error occurred.
+    fn next(&mut self) -> Option<Self::Item> {
+        self.state.as_ref()?;
+        match self.step() {
+            Ok(true) => Some(Ok(self.state().clone())),
+            Ok(false) => None,
+            Err(e) => Some(Err(e)),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::core::test_utils::{TestProblem, TestSolver};
+    use crate::core::{IterState, KV};
+    use approx::assert_relative_eq;
+
+    #[test]
+    fn test_update() {
+        let problem = TestProblem::new();
+        let solver = TestSolver::new();
+
+        let mut executor = Executor::new(problem, solver)
+            .configure(|config: IterState<Vec<f64>, (), (), (), f64>| config.param(vec![0.0, 0.0]));
+
+        // 1) Parameter vector changes, but not cost (continues to be `Inf`)
+        let new_param = vec![1.0, 1.0];
+        executor.state = Some(executor.state.take().unwrap().param(new_param.clone()));
+        executor.state.as_mut().unwrap().update();
+        assert_eq!(
+            *executor.state.as_ref().unwrap().get_best_param().unwrap(),
+            new_param
+        );
+        assert!(executor
+            .state
+            .as_ref()
+            .unwrap()
+            .get_best_cost()
+            .is_infinite());
+        assert!(executor
+            .state
+            .as_ref()
+            .unwrap()
+            .get_best_cost()
+            .is_sign_positive());
+
+        // 2) Parameter vector and cost changes to something better
+        let new_param = vec![2.0, 2.0];
+        let new_cost = 10.0;
+        executor.state = Some(
+            executor
+                .state
+                .take()
+                .unwrap()
+                .param(new_param.clone())
+                .cost(new_cost),
+        );
+        executor.state.as_mut().unwrap().update();
         assert_eq!(
             *executor.state.as_ref().unwrap().get_best_param().unwrap(),
             new_param
@@ -562,6 +1206,747 @@ mod tests {
             .is_sign_negative());
     }
 
+    #[test]
+    fn test_timeout() {
+        use crate::core::CostFunction;
+        #[cfg(feature = "serde1")]
+        use serde::{Deserialize, Serialize};
+
+        // Solver which takes a while for each iteration and moves the parameter vector
+        #[derive(Clone)]
+        #[cfg_attr(feature = "serde1", derive(Serialize, Deserialize))]
+        struct SlowSolver {}
+
+        impl<O> Solver<O, IterState<Vec<f64>, (), (), (), f64>> for SlowSolver
+        where
+            O: CostFunction<Param = Vec<f64>, Output = f64>,
+        {
+            const NAME: &'static str = "SlowSolver";
+
+            fn next_iter(
+                &mut self,
+                problem: &mut Problem<O>,
+                mut state: IterState<Vec<f64>, (), (), (), f64>,
+            ) -> Result<(IterState<Vec<f64>, (), (), (), f64>, Option<KV>), Error> {
+                std::thread::sleep(instant::Duration::from_millis(5));
+                let param: Vec<f64> = state
+                    .take_param()
+                    .unwrap()
+                    .iter()
+                    .map(|x| x + 1.0)
+                    .collect();
+                let cost = problem.cost(&param)?;
+                Ok((state.param(param).cost(cost), None))
+            }
+        }
+
+        // A timeout of zero terminates before the first iteration
+        let res = Executor::new(TestProblem::new(), TestSolver::new())
+            .configure(|state| state.param(vec![1.0f64, 1.0]).max_iters(10))
+            .timeout(instant::Duration::from_secs(0))
+            .run()
+            .unwrap();
+
+        assert_eq!(res.state.get_iter(), 0);
+        assert_eq!(
+            res.state.get_termination_reason(),
+            TerminationReason::Timeout
+        );
+
+        // Terminates long before `max_iters` is reached, but keeps the best parameter vector
+        let res = Executor::new(TestProblem::new(), SlowSolver {})
+            .configure(|state| state.param(vec![1.0f64, 1.0]).max_iters(100_000))
+            .timeout(instant::Duration::from_millis(50))
+            .timer(false)
+            .run()
+            .unwrap();
+
+        assert!(res.state.get_iter() > 0);
+        assert!(res.state.get_iter() < 100_000);
+        assert_eq!(
+            res.state.get_termination_reason(),
+            TerminationReason::Timeout
+        );
+        assert!(res.state.get_best_param().is_some());
+    }
+
+    #[test]
+    fn test_max_func_counts() {
+        use crate::core::CostFunction;
+        #[cfg(feature = "serde1")]
+        use serde::{Deserialize, Serialize};
+
+        // Solver which evaluates the cost function twice per iteration
+        #[derive(Clone)]
+        #[cfg_attr(feature = "serde1", derive(Serialize, Deserialize))]
+        struct CostSolver {}
+
+        impl<O> Solver<O, IterState<Vec<f64>, (), (), (), f64>> for CostSolver
+        where
+            O: CostFunction<Param = Vec<f64>, Output = f64>,
+        {
+            const NAME: &'static str = "CostSolver";
+
+            fn next_iter(
+                &mut self,
+                problem: &mut Problem<O>,
+                state: IterState<Vec<f64>, (), (), (), f64>,
+            ) -> Result<(IterState<Vec<f64>, (), (), (), f64>, Option<KV>), Error> {
+                let param = state.get_param().unwrap();
+                let _ = problem.cost(param)?;
+                let cost = problem.cost(param)?;
+                Ok((state.cost(cost), None))
+            }
+        }
+
+        let res = Executor::new(TestProblem::new(), CostSolver {})
+            .configure(|state| state.param(vec![1.0f64, 1.0]).max_iters(100))
+            .max_func_count("cost_count", 9)
+            .run()
+            .unwrap();
+
+        // Budget is exhausted after the fifth iteration (10 evaluations)
+        assert_eq!(res.state.get_iter(), 5);
+        assert_eq!(res.state.get_func_counts()["cost_count"], 10);
+        assert_eq!(
+            res.state.get_termination_reason(),
+            TerminationReason::MaxFuncCountsReached
+        );
+
+        // Limits on functions which are never evaluated have no effect
+        let res = Executor::new(TestProblem::new(), CostSolver {})
+            .configure(|state| state.param(vec![1.0f64, 1.0]).max_iters(10))
+            .max_func_count("gradient_count", 1)
+            .run()
+            .unwrap();
+
+        assert_eq!(res.state.get_iter(), 10);
+        assert_eq!(
+            res.state.get_termination_reason(),
+            TerminationReason::MaxItersReached
+        );
+    }
+
+    #[test]
+    fn test_termination_criteria() {
+        use crate::core::termination_criteria::{CostChange, TerminationCriterion};
+
+        type TState = IterState<Vec<f64>, (), (), (), f64>;
+
+        struct StopAt(u64);
+
+        impl TerminationCriterion<TState> for StopAt {
+            fn terminate(&mut self, state: &TState) -> TerminationReason {
+                if state.get_iter() >= self.0 {
+                    TerminationReason::Custom(format!("Stopped at {}", self.0))
+                } else {
+                    TerminationReason::NotTerminated
+                }
+            }
+        }
+
+        let res = Executor::new(TestProblem::new(), TestSolver::new())
+            .configure(|state| state.param(vec![1.0f64, 1.0]).max_iters(100))
+            .add_termination(StopAt(20))
+            .add_termination(StopAt(7))
+            .run()
+            .unwrap();
+
+        assert_eq!(res.state.get_iter(), 7);
+        assert_eq!(
+            res.state.get_termination_reason(),
+            TerminationReason::Custom("Stopped at 7".to_string())
+        );
+
+        // The stopping criteria of the solver take precedence
+        let res = Executor::new(TestProblem::new(), TestSolver::new())
+            .configure(|state| state.param(vec![1.0f64, 1.0]).max_iters(5))
+            .add_termination(StopAt(5))
+            .run()
+            .unwrap();
+
+        assert_eq!(res.state.get_iter(), 5);
+        assert_eq!(
+            res.state.get_termination_reason(),
+            TerminationReason::MaxItersReached
+        );
+
+        // `TestSolver` does not change the cost function value
+        let res = Executor::new(TestProblem::new(), TestSolver::new())
+            .configure(|state| state.param(vec![1.0f64, 1.0]).cost(1.0).max_iters(100))
+            .add_termination(CostChange::new(1e-6, 3))
+            .run()
+            .unwrap();
+
+        assert_eq!(res.state.get_iter(), 3);
+        assert_eq!(
+            res.state.get_termination_reason(),
+            TerminationReason::NoChangeInCost
+        );
+    }
+
+    #[test]
+    fn test_cancellation_token() {
+        use crate::core::termination_criteria::TerminationCriterion;
+        use crate::core::CancellationToken;
+
+        type TState = IterState<Vec<f64>, (), (), (), f64>;
+
+        // Cancels the token once the given iteration is reached
+        struct CancelAt(u64, CancellationToken);
+
+        impl TerminationCriterion<TState> for CancelAt {
+            fn terminate(&mut self, state: &TState) -> TerminationReason {
+                if state.get_iter() >= self.0 {
+                    self.1.cancel();
+                }
+                TerminationReason::NotTerminated
+            }
+        }
+
+        let token = CancellationToken::new();
+        let res = Executor::new(TestProblem::new(), TestSolver::new())
+            .configure(|state| state.param(vec![1.0f64, 1.0]).max_iters(100))
+            .cancellation_token(token.clone())
+            .add_termination(CancelAt(3, token.clone()))
+            .run()
+            .unwrap();
+
+        // The iteration in which the token was cancelled is completed
+        assert!(token.is_cancelled());
+        assert_eq!(res.state.get_iter(), 4);
+        assert_eq!(
+            res.state.get_termination_reason(),
+            TerminationReason::Aborted
+        );
+        assert_eq!(res.state.get_best_param().unwrap(), &vec![1.0f64, 1.0]);
+
+        // A token cancelled from another thread before the run stops it immediately, even if the
+        // maximum number of iterations is zero
+        let token = CancellationToken::new();
+        let handle = token.clone();
+        std::thread::spawn(move || handle.cancel()).join().unwrap();
+
+        let res = Executor::new(TestProblem::new(), TestSolver::new())
+            .configure(|state| state.param(vec![1.0f64, 1.0]).max_iters(0))
+            .cancellation_token(token)
+            .run()
+            .unwrap();
+
+        assert_eq!(res.state.get_iter(), 0);
+        assert_eq!(
+            res.state.get_termination_reason(),
+            TerminationReason::Aborted
+        );
+    }
+
+    #[test]
+    fn test_check_gradient() {
+        use crate::core::{ArgminError, DerivativeCheck};
+
+        // `TestProblem` has a constant cost function and returns the parameter vector as
+        // gradient, which is only correct at the origin.
+        let res = Executor::new(TestProblem::new(), TestSolver::new())
+            .configure(|state| state.param(vec![0.0f64, 0.0]).max_iters(5))
+            .check_gradient(DerivativeCheck::new())
+            .run()
+            .unwrap();
+        assert_eq!(res.state.get_iter(), 5);
+        // Evaluations of the check are not counted
+        assert!(res.state.get_func_counts().get("gradient_count").is_none());
+
+        let err = Executor::new(TestProblem::new(), TestSolver::new())
+            .configure(|state| state.param(vec![0.0f64, 1.0]).max_iters(5))
+            .check_gradient(DerivativeCheck::new())
+            .run()
+            .err()
+            .unwrap();
+        match err.downcast_ref::<ArgminError>() {
+            Some(ArgminError::ConditionViolated { text }) => {
+                assert!(text.contains("1 of 2 derivative components exceed the tolerance"));
+                assert!(text.contains("point 0, (1, 0): analytic 1, approx 0"));
+            }
+            _ => panic!("Unexpected error: {err}"),
+        }
+
+        // An initial parameter vector is required
+        let err = Executor::new(TestProblem::new(), TestSolver::new())
+            .check_gradient(DerivativeCheck::new())
+            .run()
+            .err()
+            .unwrap();
+        assert_eq!(
+            err.downcast_ref::<ArgminError>().unwrap().to_string(),
+            "Not initialized: \"Derivative check requires an initial parameter vector.\""
+        );
+    }
+
+    #[test]
+    fn test_stepper() {
+        let problem = TestProblem::new();
+        let solver = TestSolver::new();
+
+        let mut stepper = Executor::new(problem, solver)
+            .configure(|state| state.param(vec![1.0f64, 1.0]).max_iters(5))
+            .stepper()
+            .unwrap();
+
+        assert_eq!(stepper.state().get_iter(), 0);
+        assert!(stepper.step().unwrap());
+        assert_eq!(stepper.state().get_iter(), 1);
+
+        let iters: Vec<u64> = (&mut stepper).map(|s| s.unwrap().get_iter()).collect();
+        assert_eq!(iters, vec![2, 3, 4, 5]);
+
+        // Stepping a terminated optimization has no effect
+        assert!(!stepper.step().unwrap());
+        assert!(stepper.next().is_none());
+        assert_eq!(stepper.state().get_iter(), 5);
+
+        let res = stepper.finish().unwrap();
+        assert_eq!(res.state.get_iter(), 5);
+        assert_eq!(
+            res.state.get_termination_reason(),
+            TerminationReason::MaxItersReached
+        );
+
+        // Finishing early aborts the optimization
+        let mut stepper = Executor::new(TestProblem::new(), TestSolver::new())
+            .configure(|state| state.param(vec![1.0f64, 1.0]).max_iters(5))
+            .stepper()
+            .unwrap();
+
+        assert!(stepper.step().unwrap());
+        assert!(stepper.step().unwrap());
+        let res = stepper.finish().unwrap();
+        assert_eq!(res.state.get_iter(), 2);
+        assert_eq!(
+            res.state.get_termination_reason(),
+            TerminationReason::Aborted
+        );
+    }
+
+    #[test]
+    fn test_stepper_error() {
+        use crate::core::CostFunction;
+        #[cfg(feature = "serde1")]
+        use serde::{Deserialize, Serialize};
+
+        // Solver which fails in the second iteration
+        #[derive(Clone)]
+        #[cfg_attr(feature = "serde1", derive(Serialize, Deserialize))]
+        struct FailingSolver {}
+
+        impl<O> Solver<O, IterState<Vec<f64>, (), (), (), f64>> for FailingSolver
+        where
+            O: CostFunction<Param = Vec<f64>, Output = f64>,
+        {
+            const NAME: &'static str = "FailingSolver";
+
+            fn next_iter(
+                &mut self,
+                _problem: &mut Problem<O>,
+                state: IterState<Vec<f64>, (), (), (), f64>,
+            ) -> Result<(IterState<Vec<f64>, (), (), (), f64>, Option<KV>), Error> {
+                if state.get_iter() == 1 {
+                    Err(argmin_error!(ConditionViolated, "failure"))
+                } else {
+                    Ok((state, None))
+                }
+            }
+        }
+
+        let mut stepper = Executor::new(TestProblem::new(), FailingSolver {})
+            .configure(|state| state.param(vec![1.0f64, 1.0]).max_iters(5))
+            .stepper()
+            .unwrap();
+
+        assert!(stepper.next().unwrap().is_ok());
+        assert!(stepper.next().unwrap().is_err());
+        assert!(stepper.next().is_none());
+        assert!(stepper.step().is_err());
+        assert!(stepper.finish().is_err());
+
+        let res = Executor::new(TestProblem::new(), FailingSolver {})
+            .configure(|state| state.param(vec![1.0f64, 1.0]).max_iters(5))
+            .run();
+        assert!(res.is_err());
+    }
+
+    #[test]
+    fn test_from_result() {
+        use crate::core::test_utils::Rosenbrock;
+        use crate::solver::linesearch::MoreThuenteLineSearch;
+        use crate::solver::quasinewton::LBFGS;
+
+        let lbfgs =
+            || -> LBFGS<_, Vec<f64>, Vec<f64>, f64> { LBFGS::new(MoreThuenteLineSearch::new(), 5) };
+
+        let uninterrupted = Executor::new(Rosenbrock::new(), lbfgs())
+            .configure(|state| state.param(vec![-1.2, 1.0]).max_iters(15))
+            .run()
+            .unwrap();
+
+        let result = Executor::new(Rosenbrock::new(), lbfgs())
+            .configure(|state| state.param(vec![-1.2, 1.0]).max_iters(5))
+            .run()
+            .unwrap();
+        assert_eq!(result.state.get_iter(), 5);
+        assert_eq!(
+            result.state.get_termination_reason(),
+            TerminationReason::MaxItersReached
+        );
+
+        let resumed = Executor::from_result(result)
+            .configure(|state| state.max_iters(15))
+            .run()
+            .unwrap();
+
+        // Resuming neither resets the iteration counter, the function counts nor the history of
+        // L-BFGS, and `init` is not called again.
+        assert_eq!(resumed.state.get_iter(), 15);
+        assert_eq!(
+            resumed.state.get_termination_reason(),
+            TerminationReason::MaxItersReached
+        );
+        assert_eq!(
+            resumed.state.get_func_counts(),
+            uninterrupted.state.get_func_counts()
+        );
+        assert_eq!(
+            resumed.state.get_best_param(),
+            uninterrupted.state.get_best_param()
+        );
+        assert_eq!(
+            resumed.state.get_best_cost().to_bits(),
+            uninterrupted.state.get_best_cost().to_bits()
+        );
+    }
+
+    #[test]
+    fn test_observe_final() {
+        use std::sync::{Arc, Mutex};
+
+        #[derive(Default)]
+        struct FinalObs {
+            calls: Vec<(u64, TerminationReason)>,
+        }
+
+        impl<I: State> Observe<I> for FinalObs {
+            fn observe_final(
+                &mut self,
+                state: &I,
+                reason: &TerminationReason,
+            ) -> Result<(), Error> {
+                self.calls.push((state.get_iter(), reason.clone()));
+                Ok(())
+            }
+        }
+
+        let observer = Arc::new(Mutex::new(FinalObs::default()));
+        Executor::new(TestProblem::new(), TestSolver::new())
+            .configure(|state| state.param(vec![1.0f64, 1.0]).max_iters(5))
+            .add_observer(observer.clone(), ObserverMode::Always)
+            .run()
+            .unwrap();
+        assert_eq!(
+            observer.lock().unwrap().calls,
+            vec![(5, TerminationReason::MaxItersReached)]
+        );
+
+        // Finishing early reports the optimization as aborted
+        let observer = Arc::new(Mutex::new(FinalObs::default()));
+        let mut stepper = Executor::new(TestProblem::new(), TestSolver::new())
+            .configure(|state| state.param(vec![1.0f64, 1.0]).max_iters(5))
+            .add_observer(observer.clone(), ObserverMode::Every(10))
+            .stepper()
+            .unwrap();
+        assert!(stepper.step().unwrap());
+        stepper.finish().unwrap();
+        assert_eq!(
+            observer.lock().unwrap().calls,
+            vec![(1, TerminationReason::Aborted)]
+        );
+    }
+
+    #[test]
+    fn test_observer_errors() {
+        struct Failing;
+
+        impl<I: State> Observe<I> for Failing {
+            fn observe_iter(&mut self, _state: &I, _kv: &KV) -> Result<(), Error> {
+                Err(Error::msg("disk full"))
+            }
+        }
+
+        let result = Executor::new(TestProblem::new(), TestSolver::new())
+            .configure(|state| state.param(vec![1.0f64, 1.0]).max_iters(5))
+            .add_observer_with_policy(
+                Failing,
+                ObserverMode::Always,
+                ObserverErrorPolicy::DisableAfter(2),
+            )
+            .run()
+            .unwrap();
+        assert_eq!(result.state.get_iter(), 5);
+        let errors = result.observer_errors();
+        assert_eq!(errors.len(), 2);
+        assert_eq!(
+            errors[1].to_string(),
+            "Observer 0 failed in iteration 1: disk full"
+        );
+        assert!(errors[1].disabled);
+
+        let result = Executor::new(TestProblem::new(), TestSolver::new())
+            .configure(|state| state.param(vec![1.0f64, 1.0]).max_iters(5))
+            .add_observer(Failing, ObserverMode::Always)
+            .run();
+        assert_eq!(result.err().unwrap().to_string(), "disk full");
+    }
+
+    #[test]
+    fn test_record_history() {
+        let result = Executor::new(TestProblem::new(), TestSolver::new())
+            .configure(|state| state.param(vec![1.0f64, 1.0]).max_iters(5))
+            .run()
+            .unwrap();
+        assert!(result.history().is_none());
+
+        let result = Executor::new(TestProblem::new(), TestSolver::new())
+            .configure(|state| state.param(vec![1.0f64, 1.0]).max_iters(5))
+            .record_history(false)
+            .run()
+            .unwrap();
+        let history = result.history().unwrap();
+        let iters: Vec<u64> = history.entries.iter().map(|e| e.iter).collect();
+        assert_eq!(iters, vec![0, 1, 2, 3, 4]);
+        assert!(history.entries.iter().all(|e| e.param.is_none()));
+        assert!(history.entries.windows(2).all(|w| w[0].time <= w[1].time));
+
+        // Recording continues when resuming
+        let result = Executor::from_result(result)
+            .configure(|state| state.max_iters(8))
+            .run()
+            .unwrap();
+        let iters: Vec<u64> = result
+            .history
+            .unwrap()
+            .entries
+            .iter()
+            .map(|e| e.iter)
+            .collect();
+        assert_eq!(iters, vec![0, 1, 2, 3, 4, 5, 6, 7]);
+    }
+
+    #[test]
+    fn test_record_history_resume_time() {
+        #[cfg(feature = "serde1")]
+        use serde::{Deserialize, Serialize};
+
+        type TState = IterState<Vec<f64>, (), (), (), f64>;
+
+        // Solver which takes a while for each iteration
+        #[cfg_attr(feature = "serde1", derive(Serialize, Deserialize))]
+        struct SlowSolver {}
+
+        impl<O> Solver<O, TState> for SlowSolver {
+            const NAME: &'static str = "SlowSolver";
+
+            fn next_iter(
+                &mut self,
+                _problem: &mut Problem<O>,
+                state: TState,
+            ) -> Result<(TState, Option<KV>), Error> {
+                std::thread::sleep(instant::Duration::from_millis(5));
+                Ok((state, None))
+            }
+        }
+
+        let result = Executor::new(TestProblem::new(), SlowSolver {})
+            .configure(|state| state.param(vec![1.0f64]).max_iters(3))
+            .record_history(false)
+            .run()
+            .unwrap();
+        let result = Executor::from_result(result)
+            .configure(|state| state.max_iters(6))
+            .run()
+            .unwrap();
+
+        // Times continue from the previous run
+        let times: Vec<instant::Duration> = result
+            .history
+            .unwrap()
+            .entries
+            .iter()
+            .map(|e| e.time)
+            .collect();
+        assert_eq!(times.len(), 6);
+        assert!(times.windows(2).all(|w| w[0] < w[1]));
+        assert!(times[3] >= instant::Duration::from_millis(20));
+    }
+
+    #[test]
+    fn test_checkpointing_frequency() {
+        use crate::core::termination_criteria::TerminationCriterion;
+        use crate::core::CancellationToken;
+        #[cfg(feature = "serde1")]
+        use serde::{Deserialize, Serialize};
+        use std::sync::{Arc, Mutex};
+
+        type TState = IterState<Vec<f64>, (), (), (), f64>;
+
+        // Solver whose cost function value in iteration `i` is the `i`th element
+        #[cfg_attr(feature = "serde1", derive(Serialize, Deserialize))]
+        struct CostSequence(Vec<f64>);
+
+        impl<O> Solver<O, TState> for CostSequence {
+            const NAME: &'static str = "CostSequence";
+
+            fn next_iter(
+                &mut self,
+                _problem: &mut Problem<O>,
+                state: TState,
+            ) -> Result<(TState, Option<KV>), Error> {
+                let cost = self.0[state.get_iter() as usize];
+                Ok((state.cost(cost), None))
+            }
+        }
+
+        // Records the iteration number and termination reason of every saved checkpoint
+        #[derive(Clone)]
+        struct Recorder {
+            frequency: CheckpointingFrequency,
+            saved: Arc<Mutex<Vec<(u64, TerminationReason)>>>,
+        }
+
+        impl<S> Checkpoint<S, TState> for Recorder {
+            fn save(&self, _solver: &S, state: &TState) -> Result<(), Error> {
+                self.saved
+                    .lock()
+                    .unwrap()
+                    .push((state.get_iter(), state.get_termination_reason()));
+                Ok(())
+            }
+
+            fn load(&self) -> Result<Option<(S, TState)>, Error> {
+                Ok(None)
+            }
+
+            fn frequency(&self) -> CheckpointingFrequency {
+                self.frequency
+            }
+        }
+
+        // Cancels the token once the given iteration is reached
+        struct CancelAt(u64, CancellationToken);
+
+        impl TerminationCriterion<TState> for CancelAt {
+            fn terminate(&mut self, state: &TState) -> TerminationReason {
+                if state.get_iter() >= self.0 {
+                    self.1.cancel();
+                }
+                TerminationReason::NotTerminated
+            }
+        }
+
+        let saved = |frequency: CheckpointingFrequency| -> Vec<u64> {
+            let recorder = Recorder {
+                frequency,
+                saved: Arc::new(Mutex::new(vec![])),
+            };
+            Executor::new(
+                TestProblem::new(),
+                CostSequence(vec![3.0, 4.0, 2.0, 5.0, 1.0]),
+            )
+            .configure(|state| state.param(vec![1.0f64]).max_iters(5))
+            .checkpointing(recorder.clone())
+            .run()
+            .unwrap();
+            let saved = recorder.saved.lock().unwrap();
+            // The last checkpoint is saved on termination
+            if frequency != CheckpointingFrequency::Never {
+                assert_eq!(
+                    saved.last().unwrap(),
+                    &(5, TerminationReason::MaxItersReached)
+                );
+            }
+            saved.iter().map(|(iter, _)| *iter).collect()
+        };
+
+        assert_eq!(saved(CheckpointingFrequency::Never), Vec::<u64>::new());
+        assert_eq!(
+            saved(CheckpointingFrequency::Always),
+            vec![1, 2, 3, 4, 5, 5]
+        );
+        assert_eq!(saved(CheckpointingFrequency::Every(2)), vec![2, 4, 5]);
+        assert_eq!(
+            saved(CheckpointingFrequency::Interval(instant::Duration::ZERO)),
+            vec![1, 2, 3, 4, 5, 5]
+        );
+        assert_eq!(
+            saved(CheckpointingFrequency::Interval(
+                instant::Duration::from_secs(3600)
+            )),
+            vec![5]
+        );
+        // New best parameter vectors are found in iterations 0, 2 and 4
+        assert_eq!(saved(CheckpointingFrequency::NewBest), vec![1, 3, 5, 5]);
+        assert_eq!(saved(CheckpointingFrequency::OnTermination), vec![5]);
+
+        // A checkpoint is saved when the optimization is aborted
+        let recorder = Recorder {
+            frequency: CheckpointingFrequency::OnTermination,
+            saved: Arc::new(Mutex::new(vec![])),
+        };
+        let token = CancellationToken::new();
+        Executor::new(TestProblem::new(), TestSolver::new())
+            .configure(|state| state.param(vec![1.0f64]).max_iters(100))
+            .checkpointing(recorder.clone())
+            .cancellation_token(token.clone())
+            .add_termination(CancelAt(3, token))
+            .run()
+            .unwrap();
+        assert_eq!(
+            *recorder.saved.lock().unwrap(),
+            vec![(4, TerminationReason::Aborted)]
+        );
+    }
+
+    #[test]
+    #[cfg(feature = "serde1")]
+    fn test_resume_aborted_checkpoint() {
+        use crate::core::checkpointing::MemoryCheckpoint;
+
+        let checkpoint = MemoryCheckpoint::new(CheckpointingFrequency::OnTermination);
+        let mut stepper = Executor::new(TestProblem::new(), TestSolver::new())
+            .configure(|state| state.param(vec![1.0f64]).max_iters(10))
+            .checkpointing(checkpoint.clone())
+            .stepper()
+            .unwrap();
+        stepper.step().unwrap();
+        stepper.step().unwrap();
+        let result = stepper.finish().unwrap();
+        assert_eq!(
+            result.state.get_termination_reason(),
+            TerminationReason::Aborted
+        );
+
+        // The aborted optimization continues from the checkpoint
+        let mut stepper = Executor::new(TestProblem::new(), TestSolver::new())
+            .configure(|state| state.param(vec![1.0f64]).max_iters(10))
+            .checkpointing(checkpoint)
+            .stepper()
+            .unwrap();
+        assert_eq!(stepper.state().get_iter(), 2);
+        while stepper.step().unwrap() {}
+        let result = stepper.finish().unwrap();
+        assert_eq!(result.state.get_iter(), 10);
+        assert_eq!(
+            result.state.get_termination_reason(),
+            TerminationReason::MaxItersReached
+        );
+    }
+
     /// The solver's `init` should not be called when started from a checkpoint.
     /// See https://github.com/argmin-rs/argmin/issues/199.
     #[test]
@@ -632,12 +2017,15 @@ mod tests {
         // solver instance
         let solver = OptimizationAlgorithm { internal_state: 0 };
 
-        // Delete old checkpointing file
-        let _ = std::fs::remove_file(".checkpoints/init_test.arg");
+        // Delete old checkpointing files
+        let _ = std::fs::remove_dir_all(".checkpoints/init_test");
 
         // Create a checkpoint
-        let checkpoint =
-            FileCheckpoint::new(".checkpoints", "init_test", CheckpointingFrequency::Always);
+        let checkpoint = FileCheckpoint::new(
+            ".checkpoints/init_test",
+            "init_test",
+            CheckpointingFrequency::Always,
+        );
 
         // Create and run executor
         let executor = Executor::new(problem, solver)
@@ -661,7 +2049,62 @@ mod tests {
         // (1 from init plus 10 iterations where it is incremented by 1)
         assert_eq!(solver.internal_state, 11);
 
-        // Delete old checkpointing file
-        let _ = std::fs::remove_file(".checkpoints/init_test.arg");
+        // Delete old checkpointing files
+        let _ = std::fs::remove_dir_all(".checkpoints/init_test");
+    }
+
+    #[test]
+    #[cfg(feature = "serde1")]
+    fn test_checkpoint_resume() {
+        use crate::core::test_utils::{
+            check_checkpoint_resume, Rosenbrock, TestProblem, TestSolver,
+        };
+        use crate::solver::linesearch::MoreThuenteLineSearch;
+        use crate::solver::neldermead::NelderMead;
+        use crate::solver::quasinewton::LBFGS;
+        use crate::solver::simulatedannealing::SimulatedAnnealing;
+        use rand::SeedableRng;
+
+        check_checkpoint_resume(
+            TestProblem::new(),
+            TestSolver::new(),
+            |state| state.param(vec![1.0f64, 0.0]).max_iters(10),
+            4,
+        )
+        .unwrap();
+
+        let lbfgs: LBFGS<_, Vec<f64>, Vec<f64>, f64> = LBFGS::new(MoreThuenteLineSearch::new(), 5);
+        for interrupt in [0, 1, 7] {
+            check_checkpoint_resume(
+                Rosenbrock::new(),
+                lbfgs.clone(),
+                |state| state.param(vec![-1.2, 1.0]).max_iters(15),
+                interrupt,
+            )
+            .unwrap();
+        }
+
+        let nelder_mead: NelderMead<Vec<f64>, f64> = NelderMead::new().with_initial_params(vec![
+            vec![-1.2, 1.0],
+            vec![-1.0, 1.0],
+            vec![-1.2, 1.2],
+        ]);
+        check_checkpoint_resume(
+            Rosenbrock::new(),
+            nelder_mead,
+            |state| state.max_iters(20),
+            9,
+        )
+        .unwrap();
+
+        let rng = rand_xoshiro::Xoshiro256PlusPlus::seed_from_u64(42);
+        let annealing = SimulatedAnnealing::new(10.0, rng).unwrap();
+        check_checkpoint_resume(
+            TestProblem::new(),
+            annealing,
+            |state| state.param(vec![1.0f64, 0.0]).max_iters(10),
+            3,
+        )
+        .unwrap();
     }
 }
@@ -0,0 +1,909 @@
+// Copyright 2018-2022 argmin developers
+//
+// Licensed under the Apache License, Version 2.0 <LICENSE-APACHE or
+// http://apache.org/licenses/LICENSE-2.0> or the MIT license <LICENSE-MIT or
+// http://opensource.org/licenses/MIT>, at your option. This file may not be
+// copied, modified, or distributed except according to those terms.
+
+use crate::core::problem::report_evaluations;
+use crate::core::{ArgminFloat, CostFunction, Error, Gradient, Hessian, Jacobian, Operator};
+use argmin_math::{ArgminComplexParts, ArgminElements, ArgminMul, ArgminStackColumns, ArgminSub};
+use num_complex::Complex;
+
+/// Finite difference scheme used by [`FiniteDiff`]
+#[derive(Clone, Copy, Debug, Eq, PartialEq, Hash)]
+enum FiniteDiffMethod {
+    /// Forward differences
+    Forward,
+    /// Central differences
+    Central,
+}
+
+/// Step size for element `x` with relative step size `h`.
+pub(crate) fn scaled_step<F: ArgminFloat>(h: F, x: F) -> F {
+    h * x.abs().max(F::from_f64(1.0).unwrap())
+}
+
+/// Returns the configured step size or `eps^(1/root)` of the float type.
+pub(crate) fn step_or_default<F: ArgminFloat>(step_size: Option<f64>, root: f64) -> F {
+    match step_size {
+        Some(step_size) => F::from_f64(step_size).unwrap(),
+        None => F::epsilon().powf(F::from_f64(1.0 / root).unwrap()),
+    }
+}
+
+/// Assembles a symmetric matrix from the upper triangle `upper[i][j - i]` (`j >= i`).
+fn symmetric_from_upper<P, F>(upper: Vec<Vec<F>>) -> <P as ArgminStackColumns<P>>::Matrix
+where
+    P: ArgminElements<Element = F> + ArgminStackColumns<P>,
+    F: ArgminFloat,
+{
+    let n = upper.len();
+    let columns = (0..n)
+        .map(|j| {
+            P::from_elements(
+                (0..n)
+                    .map(|i| {
+                        if i <= j {
+                            upper[i][j - i]
+                        } else {
+                            upper[j][i - j]
+                        }
+                    })
+                    .collect(),
+            )
+        })
+        .collect();
+    P::stack_columns(columns)
+}
+
+/// Computes derivatives of a problem via finite differences
+///
+/// Wraps a problem which implements [`CostFunction`] and/or [`Operator`] and additionally
+/// implements [`Gradient`] and [`Hessian`] (based on the cost function) as well as [`Jacobian`]
+/// (based on the operator) via forward or central differences. This allows one to use solvers
+/// which require derivatives with problems for which no derivatives are available.
+///
+/// The parameter vector is required to implement
+/// [`ArgminElements`](`argmin_math::ArgminElements`) and, for Hessians and Jacobians,
+/// [`ArgminStackColumns`](`argmin_math::ArgminStackColumns`), which are available for all
+/// backends of `argmin-math` (`Vec`, `ndarray` and `nalgebra`). The gradient is of the same type
+/// as the parameter vector.
+///
+/// The step size for element `x_i` is `h * max(1, |x_i|)`, where the relative step size `h`
+/// defaults to `eps^(1/2)` (forward) or `eps^(1/3)` (central) for gradients and Jacobians and to
+/// `eps^(1/3)` (forward) or `eps^(1/4)` (central) for Hessians, with `eps` being the machine
+/// epsilon of the float type. A fixed relative step size can be set via
+/// [`with_step_size`](`FiniteDiff::with_step_size`).
+///
+/// All evaluations of the wrapped cost function or operator are added to the `cost_count` or
+/// `operator_count` function evaluation counts of the [`Problem`](`crate::core::Problem`).
+///
+/// # Example
+///
+/// ```
+/// use argmin::core::{CostFunction, Error, FiniteDiff, Gradient};
+///
+/// struct Paraboloid {}
+///
+/// impl CostFunction for Paraboloid {
+///     type Param = Vec<f64>;
+///     type Output = f64;
+///
+///     fn cost(&self, param: &Self::Param) -> Result<Self::Output, Error> {
+///         Ok(param[0].powi(2) + 2.0 * param[1].powi(2))
+///     }
+/// }
+///
+/// // `problem` implements `CostFunction` and `Gradient` (and `Hessian`)
+/// let problem = FiniteDiff::central(Paraboloid {});
+///
+/// let grad = problem.gradient(&vec![1.0, 1.0])?;
+/// # assert!((grad[0] - 2.0).abs() < 1e-6);
+/// # assert!((grad[1] - 4.0).abs() < 1e-6);
+/// # Ok::<(), Error>(())
+/// ```
+#[derive(Clone, Debug)]
+pub struct FiniteDiff<O> {
+    /// Wrapped problem
+    problem: O,
+    /// Finite difference scheme
+    method: FiniteDiffMethod,
+    /// Relative step size
+    step_size: Option<f64>,
+}
+
+impl<O> FiniteDiff<O> {
+    /// Wraps `problem` and computes derivatives via forward differences.
+    ///
+    /// Forward differences require `n + 1` evaluations for a gradient or Jacobian, where `n` is
+    /// the number of parameters.
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// # use argmin::core::test_utils::TestProblem;
+    /// use argmin::core::FiniteDiff;
+    ///
+    /// let problem = FiniteDiff::forward(TestProblem::new());
+    /// ```
+    pub fn forward(problem: O) -> Self {
+        FiniteDiff {
+            problem,
+            method: FiniteDiffMethod::Forward,
+            step_size: None,
+        }
+    }
+
+    /// Wraps `problem` and computes derivatives via central differences.
+    ///
+    /// Central differences are more accurate than forward differences but require `2n`
+    /// evaluations for a gradient or Jacobian, where `n` is the number of parameters.
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// # use argmin::core::test_utils::TestProblem;
+    /// use argmin::core::FiniteDiff;
+    ///
+    /// let problem = FiniteDiff::central(TestProblem::new());
+    /// ```
+    pub fn central(problem: O) -> Self {
+        FiniteDiff {
+            problem,
+            method: FiniteDiffMethod::Central,
+            step_size: None,
+        }
+    }
+
+    /// Sets the relative step size (default: depends on the scheme, see [`FiniteDiff`]).
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// # use argmin::core::test_utils::TestProblem;
+    /// use argmin::core::FiniteDiff;
+    ///
+    /// let problem = FiniteDiff::forward(TestProblem::new()).with_step_size(1e-6);
+    /// ```
+    #[must_use]
+    pub fn with_step_size(mut self, step_size: f64) -> Self {
+        self.step_size = Some(step_size);
+        self
+    }
+
+    /// Returns a reference to the wrapped problem.
+    pub fn inner(&self) -> &O {
+        &self.problem
+    }
+
+    /// Returns the wrapped problem.
+    pub fn into_inner(self) -> O {
+        self.problem
+    }
+
+    /// Relative step size for first derivatives
+    fn first_order_step<F: ArgminFloat>(&self) -> F {
+        match self.method {
+            FiniteDiffMethod::Forward => step_or_default(self.step_size, 2.0),
+            FiniteDiffMethod::Central => step_or_default(self.step_size, 3.0),
+        }
+    }
+
+    /// Relative step size for second derivatives
+    fn second_order_step<F: ArgminFloat>(&self) -> F {
+        match self.method {
+            FiniteDiffMethod::Forward => step_or_default(self.step_size, 3.0),
+            FiniteDiffMethod::Central => step_or_default(self.step_size, 4.0),
+        }
+    }
+}
+
+impl<O: CostFunction> FiniteDiff<O> {
+    /// Evaluates the wrapped cost function and reports the evaluation.
+    fn eval_cost(&self, param: &O::Param) -> Result<O::Output, Error> {
+        report_evaluations("cost_count", 1);
+        self.problem.cost(param)
+    }
+
+    /// Computes the gradient based on the cost function value `f0` at `param` (only required for
+    /// forward differences).
+    fn gradient_with_cost<P, F>(&self, param: &P, f0: Option<F>) -> Result<P, Error>
+    where
+        O: CostFunction<Param = P, Output = F>,
+        P: ArgminElements<Element = F>,
+        F: ArgminFloat,
+    {
+        let h: F = self.first_order_step();
+        let grad = (0..param.num_elements())
+            .map(|i| {
+                let hi = scaled_step(h, param.get_element(i));
+                match (self.method, f0) {
+                    (FiniteDiffMethod::Forward, Some(f0)) => {
+                        Ok((self.eval_cost(&param.add_to_element(i, hi))? - f0) / hi)
+                    }
+                    _ => {
+                        let fp = self.eval_cost(&param.add_to_element(i, hi))?;
+                        let fm = self.eval_cost(&param.add_to_element(i, -hi))?;
+                        Ok((fp - fm) / (F::from_f64(2.0).unwrap() * hi))
+                    }
+                }
+            })
+            .collect::<Result<Vec<F>, Error>>()?;
+        Ok(P::from_elements(grad))
+    }
+}
+
+impl<O: CostFunction> CostFunction for FiniteDiff<O> {
+    type Param = O::Param;
+    type Output = O::Output;
+
+    fn cost(&self, param: &Self::Param) -> Result<Self::Output, Error> {
+        self.problem.cost(param)
+    }
+
+    fn parallelize(&self) -> bool {
+        self.problem.parallelize()
+    }
+}
+
+impl<O: Operator> Operator for FiniteDiff<O> {
+    type Param = O::Param;
+    type Output = O::Output;
+
+    fn apply(&self, param: &Self::Param) -> Result<Self::Output, Error> {
+        self.problem.apply(param)
+    }
+}
+
+impl<O, P, F> Gradient for FiniteDiff<O>
+where
+    O: CostFunction<Param = P, Output = F>,
+    P: ArgminElements<Element = F>,
+    F: ArgminFloat,
+{
+    type Param = P;
+    type Gradient = P;
+
+    fn gradient(&self, param: &Self::Param) -> Result<Self::Gradient, Error> {
+        let f0 = match self.method {
+            FiniteDiffMethod::Forward => Some(self.eval_cost(param)?),
+            FiniteDiffMethod::Central => None,
+        };
+        self.gradient_with_cost(param, f0)
+    }
+
+    /// The cost function value at `param` is reused for forward differences.
+    fn cost_and_gradient(
+        &self,
+        param: &Self::Param,
+    ) -> Result<(<Self as CostFunction>::Output, Self::Gradient), Error> {
+        let f0 = self.problem.cost(param)?;
+        Ok((f0, self.gradient_with_cost(param, Some(f0))?))
+    }
+}
+
+impl<O, P, F> Hessian for FiniteDiff<O>
+where
+    O: CostFunction<Param = P, Output = F>,
+    P: ArgminElements<Element = F> + ArgminStackColumns<P>,
+    F: ArgminFloat,
+{
+    type Param = P;
+    type Hessian = <P as ArgminStackColumns<P>>::Matrix;
+
+    fn hessian(&self, param: &Self::Param) -> Result<Self::Hessian, Error> {
+        let n = param.num_elements();
+        let h: F = self.second_order_step();
+        let steps: Vec<F> = (0..n)
+            .map(|i| scaled_step(h, param.get_element(i)))
+            .collect();
+        let upper = match self.method {
+            FiniteDiffMethod::Forward => {
+                let f0 = self.eval_cost(param)?;
+                let fi = (0..n)
+                    .map(|i| self.eval_cost(&param.add_to_element(i, steps[i])))
+                    .collect::<Result<Vec<F>, Error>>()?;
+                (0..n)
+                    .map(|i| {
+                        (i..n)
+                            .map(|j| {
+                                let x = param.add_to_element(i, steps[i]);
+                                let fij = self.eval_cost(&x.add_to_element(j, steps[j]))?;
+                                Ok((fij - fi[i] - fi[j] + f0) / (steps[i] * steps[j]))
+                            })
+                            .collect::<Result<Vec<F>, Error>>()
+                    })
+                    .collect::<Result<Vec<_>, Error>>()?
+            }
+            FiniteDiffMethod::Central => (0..n)
+                .map(|i| {
+                    (i..n)
+                        .map(|j| {
+                            let xp = param.add_to_element(i, steps[i]);
+                            let xm = param.add_to_element(i, -steps[i]);
+                            let fpp = self.eval_cost(&xp.add_to_element(j, steps[j]))?;
+                            let fpm = self.eval_cost(&xp.add_to_element(j, -steps[j]))?;
+                            let fmp = self.eval_cost(&xm.add_to_element(j, steps[j]))?;
+                            let fmm = self.eval_cost(&xm.add_to_element(j, -steps[j]))?;
+                            Ok((fpp - fpm - fmp + fmm)
+                                / (F::from_f64(4.0).unwrap() * steps[i] * steps[j]))
+                        })
+                        .collect::<Result<Vec<F>, Error>>()
+                })
+                .collect::<Result<Vec<_>, Error>>()?,
+        };
+        Ok(symmetric_from_upper::<P, F>(upper))
+    }
+}
+
+impl<O, P, V, F> Jacobian for FiniteDiff<O>
+where
+    O: Operator<Param = P, Output = V>,
+    P: ArgminElements<Element = F>,
+    V: ArgminSub<V, V> + ArgminMul<F, V> + ArgminStackColumns<P>,
+    F: ArgminFloat,
+{
+    type Param = P;
+    type Jacobian = <V as ArgminStackColumns<P>>::Matrix;
+
+    fn jacobian(&self, param: &Self::Param) -> Result<Self::Jacobian, Error> {
+        let eval = |p: &P| {
+            report_evaluations("operator_count", 1);
+            self.problem.apply(p)
+        };
+        let h: F = self.first_order_step();
+        let r0 = match self.method {
+            FiniteDiffMethod::Forward => Some(eval(param)?),
+            FiniteDiffMethod::Central => None,
+        };
+        let columns = (0..param.num_elements())
+            .map(|i| {
+                let hi = scaled_step(h, param.get_element(i));
+                let rp = eval(&param.add_to_element(i, hi))?;
+                match r0.as_ref() {
+                    Some(r0) => Ok(rp.sub(r0).mul(&(F::from_f64(1.0).unwrap() / hi))),
+                    None => {
+                        let rm = eval(&param.add_to_element(i, -hi))?;
+                        Ok(rp
+                            .sub(&rm)
+                            .mul(&(F::from_f64(1.0).unwrap() / (F::from_f64(2.0).unwrap() * hi))))
+                    }
+                }
+            })
+            .collect::<Result<Vec<V>, Error>>()?;
+        Ok(V::stack_columns(columns))
+    }
+}
+
+/// Computes derivatives of a problem via the complex-step method
+///
+/// Wraps a problem whose [`CostFunction`] and/or [`Operator`] is defined for complex parameter
+/// vectors and implements `CostFunction`, [`Gradient`] and [`Hessian`] as well as `Operator` and
+/// [`Jacobian`] for the corresponding real parameter vectors. The real cost function value (or
+/// operator output) is the real part of the complex one.
+///
+/// In contrast to finite differences, the complex-step method does not suffer from cancellation
+/// errors, therefore gradients and Jacobians are accurate to machine precision. This requires the
+/// wrapped function to be analytic, i.e. it must be implemented purely in terms of complex
+/// arithmetic (in particular, it must not use `abs` or take the conjugate).
+///
+/// The step size for element `x_i` is `h * max(1, |x_i|)`, where the relative step size `h`
+/// defaults to `1e-20` for gradients and Jacobians and to `eps^(1/4)` for Hessians (`eps` being
+/// the machine epsilon of the float type). It can be set via
+/// [`with_step_size`](`ComplexStep::with_step_size`).
+///
+/// All evaluations of the wrapped cost function or operator are added to the `cost_count` or
+/// `operator_count` function evaluation counts of the [`Problem`](`crate::core::Problem`).
+///
+/// # Example
+///
+/// ```
+/// use argmin::core::{ComplexStep, CostFunction, Error, Gradient};
+/// use num_complex::Complex;
+///
+/// struct Paraboloid {}
+///
+/// impl CostFunction for Paraboloid {
+///     type Param = Vec<Complex<f64>>;
+///     type Output = Complex<f64>;
+///
+///     fn cost(&self, param: &Self::Param) -> Result<Self::Output, Error> {
+///         Ok(param[0].powi(2) + 2.0 * param[1].powi(2))
+///     }
+/// }
+///
+/// // `problem` implements `CostFunction`, `Gradient` and `Hessian` for `Vec<f64>`
+/// let problem = ComplexStep::new(Paraboloid {});
+///
+/// let cost = problem.cost(&vec![1.0, 1.0])?;
+/// let grad = problem.gradient(&vec![1.0, 1.0])?;
+/// # assert!((cost - 3.0).abs() < 1e-12);
+/// # assert!((grad[0] - 2.0).abs() < 1e-12);
+/// # assert!((grad[1] - 4.0).abs() < 1e-12);
+/// # Ok::<(), Error>(())
+/// ```
+#[derive(Clone, Debug)]
+pub struct ComplexStep<O> {
+    /// Wrapped problem
+    problem: O,
+    /// Relative step size
+    step_size: Option<f64>,
+}
+
+impl<O> ComplexStep<O> {
+    /// Wraps `problem` and computes derivatives via the complex-step method.
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// # use argmin::core::test_utils::TestProblem;
+    /// use argmin::core::ComplexStep;
+    ///
+    /// let problem = ComplexStep::new(TestProblem::new());
+    /// ```
+    pub fn new(problem: O) -> Self {
+        ComplexStep {
+            problem,
+            step_size: None,
+        }
+    }
+
+    /// Sets the relative step size (default: depends on the derivative, see [`ComplexStep`]).
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// # use argmin::core::test_utils::TestProblem;
+    /// use argmin::core::ComplexStep;
+    ///
+    /// let problem = ComplexStep::new(TestProblem::new()).with_step_size(1e-30);
+    /// ```
+    #[must_use]
+    pub fn with_step_size(mut self, step_size: f64) -> Self {
+        self.step_size = Some(step_size);
+        self
+    }
+
+    /// Returns a reference to the wrapped problem.
+    pub fn inner(&self) -> &O {
+        &self.problem
+    }
+
+    /// Returns the wrapped problem.
+    pub fn into_inner(self) -> O {
+        self.problem
+    }
+
+    /// Relative step size for first derivatives
+    fn first_order_step<F: ArgminFloat>(&self) -> F {
+        F::from_f64(self.step_size.unwrap_or(1e-20)).unwrap()
+    }
+}
+
+impl<O, CP, C> ComplexStep<O>
+where
+    O: CostFunction<Param = CP, Output = C>,
+{
+    /// Evaluates the wrapped cost function and reports the evaluation.
+    fn eval_cost(&self, param: &CP) -> Result<C, Error> {
+        report_evaluations("cost_count", 1);
+        self.problem.cost(param)
+    }
+}
+
+impl<O, CP, C, P, F> CostFunction for ComplexStep<O>
+where
+    O: CostFunction<Param = CP, Output = C>,
+    CP: ArgminComplexParts<Real = P>,
+    C: ArgminComplexParts<Real = F>,
+{
+    type Param = P;
+    type Output = F;
+
+    fn cost(&self, param: &Self::Param) -> Result<Self::Output, Error> {
+        Ok(self.problem.cost(&CP::from_real(param))?.real_part())
+    }
+
+    fn parallelize(&self) -> bool {
+        self.problem.parallelize()
+    }
+}
+
+impl<O, CP, CV, P, V> Operator for ComplexStep<O>
+where
+    O: Operator<Param = CP, Output = CV>,
+    CP: ArgminComplexParts<Real = P>,
+    CV: ArgminComplexParts<Real = V>,
+{
+    type Param = P;
+    type Output = V;
+
+    fn apply(&self, param: &Self::Param) -> Result<Self::Output, Error> {
+        Ok(self.problem.apply(&CP::from_real(param))?.real_part())
+    }
+}
+
+impl<O, CP, C, P, F> Gradient for ComplexStep<O>
+where
+    O: CostFunction<Param = CP, Output = C>,
+    CP: ArgminComplexParts<Real = P> + ArgminElements<Element = Complex<F>>,
+    C: ArgminComplexParts<Real = F>,
+    P: ArgminElements<Element = F>,
+    F: ArgminFloat,
+{
+    type Param = P;
+    type Gradient = P;
+
+    fn gradient(&self, param: &Self::Param) -> Result<Self::Gradient, Error> {
+        let h: F = self.first_order_step();
+        let cparam = CP::from_real(param);
+        let grad = (0..param.num_elements())
+            .map(|i| {
+                let hi = scaled_step(h, param.get_element(i));
+                let c = self.eval_cost(&cparam.add_to_element(i, Complex::new(F::zero(), hi)))?;
+                Ok(c.imag_part() / hi)
+            })
+            .collect::<Result<Vec<F>, Error>>()?;
+        Ok(P::from_elements(grad))
+    }
+
+    /// The cost function value is obtained from the real part of the first perturbed evaluation.
+    /// This evaluation is not reported, since it is already counted as the cost function
+    /// evaluation of this call.
+    fn cost_and_gradient(
+        &self,
+        param: &Self::Param,
+    ) -> Result<(<Self as CostFunction>::Output, Self::Gradient), Error> {
+        let h: F = self.first_order_step();
+        let cparam = CP::from_real(param);
+        let mut cost = None;
+        let grad = (0..param.num_elements())
+            .map(|i| {
+                let hi = scaled_step(h, param.get_element(i));
+                let xi = cparam.add_to_element(i, Complex::new(F::zero(), hi));
+                let c = if cost.is_none() {
+                    self.problem.cost(&xi)?
+                } else {
+                    self.eval_cost(&xi)?
+                };
+                cost.get_or_insert_with(|| c.real_part());
+                Ok(c.imag_part() / hi)
+            })
+            .collect::<Result<Vec<F>, Error>>()?;
+        let cost = match cost {
+            Some(cost) => cost,
+            None => self.problem.cost(&cparam)?.real_part(),
+        };
+        Ok((cost, P::from_elements(grad)))
+    }
+}
+
+impl<O, CP, C, P, F> Hessian for ComplexStep<O>
+where
+    O: CostFunction<Param = CP, Output = C>,
+    CP: ArgminComplexParts<Real = P> + ArgminElements<Element = Complex<F>>,
+    C: ArgminComplexParts<Real = F>,
+    P: ArgminElements<Element = F> + ArgminStackColumns<P>,
+    F: ArgminFloat,
+{
+    type Param = P;
+    type Hessian = <P as ArgminStackColumns<P>>::Matrix;
+
+    /// Computes `(Im f(x + i h_i e_i + h_j e_j) - Im f(x + i h_i e_i - h_j e_j)) / (2 h_i h_j)`.
+    fn hessian(&self, param: &Self::Param) -> Result<Self::Hessian, Error> {
+        let n = param.num_elements();
+        let h: F = step_or_default(self.step_size, 4.0);
+        let steps: Vec<F> = (0..n)
+            .map(|i| scaled_step(h, param.get_element(i)))
+            .collect();
+        let cparam = CP::from_real(param);
+        let upper = (0..n)
+            .map(|i| {
+                let x = cparam.add_to_element(i, Complex::new(F::zero(), steps[i]));
+                (i..n)
+                    .map(|j| {
+                        let fp = self
+                            .eval_cost(&x.add_to_element(j, Complex::new(steps[j], F::zero())))?;
+                        let fm = self
+                            .eval_cost(&x.add_to_element(j, Complex::new(-steps[j], F::zero())))?;
+                        Ok((fp.imag_part() - fm.imag_part())
+                            / (F::from_f64(2.0).unwrap() * steps[i] * steps[j]))
+                    })
+                    .collect::<Result<Vec<F>, Error>>()
+            })
+            .collect::<Result<Vec<_>, Error>>()?;
+        Ok(symmetric_from_upper::<P, F>(upper))
+    }
+}
+
+impl<O, CP, CV, P, V, F> Jacobian for ComplexStep<O>
+where
+    O: Operator<Param = CP, Output = CV>,
+    CP: ArgminComplexParts<Real = P> + ArgminElements<Element = Complex<F>>,
+    CV: ArgminComplexParts<Real = V>,
+    P: ArgminElements<Element = F>,
+    V: ArgminMul<F, V> + ArgminStackColumns<P>,
+    F: ArgminFloat,
+{
+    type Param = P;
+    type Jacobian = <V as ArgminStackColumns<P>>::Matrix;
+
+    fn jacobian(&self, param: &Self::Param) -> Result<Self::Jacobian, Error> {
+        let h: F = self.first_order_step();
+        let cparam = CP::from_real(param);
+        let columns = (0..param.num_elements())
+            .map(|i| {
+                let hi = scaled_step(h, param.get_element(i));
+                report_evaluations("operator_count", 1);
+                let r = self
+                    .problem
+                    .apply(&cparam.add_to_element(i, Complex::new(F::zero(), hi)))?;
+                Ok(r.imag_part().mul(&(F::from_f64(1.0).unwrap() / hi)))
+            })
+            .collect::<Result<Vec<V>, Error>>()?;
+        Ok(V::stack_columns(columns))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::core::{Executor, Problem, State};
+    use crate::solver::linesearch::MoreThuenteLineSearch;
+    use crate::solver::quasinewton::LBFGS;
+    use approx::assert_relative_eq;
+
+    /// `f(x) = x0^2 + 3 x0 x1 + 2 x1^3`
+    struct Polynomial {}
+
+    impl CostFunction for Polynomial {
+        type Param = Vec<f64>;
+        type Output = f64;
+
+        fn cost(&self, p: &Self::Param) -> Result<Self::Output, Error> {
+            Ok(p[0].powi(2) + 3.0 * p[0] * p[1] + 2.0 * p[1].powi(3))
+        }
+    }
+
+    /// Complex version of `Polynomial`
+    struct ComplexPolynomial {}
+
+    impl CostFunction for ComplexPolynomial {
+        type Param = Vec<Complex<f64>>;
+        type Output = Complex<f64>;
+
+        fn cost(&self, p: &Self::Param) -> Result<Self::Output, Error> {
+            Ok(p[0].powi(2) + 3.0 * p[0] * p[1] + 2.0 * p[1].powi(3))
+        }
+    }
+
+    /// `r(x) = [x0 x1, x0^2 + x1, 5]`
+    struct Residuals {}
+
+    impl Operator for Residuals {
+        type Param = Vec<f64>;
+        type Output = Vec<f64>;
+
+        fn apply(&self, p: &Self::Param) -> Result<Self::Output, Error> {
+            Ok(vec![p[0] * p[1], p[0].powi(2) + p[1], 5.0])
+        }
+    }
+
+    /// Complex version of `Residuals`
+    struct ComplexResiduals {}
+
+    impl Operator for ComplexResiduals {
+        type Param = Vec<Complex<f64>>;
+        type Output = Vec<Complex<f64>>;
+
+        fn apply(&self, p: &Self::Param) -> Result<Self::Output, Error> {
+            Ok(vec![
+                p[0] * p[1],
+                p[0].powi(2) + p[1],
+                Complex::new(5.0, 0.0),
+            ])
+        }
+    }
+
+    const X: [f64; 2] = [1.5, -2.0];
+
+    fn grad_target() -> Vec<f64> {
+        vec![2.0 * X[0] + 3.0 * X[1], 3.0 * X[0] + 6.0 * X[1].powi(2)]
+    }
+
+    fn hessian_target() -> Vec<Vec<f64>> {
+        vec![vec![2.0, 3.0], vec![3.0, 12.0 * X[1]]]
+    }
+
+    fn jacobian_target() -> Vec<Vec<f64>> {
+        vec![vec![X[1], X[0]], vec![2.0 * X[0], 1.0], vec![0.0, 0.0]]
+    }
+
+    fn assert_matrix_eq(a: &[Vec<f64>], b: &[Vec<f64>], epsilon: f64) {
+        assert_eq!(a.len(), b.len());
+        for (ra, rb) in a.iter().zip(b.iter()) {
+            assert_eq!(ra.len(), rb.len());
+            for (x, y) in ra.iter().zip(rb.iter()) {
+                assert_relative_eq!(x, y, epsilon = epsilon);
+            }
+        }
+    }
+
+    send_sync_test!(finitediff, FiniteDiff<Polynomial>);
+    send_sync_test!(complexstep, ComplexStep<ComplexPolynomial>);
+
+    #[test]
+    fn test_gradient() {
+        let x = X.to_vec();
+        for (problem, epsilon) in [
+            (FiniteDiff::forward(Polynomial {}), 1e-6),
+            (FiniteDiff::central(Polynomial {}), 1e-8),
+        ] {
+            assert_relative_eq!(problem.cost(&x).unwrap(), Polynomial {}.cost(&x).unwrap());
+            let grad = problem.gradient(&x).unwrap();
+            let (cost, grad2) = problem.cost_and_gradient(&x).unwrap();
+            assert_relative_eq!(cost, Polynomial {}.cost(&x).unwrap());
+            for i in 0..2 {
+                assert_relative_eq!(grad[i], grad_target()[i], epsilon = epsilon);
+                assert_relative_eq!(grad2[i], grad_target()[i], epsilon = epsilon);
+            }
+        }
+
+        let problem = ComplexStep::new(ComplexPolynomial {});
+        let grad = problem.gradient(&x).unwrap();
+        let (cost, grad2) = problem.cost_and_gradient(&x).unwrap();
+        assert_relative_eq!(cost, Polynomial {}.cost(&x).unwrap(), epsilon = 1e-14);
+        assert_relative_eq!(
+            problem.cost(&x).unwrap(),
+            Polynomial {}.cost(&x).unwrap(),
+            epsilon = 1e-14
+        );
+        for i in 0..2 {
+            assert_relative_eq!(grad[i], grad_target()[i], epsilon = 1e-14);
+            assert_relative_eq!(grad2[i], grad_target()[i], epsilon = 1e-14);
+        }
+    }
+
+    #[test]
+    fn test_hessian() {
+        let x = X.to_vec();
+        let hessian = FiniteDiff::forward(Polynomial {}).hessian(&x).unwrap();
+        assert_matrix_eq(&hessian, &hessian_target(), 1e-3);
+
+        let hessian = FiniteDiff::central(Polynomial {}).hessian(&x).unwrap();
+        assert_matrix_eq(&hessian, &hessian_target(), 1e-6);
+
+        let hessian = ComplexStep::new(ComplexPolynomial {}).hessian(&x).unwrap();
+        assert_matrix_eq(&hessian, &hessian_target(), 1e-6);
+    }
+
+    #[test]
+    fn test_jacobian() {
+        let x = X.to_vec();
+        let problem = FiniteDiff::forward(Residuals {});
+        assert_eq!(problem.apply(&x).unwrap(), Residuals {}.apply(&x).unwrap());
+        assert_matrix_eq(&problem.jacobian(&x).unwrap(), &jacobian_target(), 1e-6);
+
+        let jacobian = FiniteDiff::central(Residuals {}).jacobian(&x).unwrap();
+        assert_matrix_eq(&jacobian, &jacobian_target(), 1e-8);
+
+        let problem = ComplexStep::new(ComplexResiduals {});
+        assert_eq!(problem.apply(&x).unwrap(), Residuals {}.apply(&x).unwrap());
+        assert_matrix_eq(&problem.jacobian(&x).unwrap(), &jacobian_target(), 1e-14);
+    }
+
+    #[test]
+    fn test_step_size() {
+        let x = X.to_vec();
+        // A large step size leads to a large error of forward differences
+        let grad = FiniteDiff::forward(Polynomial {})
+            .with_step_size(1e-1)
+            .gradient(&x)
+            .unwrap();
+        assert!((grad[1] - grad_target()[1]).abs() > 1e-1);
+
+        // Central differences are exact for quadratic functions
+        let grad = FiniteDiff::central(Polynomial {})
+            .with_step_size(1e-1)
+            .gradient(&x)
+            .unwrap();
+        assert_relative_eq!(grad[0], grad_target()[0], epsilon = 1e-12);
+    }
+
+    #[test]
+    fn test_counts() {
+        let x = X.to_vec();
+
+        let mut problem = Problem::new(FiniteDiff::forward(Polynomial {}));
+        problem.gradient(&x).unwrap();
+        assert_eq!(problem.counts["gradient_count"], 1);
+        assert_eq!(problem.counts["cost_count"], 3);
+
+        problem.cost_and_gradient(&x).unwrap();
+        assert_eq!(problem.counts["gradient_count"], 2);
+        assert_eq!(problem.counts["cost_count"], 6);
+
+        problem.hessian(&x).unwrap();
+        assert_eq!(problem.counts["hessian_count"], 1);
+        assert_eq!(problem.counts["cost_count"], 12);
+
+        let mut problem = Problem::new(FiniteDiff::central(Polynomial {}));
+        problem.gradient(&x).unwrap();
+        assert_eq!(problem.counts["cost_count"], 4);
+
+        let mut problem = Problem::new(ComplexStep::new(ComplexPolynomial {}));
+        problem.cost_and_gradient(&x).unwrap();
+        assert_eq!(problem.counts["gradient_count"], 1);
+        assert_eq!(problem.counts["cost_count"], 2);
+
+        problem.gradient(&x).unwrap();
+        assert_eq!(problem.counts["gradient_count"], 2);
+        assert_eq!(problem.counts["cost_count"], 4);
+
+        let mut problem = Problem::new(FiniteDiff::forward(Residuals {}));
+        problem.jacobian(&x).unwrap();
+        assert_eq!(problem.counts["jacobian_count"], 1);
+        assert_eq!(problem.counts["operator_count"], 3);
+
+        let mut problem = Problem::new(ComplexStep::new(ComplexResiduals {}));
+        problem.jacobian(&x).unwrap();
+        assert_eq!(problem.counts["operator_count"], 2);
+
+        // Evaluations outside of `Problem` are not counted anywhere
+        FiniteDiff::forward(Polynomial {}).gradient(&x).unwrap();
+        let mut problem = Problem::new(Polynomial {});
+        problem.cost(&x).unwrap();
+        assert_eq!(problem.counts["cost_count"], 1);
+    }
+
+    #[test]
+    fn test_solver() {
+        use crate::core::test_utils::Rosenbrock;
+
+        let rosenbrock = Rosenbrock::new();
+        let linesearch = MoreThuenteLineSearch::new();
+        let solver = LBFGS::new(linesearch, 7);
+        let res = Executor::new(FiniteDiff::central(rosenbrock.clone()), solver)
+            .configure(|state| state.param(vec![-1.2, 1.0]).max_iters(100))
+            .run()
+            .unwrap();
+
+        let best = res.state().get_best_param().unwrap();
+        assert_relative_eq!(best[0], 1.0, epsilon = 1e-4);
+        assert_relative_eq!(best[1], 1.0, epsilon = 1e-4);
+        let counts = res.state().get_func_counts();
+        assert!(counts["cost_count"] > 4 * counts["gradient_count"]);
+        // Only the cost function of the wrapped problem is used
+        assert_eq!(rosenbrock.gradient_count(), 0);
+        assert_eq!(rosenbrock.cost_and_gradient_count(), 0);
+    }
+
+    #[cfg(feature = "nalgebral")]
+    #[test]
+    fn test_nalgebra() {
+        use nalgebra::{DMatrix, DVector};
+
+        struct NalgebraPolynomial {}
+
+        impl CostFunction for NalgebraPolynomial {
+            type Param = DVector<f64>;
+            type Output = f64;
+
+            fn cost(&self, p: &Self::Param) -> Result<Self::Output, Error> {
+                Polynomial {}.cost(&vec![p[0], p[1]])
+            }
+        }
+
+        let x = DVector::from_vec(X.to_vec());
+        let problem = FiniteDiff::central(NalgebraPolynomial {});
+        let grad: DVector<f64> = problem.gradient(&x).unwrap();
+        let hessian: DMatrix<f64> = problem.hessian(&x).unwrap();
+        for i in 0..2 {
+            assert_relative_eq!(grad[i], grad_target()[i], epsilon = 1e-8);
+            for j in 0..2 {
+                assert_relative_eq!(hessian[(i, j)], hessian_target()[i][j], epsilon = 1e-6);
+            }
+        }
+    }
+}
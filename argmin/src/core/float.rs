@@ -5,14 +5,14 @@
 // http://opensource.org/licenses/MIT>, at your option. This file may not be
 // copied, modified, or distributed except according to those terms.
 
-use crate::core::{DeserializeOwnedAlias, SerializeAlias};
+use crate::core::{DeserializeOwnedAlias, KvValue, SerializeAlias};
 use num_traits::{Float, FloatConst, FromPrimitive, ToPrimitive};
 use std::fmt::{Debug, Display};
 
 /// An alias for float types (`f32`, `f64`) which combines multiple commonly needed traits from
 /// `num_traits`, `std::fmt` and for serialization/deserialization (the latter only if the `serde1`
-/// feature is enabled). It is automatically implemented for all types which fulfill the trait
-/// bounds.
+/// feature is enabled) as well as the conversion into [`KvValue`]. It is automatically implemented
+/// for all types which fulfill the trait bounds.
 pub trait ArgminFloat:
     'static
     + Float
@@ -23,6 +23,7 @@ pub trait ArgminFloat:
     + Display
     + SerializeAlias
     + DeserializeOwnedAlias
+    + Into<KvValue>
 {
 }
 
@@ -37,5 +38,6 @@ impl<I> ArgminFloat for I where
         + Display
         + SerializeAlias
         + DeserializeOwnedAlias
+        + Into<KvValue>
 {
 }
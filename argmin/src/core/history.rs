@@ -0,0 +1,234 @@
+// Copyright 2018-2022 argmin developers
+//
+// Licensed under the Apache License, Version 2.0 <LICENSE-APACHE or
+// http://apache.org/licenses/LICENSE-2.0> or the MIT license <LICENSE-MIT or
+// http://opensource.org/licenses/MIT>, at your option. This file may not be
+// copied, modified, or distributed except according to those terms.
+
+use crate::core::{KvValue, State, KV};
+#[cfg(feature = "serde1")]
+use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
+
+/// Convergence history of an optimization run
+///
+/// Recorded by an [`Executor`](`crate::core::Executor`) if enabled via
+/// [`record_history`](`crate::core::Executor::record_history`) and returned as part of the
+/// [`OptimizationResult`](`crate::core::OptimizationResult`). Each iteration adds one
+/// [`HistoryEntry`]. With the `serde1` feature, the history can be serialized, for instance to
+/// JSON for plotting.
+///
+/// # Example
+///
+/// ```
+/// # use argmin::core::{Error, Executor};
+/// # use argmin::core::test_utils::{TestSolver, TestProblem};
+/// #
+/// # fn main() -> Result<(), Error> {
+/// # let solver = TestSolver::new();
+/// # let problem = TestProblem::new();
+/// #
+/// let result = Executor::new(problem, solver)
+///     .configure(|state| state.param(vec![1.0f64, 0.0]).max_iters(10))
+///     .record_history(false)
+///     .run()?;
+///
+/// let history = result.history.unwrap();
+/// for entry in history.entries.iter() {
+///     println!("{}: {} (best: {})", entry.iter, entry.cost, entry.best_cost);
+/// }
+/// # assert_eq!(history.entries.len(), 10);
+/// # Ok(())
+/// # }
+/// ```
+#[derive(Clone, Debug, Default)]
+#[cfg_attr(feature = "serde1", derive(Serialize, Deserialize))]
+pub struct History<P, F> {
+    /// Entries in the order of the iterations
+    pub entries: Vec<HistoryEntry<P, F>>,
+    /// Copies the parameter vector (only if parameter vectors are recorded)
+    #[cfg_attr(feature = "serde1", serde(skip))]
+    clone_param: Option<fn(&P) -> P>,
+}
+
+impl<P, F> History<P, F> {
+    /// Constructs a new empty `History`. If `params` is `true`, the parameter vector is recorded
+    /// in every iteration.
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// # use argmin::core::History;
+    /// let history: History<Vec<f64>, f64> = History::new(true);
+    /// # assert!(history.entries.is_empty());
+    /// ```
+    pub fn new(params: bool) -> Self
+    where
+        P: Clone,
+    {
+        History {
+            entries: vec![],
+            clone_param: if params { Some(P::clone) } else { None },
+        }
+    }
+
+    /// Adds an entry for the current iteration of `state`.
+    ///
+    /// `kv` holds the key-value pairs returned by the solver.
+    /// `time` is the elapsed time since the start of the optimization.
+    pub fn push<I>(&mut self, state: &I, kv: &KV, time: instant::Duration)
+    where
+        I: State<Param = P, Float = F>,
+    {
+        self.entries.push(HistoryEntry {
+            iter: state.get_iter(),
+            cost: state.get_cost(),
+            best_cost: state.get_best_cost(),
+            time,
+            func_counts: state.get_func_counts().clone(),
+            kv: kv
+                .kv
+                .iter()
+                .map(|(k, v)| (k.to_string(), v.clone()))
+                .collect(),
+            param: self
+                .clone_param
+                .and_then(|clone_param| state.get_param().map(clone_param)),
+        });
+    }
+}
+
+/// Two histories are equal if their entries are equal.
+impl<P: PartialEq, F: PartialEq> PartialEq for History<P, F> {
+    fn eq(&self, other: &Self) -> bool {
+        self.entries == other.entries
+    }
+}
+
+/// Records the convergence history while an [`Executor`](`crate::core::Executor`) runs.
+///
+/// Hides the type of the [`History`] such that the `Executor` does not need to name the
+/// parameter vector and float types of its state.
+pub(crate) trait Record<I> {
+    /// Prepares recording for a new run starting from `state`.
+    fn start(&mut self, state: &I);
+
+    /// Adds an entry for the current iteration of `state`; `time` is the elapsed time since the
+    /// start of the current run.
+    fn push(&mut self, state: &I, kv: &KV, time: instant::Duration);
+
+    /// Returns the recorded history.
+    fn into_history(self: Box<Self>) -> History<I::Param, I::Float>
+    where
+        I: State;
+}
+
+/// Records a [`History`] across several runs
+pub(crate) struct Recorder<P, F> {
+    /// Recorded history
+    history: History<P, F>,
+    /// Time which elapsed in previous runs
+    offset: instant::Duration,
+}
+
+impl<P, F> Recorder<P, F> {
+    /// Continues recording `history`
+    pub(crate) fn new(history: History<P, F>) -> Self {
+        Recorder {
+            history,
+            offset: instant::Duration::ZERO,
+        }
+    }
+}
+
+impl<I, P, F> Record<I> for Recorder<P, F>
+where
+    I: State<Param = P, Float = F>,
+{
+    /// Times of the new run are offset by the time of the last recorded entry or, if there is
+    /// none (for instance when resuming from a checkpoint), by the time stored in `state`.
+    fn start(&mut self, state: &I) {
+        self.offset = self
+            .history
+            .entries
+            .last()
+            .map(|entry| entry.time)
+            .or_else(|| state.get_time())
+            .unwrap_or_default();
+    }
+
+    fn push(&mut self, state: &I, kv: &KV, time: instant::Duration) {
+        self.history.push(state, kv, self.offset + time);
+    }
+
+    fn into_history(self: Box<Self>) -> History<P, F> {
+        self.history
+    }
+}
+
+/// Record of a single iteration in a [`History`]
+#[derive(Clone, Debug, PartialEq)]
+#[cfg_attr(feature = "serde1", derive(Serialize, Deserialize))]
+pub struct HistoryEntry<P, F> {
+    /// Iteration number
+    pub iter: u64,
+    /// Cost function value
+    pub cost: F,
+    /// Best cost function value so far
+    pub best_cost: F,
+    /// Elapsed time since the start of the optimization, including the runs it was resumed from
+    pub time: instant::Duration,
+    /// Function evaluation counts
+    pub func_counts: HashMap<String, u64>,
+    /// Key-value pairs returned by the solver
+    pub kv: Vec<(String, KvValue)>,
+    /// Parameter vector (only if recording of parameter vectors is enabled)
+    pub param: Option<P>,
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::core::IterState;
+
+    #[test]
+    fn test_push() {
+        let mut state: IterState<Vec<f64>, (), (), (), f64> =
+            IterState::new().param(vec![1.0, 2.0]).cost(3.0);
+        state.update();
+        state.increment_iter();
+        let kv = make_kv!("alpha" => 0.5;);
+
+        let mut history = History::new(false);
+        history.push(&state, &kv, instant::Duration::from_secs(1));
+        let mut history_params = History::new(true);
+        history_params.push(&state, &kv, instant::Duration::from_secs(1));
+
+        let entry = &history.entries[0];
+        assert_eq!(entry.iter, 1);
+        assert_eq!(entry.cost.to_bits(), 3.0f64.to_bits());
+        assert_eq!(entry.best_cost.to_bits(), 3.0f64.to_bits());
+        assert_eq!(entry.time, instant::Duration::from_secs(1));
+        assert_eq!(entry.kv, vec![("alpha".to_string(), KvValue::Float(0.5))]);
+        assert!(entry.param.is_none());
+        assert_eq!(history_params.entries[0].param, Some(vec![1.0, 2.0]));
+    }
+
+    #[cfg(feature = "serde1")]
+    #[test]
+    fn test_serialize() {
+        let mut state: IterState<Vec<f64>, (), (), (), f64> =
+            IterState::new().param(vec![1.0, 2.0]).cost(3.0);
+        state.update();
+        let mut history = History::new(true);
+        history.push(
+            &state,
+            &make_kv!("alpha" => 0.5;),
+            instant::Duration::from_secs(1),
+        );
+
+        let json = serde_json::to_string(&history).unwrap();
+        let deserialized: History<Vec<f64>, f64> = serde_json::from_str(&json).unwrap();
+        assert_eq!(deserialized, history);
+    }
+}
@@ -5,13 +5,217 @@
 // http://opensource.org/licenses/MIT>, at your option. This file may not be
 // copied, modified, or distributed except according to those terms.
 
+#[cfg(feature = "serde1")]
+use serde::{Deserialize, Serialize};
 use std::fmt;
 use std::fmt::Display;
-use std::rc::Rc;
+
+/// A value stored in a [`KV`]
+///
+/// Keeps the type of the value such that observers can process it accordingly, for instance by
+/// writing numbers as JSON numbers. Values are usually created via the `From` implementations
+/// for floats, integers, `bool`, strings and vectors of floats, which is what
+/// [`make_kv!`](`crate::make_kv`) does. Unsigned integers are stored as `Uint`, signed integers as
+/// `Int` and `f32` as `Float`.
+///
+/// # Example
+///
+/// ```
+/// use argmin::core::KvValue;
+///
+/// assert_eq!(KvValue::from(1.5), KvValue::Float(1.5));
+/// assert_eq!(KvValue::from(-2), KvValue::Int(-2));
+/// assert_eq!(KvValue::from(3u64), KvValue::Uint(3));
+/// assert_eq!(KvValue::from(true), KvValue::Bool(true));
+/// assert_eq!(KvValue::from("text"), KvValue::Str("text".to_string()));
+/// assert_eq!(KvValue::from(vec![1.0, 2.0]), KvValue::Vec(vec![1.0, 2.0]));
+/// ```
+#[derive(Clone, Debug, PartialEq)]
+#[cfg_attr(feature = "serde1", derive(Serialize, Deserialize))]
+pub enum KvValue {
+    /// Floating point number
+    Float(f64),
+    /// Signed integer
+    Int(i64),
+    /// Unsigned integer
+    Uint(u64),
+    /// Boolean
+    Bool(bool),
+    /// String
+    Str(String),
+    /// Vector of floating point numbers
+    Vec(Vec<f64>),
+}
+
+impl KvValue {
+    /// Returns the value as `f64` if it is a number.
+    ///
+    /// Integers are converted to `f64`, which may lose precision for large values.
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// # use argmin::core::KvValue;
+    /// assert_eq!(KvValue::Float(1.5).as_f64(), Some(1.5));
+    /// assert_eq!(KvValue::Uint(2).as_f64(), Some(2.0));
+    /// assert_eq!(KvValue::Bool(true).as_f64(), None);
+    /// ```
+    pub fn as_f64(&self) -> Option<f64> {
+        match *self {
+            KvValue::Float(x) => Some(x),
+            KvValue::Int(x) => Some(x as f64),
+            KvValue::Uint(x) => Some(x as f64),
+            _ => None,
+        }
+    }
+
+    /// Returns the value as `i64` if it is an integer which fits into an `i64`.
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// # use argmin::core::KvValue;
+    /// assert_eq!(KvValue::Int(-1).as_i64(), Some(-1));
+    /// assert_eq!(KvValue::Uint(2).as_i64(), Some(2));
+    /// assert_eq!(KvValue::Float(1.0).as_i64(), None);
+    /// ```
+    pub fn as_i64(&self) -> Option<i64> {
+        match *self {
+            KvValue::Int(x) => Some(x),
+            KvValue::Uint(x) => i64::try_from(x).ok(),
+            _ => None,
+        }
+    }
+
+    /// Returns the value as `u64` if it is a non-negative integer.
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// # use argmin::core::KvValue;
+    /// assert_eq!(KvValue::Uint(2).as_u64(), Some(2));
+    /// assert_eq!(KvValue::Int(3).as_u64(), Some(3));
+    /// assert_eq!(KvValue::Int(-1).as_u64(), None);
+    /// ```
+    pub fn as_u64(&self) -> Option<u64> {
+        match *self {
+            KvValue::Uint(x) => Some(x),
+            KvValue::Int(x) => u64::try_from(x).ok(),
+            _ => None,
+        }
+    }
+
+    /// Returns the value as `bool` if it is a boolean.
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// # use argmin::core::KvValue;
+    /// assert_eq!(KvValue::Bool(true).as_bool(), Some(true));
+    /// assert_eq!(KvValue::Int(1).as_bool(), None);
+    /// ```
+    pub fn as_bool(&self) -> Option<bool> {
+        match *self {
+            KvValue::Bool(x) => Some(x),
+            _ => None,
+        }
+    }
+
+    /// Returns the value as string slice if it is a string.
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// # use argmin::core::KvValue;
+    /// assert_eq!(KvValue::Str("text".to_string()).as_str(), Some("text"));
+    /// assert_eq!(KvValue::Float(1.0).as_str(), None);
+    /// ```
+    pub fn as_str(&self) -> Option<&str> {
+        match self {
+            KvValue::Str(x) => Some(x),
+            _ => None,
+        }
+    }
+
+    /// Returns the value as slice if it is a vector of floats.
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// # use argmin::core::KvValue;
+    /// assert_eq!(KvValue::Vec(vec![1.0, 2.0]).as_slice(), Some(&[1.0, 2.0][..]));
+    /// assert_eq!(KvValue::Float(1.0).as_slice(), None);
+    /// ```
+    pub fn as_slice(&self) -> Option<&[f64]> {
+        match self {
+            KvValue::Vec(x) => Some(x),
+            _ => None,
+        }
+    }
+}
+
+impl Display for KvValue {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            KvValue::Float(x) => write!(f, "{}", x),
+            KvValue::Int(x) => write!(f, "{}", x),
+            KvValue::Uint(x) => write!(f, "{}", x),
+            KvValue::Bool(x) => write!(f, "{}", x),
+            KvValue::Str(x) => write!(f, "{}", x),
+            KvValue::Vec(x) => write!(f, "{:?}", x),
+        }
+    }
+}
+
+macro_rules! kv_value_from {
+    ($variant:ident, $target:ty; $($t:ty),*) => {
+        $(
+            impl From<$t> for KvValue {
+                fn from(val: $t) -> Self {
+                    KvValue::$variant(val as $target)
+                }
+            }
+        )*
+    };
+}
+
+kv_value_from!(Float, f64; f32, f64);
+kv_value_from!(Int, i64; i8, i16, i32, i64, isize);
+kv_value_from!(Uint, u64; u8, u16, u32, u64, usize);
+
+impl From<bool> for KvValue {
+    fn from(val: bool) -> Self {
+        KvValue::Bool(val)
+    }
+}
+
+impl From<String> for KvValue {
+    fn from(val: String) -> Self {
+        KvValue::Str(val)
+    }
+}
+
+impl From<&str> for KvValue {
+    fn from(val: &str) -> Self {
+        KvValue::Str(val.to_string())
+    }
+}
+
+impl From<Vec<f64>> for KvValue {
+    fn from(val: Vec<f64>) -> Self {
+        KvValue::Vec(val)
+    }
+}
+
+impl From<&[f64]> for KvValue {
+    fn from(val: &[f64]) -> Self {
+        KvValue::Vec(val.to_vec())
+    }
+}
 
 /// A simple key-value storage
 ///
-/// Keeps pairs of `(&'static str, Rc<dyn Display>)` and is used to pass key-value pairs to
+/// Keeps pairs of `(&'static str, KvValue)` and is used to pass key-value pairs to
 /// [`Observers`](`crate::core::observers`) in each iteration of an optimization algorithm.
 /// Typically constructed using the [`make_kv!`](`crate::make_kv`) macro.
 ///
@@ -19,6 +223,7 @@ use std::rc::Rc;
 ///
 /// ```
 /// use argmin::make_kv;
+/// # use argmin::core::KvValue;
 ///
 /// let kv = make_kv!(
 ///     "key1" => "value1";
@@ -31,12 +236,12 @@ use std::rc::Rc;
 /// # assert_eq!(kv.kv[1].0, "key2");
 /// # assert_eq!(format!("{}", kv.kv[1].1), "value2");
 /// # assert_eq!(kv.kv[2].0, "key3");
-/// # assert_eq!(format!("{}", kv.kv[2].1), "1234");
+/// # assert_eq!(kv.kv[2].1, KvValue::Int(1234));
 /// ```
-#[derive(Clone, Default)]
+#[derive(Clone, Debug, Default, PartialEq)]
 pub struct KV {
     /// The actual key value storage
-    pub kv: Vec<(&'static str, Rc<dyn Display>)>,
+    pub kv: Vec<(&'static str, KvValue)>,
 }
 
 impl Display for KV {
@@ -69,39 +274,53 @@ impl KV {
     /// # Example
     ///
     /// ```
-    /// # use argmin::core::KV;
-    /// # use std::rc::Rc;
+    /// # use argmin::core::{KV, KvValue};
     ///
     /// let mut kv = KV::new();
-    /// kv.push("key", Rc::new("value"));
-    /// kv.push("key", Rc::new(1234));
+    /// kv.push("key", "value");
+    /// kv.push("key", 1234);
     /// # assert_eq!(kv.kv.len(), 2);
     /// # assert_eq!(kv.kv[0].0, "key");
     /// # assert_eq!(format!("{}", kv.kv[0].1), "value");
     /// # assert_eq!(kv.kv[1].0, "key");
-    /// # assert_eq!(format!("{}", kv.kv[1].1), "1234");
+    /// # assert_eq!(kv.kv[1].1, KvValue::Int(1234));
     /// ```
-    pub fn push(&mut self, key: &'static str, val: Rc<dyn Display>) -> &mut Self {
-        self.kv.push((key, val));
+    pub fn push<V: Into<KvValue>>(&mut self, key: &'static str, val: V) -> &mut Self {
+        self.kv.push((key, val.into()));
         self
     }
 
+    /// Returns the first value stored under `key`
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// # use argmin::core::{KV, KvValue};
+    ///
+    /// let mut kv = KV::new();
+    /// kv.push("key", 1.5);
+    /// assert_eq!(kv.get("key"), Some(&KvValue::Float(1.5)));
+    /// assert_eq!(kv.get("other"), None);
+    /// ```
+    pub fn get(&self, key: &str) -> Option<&KvValue> {
+        self.kv.iter().find(|(k, _)| *k == key).map(|(_, v)| v)
+    }
+
     /// Merge with another `KV`
     ///
     /// # Example
     ///
     /// ```
     /// # use argmin::core::KV;
-    /// # use std::rc::Rc;
     ///
     /// let mut kv1 = KV::new();
-    /// kv1.push("key1", Rc::new("value1"));
+    /// kv1.push("key1", "value1");
     /// # assert_eq!(kv1.kv.len(), 1);
     /// # assert_eq!(kv1.kv[0].0, "key1");
     /// # assert_eq!(format!("{}", kv1.kv[0].1), "value1");
     ///
     /// let mut kv2 = KV::new();
-    /// kv2.push("key2", Rc::new("value2"));
+    /// kv2.push("key2", "value2");
     /// # assert_eq!(kv2.kv.len(), 1);
     /// # assert_eq!(kv2.kv[0].0, "key2");
     /// # assert_eq!(format!("{}", kv2.kv[0].1), "value2");
@@ -120,8 +339,8 @@ impl KV {
     }
 }
 
-impl std::iter::FromIterator<(&'static str, Rc<dyn Display>)> for KV {
-    fn from_iter<I: IntoIterator<Item = (&'static str, Rc<dyn Display>)>>(iter: I) -> Self {
+impl std::iter::FromIterator<(&'static str, KvValue)> for KV {
+    fn from_iter<I: IntoIterator<Item = (&'static str, KvValue)>>(iter: I) -> Self {
         let mut c = KV::new();
         for i in iter {
             c.push(i.0, i.1);
@@ -130,10 +349,45 @@ impl std::iter::FromIterator<(&'static str, Rc<dyn Display>)> for KV {
     }
 }
 
-impl std::iter::Extend<(&'static str, Rc<dyn Display>)> for KV {
-    fn extend<I: IntoIterator<Item = (&'static str, Rc<dyn Display>)>>(&mut self, iter: I) {
+impl std::iter::Extend<(&'static str, KvValue)> for KV {
+    fn extend<I: IntoIterator<Item = (&'static str, KvValue)>>(&mut self, iter: I) {
         for i in iter {
             self.push(i.0, i.1);
         }
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    send_sync_test!(kv, KV);
+    send_sync_test!(kvvalue, KvValue);
+
+    #[test]
+    fn test_make_kv() {
+        let kv = make_kv!(
+            "float" => 1.5f32;
+            "int" => -3i32;
+            "uint" => 3usize;
+            "bool" => false;
+            "str" => String::from("text");
+            "vec" => vec![1.0, 2.0];
+        );
+        assert_eq!(
+            kv.kv,
+            vec![
+                ("float", KvValue::Float(1.5)),
+                ("int", KvValue::Int(-3)),
+                ("uint", KvValue::Uint(3)),
+                ("bool", KvValue::Bool(false)),
+                ("str", KvValue::Str("text".to_string())),
+                ("vec", KvValue::Vec(vec![1.0, 2.0])),
+            ]
+        );
+        assert_eq!(
+            format!("{}", kv),
+            "KV\n   float: 1.5\n   int: -3\n   uint: 3\n   bool: false\n   str: text\n   vec: [1.0, 2.0]\n"
+        );
+    }
+}
@@ -9,6 +9,10 @@
 
 /// Creates an `KV` at compile time
 ///
+/// Values are converted into [`KvValue`](`crate::core::KvValue`)s via `Into`, therefore all types
+/// with a corresponding `From` implementation (floats, integers, `bool`, strings and vectors of
+/// floats) can be used.
+///
 /// # Example
 ///
 /// ```
@@ -30,7 +34,7 @@
 #[macro_export]
 macro_rules! make_kv {
     ($($k:expr =>  $v:expr;)*) => {
-        $crate::core::KV { kv: vec![ $(($k, std::rc::Rc::new($v))),* ] }
+        $crate::core::KV { kv: vec![ $(($k, ::std::convert::Into::<$crate::core::KvValue>::into($v))),* ] }
     };
 }
 
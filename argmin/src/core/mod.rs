@@ -13,18 +13,34 @@
 /// Macros
 #[macro_use]
 pub mod macros;
+/// Caching of expensive problems
+mod cache;
+/// Cancellation of running optimizations
+mod cancellation;
+/// Chaining of solvers
+mod chain;
 /// Checkpointing
 pub mod checkpointing;
+/// Verification of derivatives
+mod derivative_check;
 /// Error handling
 mod errors;
 /// Executor
 mod executor;
+/// Finite difference adapters
+mod finitediff;
 /// Trait alias for float types
 mod float;
+/// Convergence history
+mod history;
 /// Key value datastructure
 mod kv;
+/// Multi-start optimization
+mod multistart;
 /// Observers
 pub mod observers;
+/// Trait aliases for parallelization
+mod parallelization;
 /// Traits and structs for defining and handling optimization problems
 mod problem;
 /// Definition of the return type of the solvers
@@ -37,6 +53,8 @@ mod solver;
 mod state;
 /// Definition of termination reasons
 mod termination;
+/// Termination criteria
+pub mod termination_criteria;
 /// Convenience utilities for testing
 pub mod test_utils;
 
@@ -44,11 +62,21 @@ pub use crate::solver::conjugategradient::beta::NLCGBetaUpdate;
 pub use crate::solver::linesearch::LineSearch;
 pub use crate::solver::trustregion::TrustRegionRadius;
 pub use anyhow::Error;
+pub use cache::CachedProblem;
+pub use cancellation::CancellationToken;
+pub use chain::{Chain, ChainStage};
+pub use derivative_check::{ComponentError, DerivativeCheck, DerivativeReport};
 pub use errors::ArgminError;
-pub use executor::Executor;
+pub use executor::{Executor, Stepper};
+pub use finitediff::{ComplexStep, FiniteDiff};
 pub use float::ArgminFloat;
-pub use kv::KV;
-pub use problem::{CostFunction, Gradient, Hessian, Jacobian, LinearProgram, Operator, Problem};
+pub use history::{History, HistoryEntry};
+pub use kv::{KvValue, KV};
+pub use multistart::{MultiStart, MultiStartResult, MultiStartRun};
+pub use parallelization::{SendAlias, SyncAlias};
+pub use problem::{
+    CostFunction, CostGradient, Gradient, Hessian, Jacobian, LinearProgram, Operator, Problem,
+};
 pub use result::OptimizationResult;
 pub use serialization::{DeserializeOwnedAlias, SerializeAlias};
 pub use solver::Solver;
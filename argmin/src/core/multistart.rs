@@ -0,0 +1,574 @@
+// Copyright 2018-2022 argmin developers
+//
+// Licensed under the Apache License, Version 2.0 <LICENSE-APACHE or
+// http://apache.org/licenses/LICENSE-2.0> or the MIT license <LICENSE-MIT or
+// http://opensource.org/licenses/MIT>, at your option. This file may not be
+// copied, modified, or distributed except according to those terms.
+
+use crate::core::{
+    ArgminFloat, DeserializeOwnedAlias, Error, Executor, IterState, OptimizationResult, SendAlias,
+    SerializeAlias, Solver, State, SyncAlias, TerminationReason,
+};
+use argmin_math::ArgminRandom;
+use num_traits::Float;
+use std::collections::HashMap;
+
+/// Samples an initial parameter vector
+type Sampler<P> = Box<dyn FnMut() -> P>;
+
+/// Configures the state of a single run
+type Configure<I> = Box<dyn Fn(I) -> I + Send + Sync>;
+
+/// Prepares the solver of a single run for an initial parameter vector
+type SetupSolver<S, P> = Box<dyn Fn(S, &P) -> S>;
+
+/// Initial parameter vector and result of a single run
+type RunResult<O, S, I> = (
+    <I as State>::Param,
+    Result<OptimizationResult<O, S, I>, Error>,
+);
+
+/// Result of `MultiStart::run` for `IterState`
+type MultiStartIterResult<O, S, P, G, J, H, F> =
+    Result<MultiStartResult<O, S, IterState<P, G, J, H, F>>, Error>;
+
+/// Runs a local solver from multiple initial parameter vectors
+///
+/// Local solvers only find the local minimum closest to their initial parameter vector. In order
+/// to search for the global minimum of a multimodal problem, `MultiStart` samples a number of
+/// initial parameter vectors, either uniformly within given [`bounds`](`MultiStart::bounds`) or
+/// via a user-defined [`sampler`](`MultiStart::sampler`). For each of them, the problem and the
+/// solver are cloned and run by an [`Executor`]. The result of the run with the lowest best cost
+/// is returned together with a summary of all runs in a [`MultiStartResult`].
+///
+/// The state of each run can be configured via [`configure`](`MultiStart::configure`) (for
+/// instance to set the maximum number of iterations). The initial parameter vector is set before
+/// `configure` is called. Solvers which do not take the initial parameter vector from the state
+/// (such as Nelder-Mead, which requires an initial simplex) can be prepared for each initial
+/// parameter vector via [`setup_solver`](`MultiStart::setup_solver`).
+///
+/// If the `rayon` feature is enabled, the runs can be performed in parallel via
+/// [`parallel`](`MultiStart::parallel`).
+///
+/// # Example
+///
+/// ```
+/// # use argmin::core::{Error, MultiStart, State};
+/// # use argmin::core::test_utils::{TestSolver, TestProblem};
+/// #
+/// # fn main() -> Result<(), Error> {
+/// # let solver = TestSolver::new();
+/// # let problem = TestProblem::new();
+/// #
+/// let result = MultiStart::new(problem, solver, 10)
+///     // Sample initial parameter vectors within [-5, 5] x [-5, 5]
+///     .bounds(vec![-5.0, -5.0], vec![5.0, 5.0])
+///     // Configure the state of every run
+///     .configure(|state| state.max_iters(100))
+///     .run()?;
+///
+/// // Best of all runs
+/// let best_cost = result.best.state().get_best_cost();
+///
+/// // Summary of all runs
+/// for run in result.runs.iter() {
+///     println!("{:?}: {}", run.initial_param, run.best_cost);
+/// }
+/// # assert_eq!(result.runs.len(), 10);
+/// # Ok(())
+/// # }
+/// ```
+pub struct MultiStart<O, S, I: State> {
+    /// Problem
+    problem: O,
+    /// Solver which is cloned for every run
+    solver: S,
+    /// Number of runs
+    num_starts: usize,
+    /// Samples the initial parameter vectors
+    sampler: Option<Sampler<I::Param>>,
+    /// Configures the state of every run
+    configure: Option<Configure<I>>,
+    /// Prepares the solver of every run
+    setup_solver: Option<SetupSolver<S, I::Param>>,
+    /// Indicates whether the runs are performed in parallel
+    #[cfg(feature = "rayon")]
+    parallel: bool,
+}
+
+impl<O, S, P, G, J, H, F> MultiStart<O, S, IterState<P, G, J, H, F>>
+where
+    O: Clone + SendAlias + SyncAlias,
+    S: Clone + Solver<O, IterState<P, G, J, H, F>> + SendAlias,
+    IterState<P, G, J, H, F>:
+        State<Param = P, Float = F> + SerializeAlias + DeserializeOwnedAlias + SendAlias,
+    P: Clone + SendAlias,
+    F: ArgminFloat + SendAlias,
+{
+    /// Constructs a new `MultiStart` which runs `solver` on `problem` from `num_starts` initial
+    /// parameter vectors.
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// # use argmin::core::MultiStart;
+    /// # use argmin::core::test_utils::{TestSolver, TestProblem};
+    /// #
+    /// let multistart = MultiStart::new(TestProblem::new(), TestSolver::new(), 10);
+    /// ```
+    pub fn new(problem: O, solver: S, num_starts: usize) -> Self {
+        MultiStart {
+            problem,
+            solver,
+            num_starts,
+            sampler: None,
+            configure: None,
+            setup_solver: None,
+            #[cfg(feature = "rayon")]
+            parallel: false,
+        }
+    }
+
+    /// Samples the initial parameter vectors uniformly between `lower` and `upper` via
+    /// [`ArgminRandom`](`argmin_math::ArgminRandom`).
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// # use argmin::core::MultiStart;
+    /// # use argmin::core::test_utils::{TestSolver, TestProblem};
+    /// #
+    /// let multistart = MultiStart::new(TestProblem::new(), TestSolver::new(), 10)
+    ///     .bounds(vec![-1.0, -1.0], vec![1.0, 1.0]);
+    /// ```
+    #[must_use]
+    pub fn bounds(self, lower: P, upper: P) -> Self
+    where
+        P: ArgminRandom + 'static,
+    {
+        self.sampler(move || P::rand_from_range(&lower, &upper))
+    }
+
+    /// Samples the initial parameter vectors by calling `sampler` once for each run.
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// # use argmin::core::MultiStart;
+    /// # use argmin::core::test_utils::{TestSolver, TestProblem};
+    /// #
+    /// // Initial parameter vectors on a line
+    /// let mut x = 0.0;
+    /// let multistart = MultiStart::new(TestProblem::new(), TestSolver::new(), 10)
+    ///     .sampler(move || {
+    ///         x += 1.0;
+    ///         vec![x, x]
+    ///     });
+    /// ```
+    #[must_use]
+    pub fn sampler<T: FnMut() -> P + 'static>(mut self, sampler: T) -> Self {
+        self.sampler = Some(Box::new(sampler));
+        self
+    }
+
+    /// Configures the state of every run, equivalent to
+    /// [`Executor::configure`](`crate::core::Executor::configure`). The initial parameter vector
+    /// is already set when `configure` is called.
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// # use argmin::core::MultiStart;
+    /// # use argmin::core::test_utils::{TestSolver, TestProblem};
+    /// #
+    /// let multistart = MultiStart::new(TestProblem::new(), TestSolver::new(), 10)
+    ///     .configure(|state| state.max_iters(100).target_cost(0.0));
+    /// ```
+    #[must_use]
+    pub fn configure<T>(mut self, configure: T) -> Self
+    where
+        T: Fn(IterState<P, G, J, H, F>) -> IterState<P, G, J, H, F> + Send + Sync + 'static,
+    {
+        self.configure = Some(Box::new(configure));
+        self
+    }
+
+    /// Prepares the (cloned) solver of every run for its initial parameter vector.
+    ///
+    /// This is needed for solvers which do not take the initial parameter vector from the state.
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// # use argmin::core::MultiStart;
+    /// # use argmin::core::test_utils::TestProblem;
+    /// use argmin::solver::neldermead::NelderMead;
+    ///
+    /// let multistart = MultiStart::new(TestProblem::new(), NelderMead::new(), 10)
+    ///     .setup_solver(|solver, x0: &Vec<f64>| {
+    ///         // Initial simplex around `x0`
+    ///         let x1 = vec![x0[0] + 0.1, x0[1]];
+    ///         let x2 = vec![x0[0], x0[1] + 0.1];
+    ///         solver.with_initial_params(vec![x0.clone(), x1, x2])
+    ///     });
+    /// ```
+    #[must_use]
+    pub fn setup_solver<T: Fn(S, &P) -> S + 'static>(mut self, setup_solver: T) -> Self {
+        self.setup_solver = Some(Box::new(setup_solver));
+        self
+    }
+
+    /// Performs the runs in parallel (requires the `rayon` feature). Defaults to `false`.
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// # use argmin::core::MultiStart;
+    /// # use argmin::core::test_utils::{TestSolver, TestProblem};
+    /// #
+    /// let multistart = MultiStart::new(TestProblem::new(), TestSolver::new(), 10).parallel(true);
+    /// ```
+    #[cfg(feature = "rayon")]
+    #[must_use]
+    pub fn parallel(mut self, parallel: bool) -> Self {
+        self.parallel = parallel;
+        self
+    }
+
+    /// Performs all runs and returns the best result together with a summary of all runs.
+    ///
+    /// Runs which fail with an error are listed in the summary, but do not abort the remaining
+    /// runs. An error is only returned if all runs failed (the error of the first run), if no
+    /// sampler was provided or if the number of starts is zero.
+    pub fn run(mut self) -> MultiStartIterResult<O, S, P, G, J, H, F> {
+        if self.num_starts == 0 {
+            return Err(argmin_error!(
+                InvalidParameter,
+                "`MultiStart`: Number of starts must be larger than 0."
+            ));
+        }
+        let sampler = self.sampler.as_mut().ok_or_else(argmin_error_closure!(
+            NotInitialized,
+            "`MultiStart`: Either bounds or a sampler are required."
+        ))?;
+
+        // Sampling and preparing the solvers is done sequentially such that user-provided
+        // closures do not need to be thread safe.
+        let starts: Vec<(S, P)> = (0..self.num_starts)
+            .map(|_| {
+                let param = (sampler)();
+                let solver = match self.setup_solver.as_ref() {
+                    Some(setup_solver) => (setup_solver)(self.solver.clone(), &param),
+                    None => self.solver.clone(),
+                };
+                (solver, param)
+            })
+            .collect();
+
+        let problem = &self.problem;
+        let configure = &self.configure;
+        let run = |(solver, param): (S, P)| {
+            let result = Executor::new(problem.clone(), solver)
+                .configure(|state| {
+                    let state = state.param(param.clone());
+                    match configure.as_ref() {
+                        Some(configure) => (configure)(state),
+                        None => state,
+                    }
+                })
+                .run();
+            (param, result)
+        };
+
+        #[cfg(feature = "rayon")]
+        let results: Vec<_> = if self.parallel {
+            use rayon::prelude::*;
+            starts.into_par_iter().map(run).collect()
+        } else {
+            starts.into_iter().map(run).collect()
+        };
+        #[cfg(not(feature = "rayon"))]
+        let results: Vec<_> = starts.into_iter().map(run).collect();
+
+        MultiStartResult::from_results(results)
+    }
+}
+
+/// Summary of a single run of a [`MultiStart`]
+#[derive(Debug)]
+pub struct MultiStartRun<P, F> {
+    /// Initial parameter vector
+    pub initial_param: P,
+    /// Best cost function value (infinity if the run failed)
+    pub best_cost: F,
+    /// Number of iterations
+    pub iterations: u64,
+    /// Reason for termination
+    pub termination_reason: TerminationReason,
+    /// Function evaluation counts
+    pub func_counts: HashMap<String, u64>,
+    /// Error if the run failed
+    pub error: Option<Error>,
+}
+
+/// Result of a [`MultiStart`]
+pub struct MultiStartResult<O, S, I: State> {
+    /// Result of the run with the lowest best cost
+    pub best: OptimizationResult<O, S, I>,
+    /// Index of the best run in `runs`
+    pub best_run: usize,
+    /// Summary of all runs in the order of their initial parameter vectors
+    pub runs: Vec<MultiStartRun<I::Param, I::Float>>,
+}
+
+impl<O, S, I: State> MultiStartResult<O, S, I> {
+    /// Assembles the result from the results of all runs.
+    fn from_results(results: Vec<RunResult<O, S, I>>) -> Result<Self, Error> {
+        let mut runs = Vec::with_capacity(results.len());
+        let mut best: Option<(usize, OptimizationResult<O, S, I>)> = None;
+        for (idx, (initial_param, result)) in results.into_iter().enumerate() {
+            match result {
+                Ok(result) => {
+                    let state = result.state();
+                    runs.push(MultiStartRun {
+                        initial_param,
+                        best_cost: state.get_best_cost(),
+                        iterations: state.get_iter(),
+                        termination_reason: state.get_termination_reason(),
+                        func_counts: state.get_func_counts().clone(),
+                        error: None,
+                    });
+                    // NaN costs never replace an existing best result
+                    let is_better = match best.as_ref() {
+                        Some((_, b)) => state.get_best_cost() < b.state().get_best_cost(),
+                        None => true,
+                    };
+                    if is_better {
+                        best = Some((idx, result));
+                    }
+                }
+                Err(error) => runs.push(MultiStartRun {
+                    initial_param,
+                    best_cost: I::Float::infinity(),
+                    iterations: 0,
+                    termination_reason: TerminationReason::NotTerminated,
+                    func_counts: HashMap::new(),
+                    error: Some(error),
+                }),
+            }
+        }
+        match best {
+            Some((best_run, best)) => Ok(MultiStartResult {
+                best,
+                best_run,
+                runs,
+            }),
+            None => Err(runs.into_iter().next().unwrap().error.unwrap()),
+        }
+    }
+
+    /// Returns the function evaluation counts summed over all runs.
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// # use argmin::core::{Error, MultiStart};
+    /// # use argmin::core::test_utils::{TestSolver, TestProblem};
+    /// #
+    /// # fn main() -> Result<(), Error> {
+    /// let result = MultiStart::new(TestProblem::new(), TestSolver::new(), 10)
+    ///     .bounds(vec![-1.0, -1.0], vec![1.0, 1.0])
+    ///     .configure(|state| state.max_iters(10))
+    ///     .run()?;
+    ///
+    /// let counts = result.total_func_counts();
+    /// # Ok(())
+    /// # }
+    /// ```
+    pub fn total_func_counts(&self) -> HashMap<String, u64> {
+        let mut counts = HashMap::new();
+        for run in self.runs.iter() {
+            for (k, v) in run.func_counts.iter() {
+                *counts.entry(k.clone()).or_insert(0) += v;
+            }
+        }
+        counts
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::core::{CostFunction, Gradient};
+    use crate::solver::linesearch::MoreThuenteLineSearch;
+    use crate::solver::neldermead::NelderMead;
+    use crate::solver::quasinewton::LBFGS;
+    use approx::assert_relative_eq;
+
+    /// Multimodal function with local minima at `x = 2 pi k` (cost `-1 + 0.01 (2 pi k)^2`); the
+    /// global minimum is at `x = 0`.
+    #[derive(Clone)]
+    struct Multimodal {}
+
+    impl CostFunction for Multimodal {
+        type Param = Vec<f64>;
+        type Output = f64;
+
+        fn cost(&self, p: &Self::Param) -> Result<Self::Output, Error> {
+            if p[0].is_nan() {
+                return Err(argmin_error!(InvalidParameter, "NaN"));
+            }
+            Ok(-p[0].cos() + 0.01 * p[0].powi(2))
+        }
+    }
+
+    impl Gradient for Multimodal {
+        type Param = Vec<f64>;
+        type Gradient = Vec<f64>;
+
+        fn gradient(&self, p: &Self::Param) -> Result<Self::Gradient, Error> {
+            Ok(vec![p[0].sin() + 0.02 * p[0]])
+        }
+    }
+
+    type Lbfgs = LBFGS<MoreThuenteLineSearch<Vec<f64>, Vec<f64>, f64>, Vec<f64>, Vec<f64>, f64>;
+
+    fn lbfgs() -> Lbfgs {
+        LBFGS::new(MoreThuenteLineSearch::new(), 5)
+    }
+
+    #[test]
+    fn test_multistart() {
+        // Initial parameter vectors near the local minima at 2 pi k for k = -3, ..., 3
+        let mut k = -4.0;
+        let result = MultiStart::new(Multimodal {}, lbfgs(), 7)
+            .sampler(move || {
+                k += 1.0;
+                vec![2.0 * std::f64::consts::PI * k + 0.5]
+            })
+            .configure(|state| state.max_iters(50))
+            .run()
+            .unwrap();
+
+        assert_eq!(result.runs.len(), 7);
+        assert_eq!(result.best_run, 3);
+        assert_relative_eq!(result.runs[3].initial_param[0], 0.5);
+        assert_relative_eq!(
+            result.best.state().get_best_param().unwrap()[0],
+            0.0,
+            epsilon = 1e-6
+        );
+        assert_relative_eq!(result.best.state().get_best_cost(), -1.0, epsilon = 1e-10);
+        for run in result.runs.iter() {
+            assert!(run.error.is_none());
+            assert!(run.iterations > 0);
+            assert!(run.best_cost >= result.runs[3].best_cost);
+            assert_ne!(run.termination_reason, TerminationReason::NotTerminated);
+        }
+        assert_eq!(
+            result.total_func_counts()["cost_count"],
+            result
+                .runs
+                .iter()
+                .map(|r| r.func_counts["cost_count"])
+                .sum::<u64>()
+        );
+    }
+
+    #[test]
+    fn test_bounds() {
+        let result = MultiStart::new(Multimodal {}, lbfgs(), 20)
+            .bounds(vec![-3.0], vec![3.0])
+            .configure(|state| state.max_iters(50))
+            .run()
+            .unwrap();
+
+        for run in result.runs.iter() {
+            assert!(run.initial_param[0] >= -3.0 && run.initial_param[0] <= 3.0);
+        }
+        assert_relative_eq!(
+            result.best.state().get_best_param().unwrap()[0],
+            0.0,
+            epsilon = 1e-6
+        );
+    }
+
+    #[test]
+    fn test_setup_solver() {
+        let mut k = -2.0;
+        let result = MultiStart::new(Multimodal {}, NelderMead::new(), 3)
+            .sampler(move || {
+                k += 1.0;
+                vec![2.0 * std::f64::consts::PI * k + 0.5]
+            })
+            .setup_solver(|solver, x0: &Vec<f64>| {
+                solver.with_initial_params(vec![x0.clone(), vec![x0[0] + 0.3]])
+            })
+            .configure(|state| state.max_iters(100))
+            .run()
+            .unwrap();
+
+        assert_eq!(result.best_run, 1);
+        assert_relative_eq!(
+            result.best.state().get_best_param().unwrap()[0],
+            0.0,
+            epsilon = 1e-3
+        );
+    }
+
+    #[test]
+    fn test_errors() {
+        let mut values = vec![f64::NAN, 1.0, f64::NAN].into_iter();
+        let result = MultiStart::new(Multimodal {}, lbfgs(), 3)
+            .sampler(move || vec![values.next().unwrap()])
+            .configure(|state| state.max_iters(50))
+            .run()
+            .unwrap();
+
+        assert_eq!(result.best_run, 1);
+        assert!(result.runs[0].error.is_some());
+        assert!(result.runs[1].error.is_none());
+        assert!(result.runs[2].error.is_some());
+        assert!(result.runs[0].best_cost.is_infinite());
+
+        // All runs fail
+        let res = MultiStart::new(Multimodal {}, lbfgs(), 3)
+            .sampler(|| vec![f64::NAN])
+            .run();
+        assert!(res.is_err());
+
+        // No sampler
+        let res = MultiStart::new(Multimodal {}, lbfgs(), 3).run();
+        assert_eq!(
+            res.err()
+                .unwrap()
+                .downcast_ref::<crate::core::ArgminError>()
+                .unwrap()
+                .to_string(),
+            "Not initialized: \"`MultiStart`: Either bounds or a sampler are required.\""
+        );
+
+        // No starts
+        let res = MultiStart::new(Multimodal {}, lbfgs(), 0)
+            .bounds(vec![-3.0], vec![3.0])
+            .run();
+        assert!(res.is_err());
+    }
+
+    #[cfg(feature = "rayon")]
+    #[test]
+    fn test_parallel() {
+        let result = MultiStart::new(Multimodal {}, lbfgs(), 16)
+            .bounds(vec![-10.0], vec![10.0])
+            .configure(|state| state.max_iters(50))
+            .parallel(true)
+            .run()
+            .unwrap();
+
+        assert_eq!(result.runs.len(), 16);
+        let best_cost = result.best.state().get_best_cost();
+        for run in result.runs.iter() {
+            assert!(run.best_cost >= best_cost);
+        }
+    }
+}
@@ -0,0 +1,498 @@
+// Copyright 2018-2022 argmin developers
+//
+// Licensed under the Apache License, Version 2.0 <LICENSE-APACHE or
+// http://apache.org/licenses/LICENSE-2.0> or the MIT license <LICENSE-MIT or
+// http://opensource.org/licenses/MIT>, at your option. This file may not be
+// copied, modified, or distributed except according to those terms.
+
+//! # Run observers on a dedicated worker thread.
+//!
+//! See documentation of [`AsyncObserver`] and [`OverflowPolicy`] for details.
+
+use crate::core::observers::Observe;
+use crate::core::{Error, TerminationReason, KV};
+use std::collections::VecDeque;
+use std::sync::{Arc, Condvar, Mutex, MutexGuard};
+use std::thread::JoinHandle;
+
+/// Runs an observer on a dedicated worker thread.
+///
+/// Each call copies the state and the key-value pairs into a bounded queue and returns
+/// immediately, while the wrapped observer processes the queue on its own thread. This way, slow
+/// observers (such as observers writing to disk) do not slow down the iterations of the solver.
+///
+/// The behavior when the queue is full is defined by the [`OverflowPolicy`] (`Block` by
+/// default). Initialization and termination are never dropped. Once the optimization terminated,
+/// `observe_final` waits until the worker processed all queued iterations, therefore all
+/// observations are completed when [`Executor::run`](`crate::core::Executor::run`) returns.
+///
+/// Errors returned by the wrapped observer are returned by the next call to the `AsyncObserver`
+/// (at the latest by `observe_final`) and are handled according to the
+/// [`ObserverErrorPolicy`](`crate::core::observers::ObserverErrorPolicy`) of the observer. Only
+/// the first error is kept until it was returned.
+///
+/// # Example
+///
+/// ```
+/// # use argmin::core::{Error, Executor};
+/// # use argmin::core::test_utils::{TestSolver, TestProblem};
+/// use argmin::core::observers::{AsyncObserver, ObserverMode, OverflowPolicy};
+/// # #[cfg(feature = "slog-logger")]
+/// use argmin::core::observers::SlogLogger;
+///
+/// # fn main() -> Result<(), Error> {
+/// # #[cfg(feature = "slog-logger")]
+/// let observer = AsyncObserver::new(SlogLogger::term())
+///     .capacity(16)
+///     .overflow_policy(OverflowPolicy::KeepLatest);
+///
+/// # #[cfg(feature = "slog-logger")]
+/// let result = Executor::new(TestProblem::new(), TestSolver::new())
+///     .configure(|state| state.param(vec![1.0f64, 0.0]).max_iters(10))
+///     .add_observer(observer, ObserverMode::Always)
+///     .run()?;
+/// # Ok(())
+/// # }
+/// ```
+pub struct AsyncObserver<I> {
+    /// Wrapped observer, moved to the worker thread once the first message is sent
+    observer: Option<Box<dyn Observe<I> + Send>>,
+    /// Queue shared with the worker thread
+    shared: Arc<Shared<I>>,
+    /// Worker thread
+    worker: Option<JoinHandle<()>>,
+}
+
+/// Indicates what [`AsyncObserver`] does when its queue is full.
+///
+/// `Block` waits until the worker thread processed an iteration, `Drop` discards the current
+/// iteration and `KeepLatest` discards the oldest queued iteration in favor of the current one.
+#[derive(Copy, Clone, Debug, Eq, PartialEq)]
+pub enum OverflowPolicy {
+    /// Wait until there is space in the queue
+    Block,
+    /// Discard the current iteration
+    Drop,
+    /// Discard the oldest queued iteration
+    KeepLatest,
+}
+
+impl Default for OverflowPolicy {
+    /// The default for `OverflowPolicy` is `Block`
+    fn default() -> OverflowPolicy {
+        OverflowPolicy::Block
+    }
+}
+
+/// Observation which is passed to the worker thread
+enum Message<I> {
+    /// Call of `observe_init`
+    Init(String, KV),
+    /// Call of `observe_iter`
+    Iter(I, KV),
+    /// Call of `observe_final`
+    Final(I, TerminationReason),
+}
+
+/// Queue and status shared between [`AsyncObserver`] and the worker thread
+struct Shared<I> {
+    /// Queue and status
+    queue: Mutex<Queue<I>>,
+    /// Notified whenever the queue or the status changes
+    changed: Condvar,
+}
+
+/// Queue and status of the worker thread
+struct Queue<I> {
+    /// Queued messages
+    messages: VecDeque<Message<I>>,
+    /// Maximum number of queued iterations
+    capacity: usize,
+    /// Behavior when the queue is full
+    policy: OverflowPolicy,
+    /// Indicates whether the worker currently processes a message
+    busy: bool,
+    /// Indicates that no more messages will be sent
+    closed: bool,
+    /// Indicates that the worker thread stopped
+    stopped: bool,
+    /// First error returned by the observer which was not returned yet
+    error: Option<Error>,
+}
+
+impl<I> Shared<I> {
+    /// Locks the queue
+    fn lock(&self) -> MutexGuard<'_, Queue<I>> {
+        self.queue.lock().unwrap()
+    }
+}
+
+/// Marks the worker thread as stopped when dropped, even if the observer panicked.
+struct StopGuard<I>(Arc<Shared<I>>);
+
+impl<I> Drop for StopGuard<I> {
+    fn drop(&mut self) {
+        if let Ok(mut queue) = self.0.queue.lock() {
+            queue.stopped = true;
+            queue.busy = false;
+        }
+        self.0.changed.notify_all();
+    }
+}
+
+impl<I> AsyncObserver<I>
+where
+    I: Send + 'static,
+{
+    /// Wraps `observer` such that it runs on a dedicated worker thread.
+    ///
+    /// The queue holds up to 64 iterations by default.
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// # use argmin::core::IterState;
+    /// # use argmin::core::observers::AsyncObserver;
+    /// # #[cfg(feature = "slog-logger")]
+    /// use argmin::core::observers::SlogLogger;
+    ///
+    /// # #[cfg(feature = "slog-logger")]
+    /// let observer: AsyncObserver<IterState<Vec<f64>, (), (), (), f64>> =
+    ///     AsyncObserver::new(SlogLogger::term());
+    /// ```
+    pub fn new<OBS: Observe<I> + Send + 'static>(observer: OBS) -> Self {
+        AsyncObserver {
+            observer: Some(Box::new(observer)),
+            shared: Arc::new(Shared {
+                queue: Mutex::new(Queue {
+                    messages: VecDeque::new(),
+                    capacity: 64,
+                    policy: OverflowPolicy::default(),
+                    busy: false,
+                    closed: false,
+                    stopped: false,
+                    error: None,
+                }),
+                changed: Condvar::new(),
+            }),
+            worker: None,
+        }
+    }
+
+    /// Set the maximum number of queued iterations (at least 1).
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// # use argmin::core::IterState;
+    /// # use argmin::core::observers::AsyncObserver;
+    /// # #[cfg(feature = "slog-logger")]
+    /// use argmin::core::observers::SlogLogger;
+    ///
+    /// # #[cfg(feature = "slog-logger")]
+    /// let observer: AsyncObserver<IterState<Vec<f64>, (), (), (), f64>> =
+    ///     AsyncObserver::new(SlogLogger::term()).capacity(16);
+    /// ```
+    #[must_use]
+    pub fn capacity(self, capacity: usize) -> Self {
+        self.shared.lock().capacity = capacity.max(1);
+        self
+    }
+
+    /// Set the [`OverflowPolicy`] which defines the behavior when the queue is full.
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// # use argmin::core::IterState;
+    /// # use argmin::core::observers::{AsyncObserver, OverflowPolicy};
+    /// # #[cfg(feature = "slog-logger")]
+    /// use argmin::core::observers::SlogLogger;
+    ///
+    /// # #[cfg(feature = "slog-logger")]
+    /// let observer: AsyncObserver<IterState<Vec<f64>, (), (), (), f64>> =
+    ///     AsyncObserver::new(SlogLogger::term()).overflow_policy(OverflowPolicy::Drop);
+    /// ```
+    #[must_use]
+    pub fn overflow_policy(self, policy: OverflowPolicy) -> Self {
+        self.shared.lock().policy = policy;
+        self
+    }
+
+    /// Starts the worker thread if it is not running yet.
+    fn start(&mut self) -> Result<(), Error> {
+        if let Some(mut observer) = self.observer.take() {
+            let shared = Arc::clone(&self.shared);
+            let worker = std::thread::Builder::new()
+                .name("argmin-observer".to_string())
+                .spawn(move || {
+                    let _guard = StopGuard(Arc::clone(&shared));
+                    while let Some(message) = next_message(&shared) {
+                        let result = match message {
+                            Message::Init(name, kv) => observer.observe_init(&name, &kv),
+                            Message::Iter(state, kv) => observer.observe_iter(&state, &kv),
+                            Message::Final(state, reason) => {
+                                observer.observe_final(&state, &reason)
+                            }
+                        };
+                        let mut queue = shared.lock();
+                        queue.busy = false;
+                        if let Err(e) = result {
+                            queue.error.get_or_insert(e);
+                        }
+                        shared.changed.notify_all();
+                    }
+                })?;
+            self.worker = Some(worker);
+        }
+        Ok(())
+    }
+
+    /// Adds `message` to the queue. Iterations are subject to the [`OverflowPolicy`].
+    ///
+    /// Returns the pending error of the observer, if any.
+    fn send(&mut self, message: Message<I>) -> Result<(), Error> {
+        self.start()?;
+        let mut queue = self.shared.lock();
+        if let Some(e) = queue.error.take() {
+            return Err(e);
+        }
+        if queue.stopped {
+            return Err(argmin_error!(
+                PotentialBug,
+                "`AsyncObserver`: Worker thread stopped unexpectedly."
+            ));
+        }
+        if let Message::Iter(..) = message {
+            let is_iter = |m: &Message<I>| matches!(m, Message::Iter(..));
+            while queue.messages.iter().filter(|m| is_iter(m)).count() >= queue.capacity {
+                match queue.policy {
+                    OverflowPolicy::Block if !queue.stopped => {
+                        queue = self.shared.changed.wait(queue).unwrap();
+                    }
+                    OverflowPolicy::Block | OverflowPolicy::Drop => return Ok(()),
+                    OverflowPolicy::KeepLatest => {
+                        let oldest = queue.messages.iter().position(is_iter).unwrap();
+                        queue.messages.remove(oldest);
+                    }
+                }
+            }
+        }
+        queue.messages.push_back(message);
+        self.shared.changed.notify_all();
+        Ok(())
+    }
+
+    /// Waits until the worker thread processed all queued messages.
+    ///
+    /// Returns the pending error of the observer, if any.
+    fn drain(&self) -> Result<(), Error> {
+        let mut queue = self.shared.lock();
+        while (!queue.messages.is_empty() || queue.busy) && !queue.stopped {
+            queue = self.shared.changed.wait(queue).unwrap();
+        }
+        match queue.error.take() {
+            Some(e) => Err(e),
+            None => Ok(()),
+        }
+    }
+}
+
+/// Waits for the next message. Returns `None` once the queue is closed and empty.
+fn next_message<I>(shared: &Shared<I>) -> Option<Message<I>> {
+    let mut queue = shared.lock();
+    loop {
+        if let Some(message) = queue.messages.pop_front() {
+            queue.busy = true;
+            shared.changed.notify_all();
+            return Some(message);
+        }
+        if queue.closed {
+            return None;
+        }
+        queue = shared.changed.wait(queue).unwrap();
+    }
+}
+
+/// Closes the queue and waits until the worker thread processed all queued messages.
+impl<I> Drop for AsyncObserver<I> {
+    fn drop(&mut self) {
+        if let Ok(mut queue) = self.shared.queue.lock() {
+            queue.closed = true;
+        }
+        self.shared.changed.notify_all();
+        if let Some(worker) = self.worker.take() {
+            let _ = worker.join();
+        }
+    }
+}
+
+impl<I> Observe<I> for AsyncObserver<I>
+where
+    I: Clone + Send + 'static,
+{
+    /// Queues the initialization.
+    fn observe_init(&mut self, name: &str, kv: &KV) -> Result<(), Error> {
+        self.send(Message::Init(name.to_string(), kv.clone()))
+    }
+
+    /// Queues a copy of `state` and `kv`, subject to the [`OverflowPolicy`].
+    fn observe_iter(&mut self, state: &I, kv: &KV) -> Result<(), Error> {
+        self.send(Message::Iter(state.clone(), kv.clone()))
+    }
+
+    /// Queues the termination and waits until all queued messages were processed.
+    fn observe_final(&mut self, state: &I, reason: &TerminationReason) -> Result<(), Error> {
+        self.send(Message::Final(state.clone(), reason.clone()))?;
+        self.drain()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::core::observers::ObserverMode;
+    use crate::core::test_utils::{TestProblem, TestSolver};
+    use crate::core::{Executor, IterState, State};
+
+    type TState = IterState<Vec<f64>, (), (), (), f64>;
+
+    /// Records the observed iterations; `observe_init` waits until `gate` is opened.
+    #[derive(Clone, Default)]
+    struct Recorder {
+        iters: Arc<Mutex<Vec<u64>>>,
+        finals: Arc<Mutex<Vec<TerminationReason>>>,
+        gate: Option<Arc<(Mutex<bool>, Condvar)>>,
+        fail: bool,
+    }
+
+    impl Observe<TState> for Recorder {
+        fn observe_init(&mut self, _name: &str, _kv: &KV) -> Result<(), Error> {
+            if let Some(gate) = self.gate.as_ref() {
+                let mut open = gate.0.lock().unwrap();
+                while !*open {
+                    open = gate.1.wait(open).unwrap();
+                }
+            }
+            Ok(())
+        }
+
+        fn observe_iter(&mut self, state: &TState, _kv: &KV) -> Result<(), Error> {
+            std::thread::sleep(std::time::Duration::from_millis(1));
+            self.iters.lock().unwrap().push(state.get_iter());
+            if self.fail {
+                return Err(Error::msg("observer failed"));
+            }
+            Ok(())
+        }
+
+        fn observe_final(
+            &mut self,
+            _state: &TState,
+            reason: &TerminationReason,
+        ) -> Result<(), Error> {
+            self.finals.lock().unwrap().push(reason.clone());
+            Ok(())
+        }
+    }
+
+    #[test]
+    fn test_executor() {
+        let recorder = Recorder::default();
+        Executor::new(TestProblem::new(), TestSolver::new())
+            .configure(|state| state.param(vec![1.0f64, 1.0]).max_iters(20))
+            .add_observer(
+                AsyncObserver::new(recorder.clone()).capacity(2),
+                ObserverMode::Always,
+            )
+            .run()
+            .unwrap();
+        // All iterations were observed before `run` returned
+        assert_eq!(
+            *recorder.iters.lock().unwrap(),
+            (0..20).collect::<Vec<u64>>()
+        );
+        assert_eq!(
+            *recorder.finals.lock().unwrap(),
+            vec![TerminationReason::MaxItersReached]
+        );
+    }
+
+    /// Queues five iterations while the worker is blocked in `observe_init` and returns the
+    /// observed iterations.
+    fn overflow(policy: OverflowPolicy) -> Vec<u64> {
+        let gate = Arc::new((Mutex::new(false), Condvar::new()));
+        let recorder = Recorder {
+            gate: Some(gate.clone()),
+            ..Recorder::default()
+        };
+        let mut observer = AsyncObserver::new(recorder.clone())
+            .capacity(2)
+            .overflow_policy(policy);
+
+        let mut state: TState = IterState::new();
+        observer.observe_init("test", &KV::new()).unwrap();
+        for _ in 0..5 {
+            observer.observe_iter(&state, &KV::new()).unwrap();
+            state.increment_iter();
+        }
+        *gate.0.lock().unwrap() = true;
+        gate.1.notify_all();
+        observer
+            .observe_final(&state, &TerminationReason::MaxItersReached)
+            .unwrap();
+        assert_eq!(recorder.finals.lock().unwrap().len(), 1);
+        let iters = recorder.iters.lock().unwrap().clone();
+        iters
+    }
+
+    #[test]
+    fn test_overflow_policy() {
+        assert_eq!(overflow(OverflowPolicy::Drop), vec![0, 1]);
+        assert_eq!(overflow(OverflowPolicy::KeepLatest), vec![3, 4]);
+    }
+
+    #[test]
+    fn test_overflow_block() {
+        let recorder = Recorder::default();
+        let mut observer = AsyncObserver::new(recorder.clone()).capacity(1);
+        let mut state: TState = IterState::new();
+        for _ in 0..5 {
+            observer.observe_iter(&state, &KV::new()).unwrap();
+            state.increment_iter();
+        }
+        observer
+            .observe_final(&state, &TerminationReason::MaxItersReached)
+            .unwrap();
+        assert_eq!(*recorder.iters.lock().unwrap(), vec![0, 1, 2, 3, 4]);
+    }
+
+    #[test]
+    fn test_error() {
+        let recorder = Recorder {
+            fail: true,
+            ..Recorder::default()
+        };
+        let mut observer = AsyncObserver::new(recorder.clone());
+        let state: TState = IterState::new();
+        observer.observe_iter(&state, &KV::new()).unwrap();
+        let err = observer
+            .observe_final(&state, &TerminationReason::MaxItersReached)
+            .unwrap_err();
+        assert_eq!(err.to_string(), "observer failed");
+        assert_eq!(recorder.finals.lock().unwrap().len(), 1);
+    }
+
+    #[test]
+    fn test_drop_drains() {
+        let recorder = Recorder::default();
+        let mut observer = AsyncObserver::new(recorder.clone());
+        let mut state: TState = IterState::new();
+        for _ in 0..5 {
+            observer.observe_iter(&state, &KV::new()).unwrap();
+            state.increment_iter();
+        }
+        drop(observer);
+        assert_eq!(*recorder.iters.lock().unwrap(), vec![0, 1, 2, 3, 4]);
+    }
+}
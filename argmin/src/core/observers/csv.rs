@@ -0,0 +1,320 @@
+// Copyright 2018-2022 argmin developers
+//
+// Licensed under the Apache License, Version 2.0 <LICENSE-APACHE or
+// http://apache.org/licenses/LICENSE-2.0> or the MIT license <LICENSE-MIT or
+// http://opensource.org/licenses/MIT>, at your option. This file may not be
+// copied, modified, or distributed except according to those terms.
+
+//! # Write the progress of the optimization to a CSV file.
+//!
+//! See documentation of [`WriteToCsv`] for details.
+
+use crate::core::observers::Observe;
+use crate::core::{Error, State, TerminationReason, KV};
+use argmin_math::ArgminElements;
+use std::fmt::Display;
+use std::fs::File;
+use std::io::{BufWriter, Write};
+use std::path::PathBuf;
+
+/// Write the progress of the optimization to a CSV file.
+///
+/// Every observed iteration appends one row to the file, which makes the output easy to load into
+/// spreadsheets or `pandas`. The columns are
+///
+/// * `iter`, `cost`, `best_cost` and `time` (duration of the iteration in seconds, empty if
+///   [timing](`crate::core::Executor::timer`) is disabled),
+/// * one column per function evaluation counter (such as `cost_count`),
+/// * one column per key-value pair returned by the solver,
+/// * optionally one column per element of the parameter vector (`param_0`, `param_1`, ...), see
+///   [`with_params`](`WriteToCsv::with_params`).
+///
+/// The columns are determined by the first row written after initialization. Keys which only
+/// appear later are ignored and keys which are missing in later iterations result in empty
+/// fields. The file is (re)created in `observe_init` and flushed once the optimization
+/// terminated. How often rows are written is controlled by the
+/// [`ObserverMode`](`crate::core::observers::ObserverMode`) passed to
+/// [`add_observer`](`crate::core::Executor::add_observer`).
+///
+/// # Example
+///
+/// ```
+/// # use argmin::core::{Error, Executor};
+/// # use argmin::core::observers::{ObserverMode, WriteToCsv};
+/// # use argmin::core::test_utils::{TestSolver, TestProblem};
+/// #
+/// # fn main() -> Result<(), Error> {
+/// # let dir = std::env::temp_dir().join("argmin_write_to_csv_doctest");
+/// # let path = dir.join("progress.csv");
+/// # let path = path.to_str().unwrap();
+/// let observer = WriteToCsv::new(path).with_params();
+///
+/// let result = Executor::new(TestProblem::new(), TestSolver::new())
+///     .configure(|state| state.param(vec![1.0f64, 0.0]).max_iters(10))
+///     .add_observer(observer, ObserverMode::Every(2))
+///     .run()?;
+/// # let csv = std::fs::read_to_string(path)?;
+/// # assert_eq!(csv.lines().next().unwrap(), "iter,cost,best_cost,time,param_0,param_1");
+/// # assert_eq!(csv.lines().count(), 6);
+/// # std::fs::remove_dir_all(dir)?;
+/// # Ok(())
+/// # }
+/// ```
+pub struct WriteToCsv<P> {
+    /// Path of the CSV file
+    path: PathBuf,
+    /// Writer, available after initialization
+    writer: Option<BufWriter<File>>,
+    /// Function evaluation counters, KV keys and number of parameter columns written to the file;
+    /// `None` until the header was written
+    columns: Option<(Vec<String>, Vec<&'static str>, usize)>,
+    /// Converts the parameter vector into its elements, if enabled
+    params: Option<fn(&P) -> Vec<String>>,
+}
+
+impl<P> WriteToCsv<P> {
+    /// Create a new instance of `WriteToCsv` which writes to the file at `path`.
+    ///
+    /// The file and all of its parent directories are created when the optimization starts. An
+    /// existing file is overwritten.
+    ///
+    /// # Example
+    /// ```
+    /// # use argmin::core::observers::WriteToCsv;
+    /// let observer: WriteToCsv<Vec<f64>> = WriteToCsv::new("progress.csv");
+    /// ```
+    pub fn new<N: AsRef<str>>(path: N) -> Self {
+        WriteToCsv {
+            path: PathBuf::from(path.as_ref()),
+            writer: None,
+            columns: None,
+            params: None,
+        }
+    }
+
+    /// Adds one column per element of the parameter vector.
+    ///
+    /// Works with all parameter vectors which implement
+    /// [`ArgminElements`](`argmin_math::ArgminElements`), such as `Vec`, `ndarray::Array1` and
+    /// `nalgebra` vectors.
+    ///
+    /// # Example
+    /// ```
+    /// # use argmin::core::observers::WriteToCsv;
+    /// let observer: WriteToCsv<Vec<f64>> = WriteToCsv::new("progress.csv").with_params();
+    /// ```
+    #[must_use]
+    pub fn with_params(mut self) -> Self
+    where
+        P: ArgminElements,
+        P::Element: Display,
+    {
+        self.params = Some(param_elements::<P>);
+        self
+    }
+
+    /// Creates the file and all of its parent directories
+    fn create(&mut self) -> Result<(), Error> {
+        if let Some(dir) = self.path.parent() {
+            if !dir.as_os_str().is_empty() && !dir.exists() {
+                std::fs::create_dir_all(dir)?
+            }
+        }
+        self.writer = Some(BufWriter::new(File::create(&self.path)?));
+        self.columns = None;
+        Ok(())
+    }
+}
+
+/// Returns the elements of `param` as strings
+fn param_elements<P>(param: &P) -> Vec<String>
+where
+    P: ArgminElements,
+    P::Element: Display,
+{
+    (0..param.num_elements())
+        .map(|i| param.get_element(i).to_string())
+        .collect()
+}
+
+/// Quotes a field if it contains a delimiter, a quote or a line break
+fn escape(field: String) -> String {
+    if field.contains([',', '"', '\n', '\r']) {
+        format!("\"{}\"", field.replace('"', "\"\""))
+    } else {
+        field
+    }
+}
+
+impl<I, P> Observe<I> for WriteToCsv<P>
+where
+    I: State<Param = P>,
+{
+    /// Creates the file.
+    fn observe_init(&mut self, _name: &str, _kv: &KV) -> Result<(), Error> {
+        self.create()
+    }
+
+    /// Appends a row to the file. The header is written before the first row.
+    fn observe_iter(&mut self, state: &I, kv: &KV) -> Result<(), Error> {
+        if self.writer.is_none() {
+            self.create()?;
+        }
+        let params = match (self.params, state.get_param()) {
+            (Some(elements), Some(param)) => elements(param),
+            _ => vec![],
+        };
+        let writer = self.writer.as_mut().unwrap();
+
+        if self.columns.is_none() {
+            let mut counts: Vec<String> = state.get_func_counts().keys().cloned().collect();
+            counts.sort();
+            let keys: Vec<&'static str> = kv
+                .kv
+                .iter()
+                .map(|(k, _)| *k)
+                .filter(|k| *k != "time")
+                .collect();
+            let header: Vec<String> = ["iter", "cost", "best_cost", "time"]
+                .iter()
+                .map(|c| c.to_string())
+                .chain(counts.iter().cloned())
+                .chain(keys.iter().map(|k| k.to_string()))
+                .chain((0..params.len()).map(|i| format!("param_{}", i)))
+                .map(escape)
+                .collect();
+            writeln!(writer, "{}", header.join(","))?;
+            self.columns = Some((counts, keys, params.len()));
+        }
+        let (counts, keys, num_params) = self.columns.as_ref().unwrap();
+
+        let func_counts = state.get_func_counts();
+        let row: Vec<String> = [
+            state.get_iter().to_string(),
+            state.get_cost().to_string(),
+            state.get_best_cost().to_string(),
+            kv.get("time").map(|t| t.to_string()).unwrap_or_default(),
+        ]
+        .into_iter()
+        .chain(counts.iter().map(|c| {
+            func_counts
+                .get(c)
+                .map(|n| n.to_string())
+                .unwrap_or_default()
+        }))
+        .chain(
+            keys.iter()
+                .map(|k| kv.get(k).map(|v| v.to_string()).unwrap_or_default()),
+        )
+        .chain(
+            params
+                .into_iter()
+                .chain(std::iter::repeat(String::new()))
+                .take(*num_params),
+        )
+        .map(escape)
+        .collect();
+        writeln!(writer, "{}", row.join(","))?;
+        Ok(())
+    }
+
+    /// Flushes the file.
+    fn observe_final(&mut self, _state: &I, _reason: &TerminationReason) -> Result<(), Error> {
+        if let Some(writer) = self.writer.as_mut() {
+            writer.flush()?;
+        }
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::core::IterState;
+
+    send_sync_test!(write_to_csv, WriteToCsv<Vec<f64>>);
+
+    /// Runs `observer` on three iterations with parameter vectors `params` and returns the file
+    fn observe<P: Clone>(mut observer: WriteToCsv<P>, params: [P; 3], path: &PathBuf) -> String {
+        let mut state: IterState<P, (), (), (), f64> = IterState::new();
+        Observe::<IterState<P, (), (), (), f64>>::observe_init(&mut observer, "test", &KV::new())
+            .unwrap();
+        for (i, param) in params.into_iter().enumerate() {
+            state = state.param(param).cost(3.0 - i as f64);
+            state.update();
+            state.counts.insert("cost_count".to_string(), 2 * i as u64);
+            let kv = if i == 1 {
+                make_kv!("time" => 0.5; "action" => "reflect, then shrink"; "extra" => 1;)
+            } else {
+                make_kv!("action" => "expand";)
+            };
+            observer.observe_iter(&state, &kv).unwrap();
+            state.increment_iter();
+        }
+        observer
+            .observe_final(&state, &TerminationReason::MaxItersReached)
+            .unwrap();
+        let csv = std::fs::read_to_string(path).unwrap();
+        std::fs::remove_file(path).unwrap();
+        csv
+    }
+
+    #[test]
+    fn test_observe() {
+        let path = std::env::temp_dir().join("argmin_write_to_csv_test_observe.csv");
+        let observer = WriteToCsv::new(path.to_str().unwrap());
+        let csv = observe(observer, [vec![1.0, 2.0], vec![3.0], vec![4.0]], &path);
+        assert_eq!(
+            csv,
+            "iter,cost,best_cost,time,cost_count,action\n\
+             0,3,3,,0,expand\n\
+             1,2,2,0.5,2,\"reflect, then shrink\"\n\
+             2,1,1,,4,expand\n"
+        );
+    }
+
+    #[test]
+    fn test_observe_params() {
+        let path = std::env::temp_dir().join("argmin_write_to_csv_test_observe_params.csv");
+        let observer = WriteToCsv::new(path.to_str().unwrap()).with_params();
+        let csv = observe(observer, [vec![1.0, 2.0], vec![3.0], vec![4.0, 5.0]], &path);
+        assert_eq!(
+            csv,
+            "iter,cost,best_cost,time,cost_count,action,param_0,param_1\n\
+             0,3,3,,0,expand,1,2\n\
+             1,2,2,0.5,2,\"reflect, then shrink\",3,\n\
+             2,1,1,,4,expand,4,5\n"
+        );
+    }
+
+    #[test]
+    #[cfg(feature = "ndarrayl")]
+    fn test_observe_params_ndarray() {
+        use ndarray::array;
+
+        let path = std::env::temp_dir().join("argmin_write_to_csv_test_observe_ndarray.csv");
+        let observer = WriteToCsv::new(path.to_str().unwrap()).with_params();
+        let csv = observe(observer, [array![1.0], array![2.0], array![3.0]], &path);
+        assert_eq!(
+            csv.lines().next().unwrap().split(',').last(),
+            Some("param_0")
+        );
+        assert_eq!(csv.lines().last().unwrap().split(',').last(), Some("3"));
+    }
+
+    #[test]
+    #[cfg(feature = "nalgebral")]
+    fn test_observe_params_nalgebra() {
+        use nalgebra::DVector;
+
+        let path = std::env::temp_dir().join("argmin_write_to_csv_test_observe_nalgebra.csv");
+        let observer = WriteToCsv::new(path.to_str().unwrap()).with_params();
+        let params = [1.0, 2.0, 3.0].map(|x| DVector::from_vec(vec![x]));
+        let csv = observe(observer, params, &path);
+        assert_eq!(
+            csv.lines().next().unwrap().split(',').last(),
+            Some("param_0")
+        );
+        assert_eq!(csv.lines().last().unwrap().split(',').last(), Some("3"));
+    }
+}
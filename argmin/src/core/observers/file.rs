@@ -10,7 +10,7 @@
 //! See documentation of [`WriteToFile`] and [`WriteToFileSerializer`] for details.
 
 use crate::core::observers::Observe;
-use crate::core::{Error, State, KV};
+use crate::core::{Error, State, TerminationReason, KV};
 use serde::Serialize;
 use std::default::Default;
 use std::fs::File;
@@ -20,9 +20,10 @@ use std::path::PathBuf;
 /// Write parameter vectors to a file during optimization.
 ///
 /// This observer requires a directory to save the files to and a file prefix. Files will be
-/// written to disk as `<directory>/<file_prefix>_<iteration_number>.arp`. For serialization
-/// either `JSON` or [`bincode`](https://crates.io/crates/bincode) can be chosen via the enum
-/// [`WriteToFileSerializer`].
+/// written to disk as `<directory>/<file_prefix>_<iteration_number>.arp`. After the optimization
+/// terminated, the best parameter vector is written to `<directory>/<file_prefix>_best.arp`. For
+/// serialization either `JSON` or [`bincode`](https://crates.io/crates/bincode) can be chosen via
+/// the enum [`WriteToFileSerializer`].
 ///
 /// This feature requires the `serde1` feature to be set.
 ///
@@ -69,11 +70,31 @@ impl WriteToFile {
             serializer,
         }
     }
+
+    /// Writes `param` to `<dir>/<prefix>_<suffix>.arp`
+    fn write<P: Serialize>(&self, param: &P, suffix: &str) -> Result<(), Error> {
+        if !self.dir.exists() {
+            std::fs::create_dir_all(&self.dir)?
+        }
+
+        let fname = self.dir.join(format!("{}_{}.arp", self.prefix, suffix));
+        let f = BufWriter::new(File::create(fname)?);
+
+        match self.serializer {
+            WriteToFileSerializer::Bincode => {
+                bincode::serialize_into(f, param)?;
+            }
+            WriteToFileSerializer::JSON => {
+                serde_json::to_writer_pretty(f, param)?;
+            }
+        }
+        Ok(())
+    }
 }
 
-/// `WriteToFile` only implements `observer_iter` and not `observe_init` to avoid saving the
-/// initial parameter vector. It will only save if there is a parameter vector available in the
-/// state, otherwise it will skip saving silently.
+/// `WriteToFile` only implements `observer_iter` and `observe_final` and not `observe_init` to
+/// avoid saving the initial parameter vector. It will only save if there is a parameter vector
+/// available in the state, otherwise it will skip saving silently.
 impl<I> Observe<I> for WriteToFile
 where
     I: State,
@@ -81,22 +102,15 @@ where
 {
     fn observe_iter(&mut self, state: &I, _kv: &KV) -> Result<(), Error> {
         if let Some(param) = state.get_param() {
-            let iter = state.get_iter();
-            if !self.dir.exists() {
-                std::fs::create_dir_all(&self.dir)?
-            }
-
-            let fname = self.dir.join(format!("{}_{}.arp", self.prefix, iter));
-            let f = BufWriter::new(File::create(fname)?);
+            self.write(param, &state.get_iter().to_string())?;
+        }
+        Ok(())
+    }
 
-            match self.serializer {
-                WriteToFileSerializer::Bincode => {
-                    bincode::serialize_into(f, param)?;
-                }
-                WriteToFileSerializer::JSON => {
-                    serde_json::to_writer_pretty(f, param)?;
-                }
-            }
+    /// Writes the best parameter vector to `<dir>/<prefix>_best.arp`.
+    fn observe_final(&mut self, state: &I, _reason: &TerminationReason) -> Result<(), Error> {
+        if let Some(param) = state.get_best_param() {
+            self.write(param, "best")?;
         }
         Ok(())
     }
@@ -140,4 +154,28 @@ mod tests {
     use super::*;
 
     send_sync_test!(write_to_file, WriteToFile);
+
+    #[test]
+    fn test_observe_final() {
+        use crate::core::IterState;
+
+        let dir = std::env::temp_dir().join("argmin_write_to_file_test_observe_final");
+        let mut observer =
+            WriteToFile::new(dir.to_str().unwrap(), "param", WriteToFileSerializer::JSON);
+
+        let mut state: IterState<Vec<f64>, (), (), (), f64> =
+            IterState::new().param(vec![1.0, 2.0]).cost(1.0);
+        state.update();
+        let mut state = state.param(vec![3.0, 4.0]).cost(2.0);
+        state.update();
+
+        observer
+            .observe_final(&state, &TerminationReason::MaxItersReached)
+            .unwrap();
+
+        let f = File::open(dir.join("param_best.arp")).unwrap();
+        let best: Vec<f64> = serde_json::from_reader(f).unwrap();
+        assert_eq!(best, vec![1.0, 2.0]);
+        std::fs::remove_dir_all(dir).unwrap();
+    }
 }
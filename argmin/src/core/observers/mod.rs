@@ -15,21 +15,45 @@
 //! to disk and as such requires the parameter vector to be serializable. Hence this feature is
 //! only available with the `serde1` feature.
 //!
+//! The observer [`WriteToCsv`](`crate::core::observers::WriteToCsv`) appends one row per
+//! iteration to a CSV file, which is convenient for analyzing the progress of the optimization in
+//! spreadsheets or with `pandas`.
+//!
+//! The observer [`Progress`](`crate::core::observers::Progress`) shows the progress of the
+//! optimization in the terminal.
+//!
+//! Slow observers can be moved to a dedicated worker thread by wrapping them in an
+//! [`AsyncObserver`](`crate::core::observers::AsyncObserver`).
+//!
 //! The observer [`SlogLogger`](`crate::core::observers::SlogLogger`) logs the progress of the
 //! optimization to screen or to disk. This requires the `slog-logger` feature. Writing to disk
 //! requires the `serde1` feature in addition.
+//!
+//! The observer [`TracingLogger`](`crate::core::observers::TracingLogger`) reports the progress
+//! of the optimization to the subscribers of the `tracing` crate. This requires the
+//! `tracing-logger` feature.
 
+pub mod async_observer;
+pub mod csv;
 #[cfg(feature = "serde1")]
 pub mod file;
+pub mod progress;
 #[cfg(feature = "slog-logger")]
 pub mod slog_logger;
+#[cfg(feature = "tracing-logger")]
+pub mod tracing_logger;
 
+pub use self::csv::*;
+pub use async_observer::*;
 #[cfg(feature = "serde1")]
 pub use file::*;
+pub use progress::*;
 #[cfg(feature = "slog-logger")]
 pub use slog_logger::*;
+#[cfg(feature = "tracing-logger")]
+pub use tracing_logger::*;
 
-use crate::core::{Error, State, KV};
+use crate::core::{Error, State, TerminationReason, KV};
 use std::default::Default;
 use std::sync::{Arc, Mutex};
 
@@ -38,7 +62,7 @@ use std::sync::{Arc, Mutex};
 /// # Example
 ///
 /// ```
-/// use argmin::core::{Error, KV, State};
+/// use argmin::core::{Error, KV, State, TerminationReason};
 /// use argmin::core::observers::Observe;
 ///
 /// struct MyObserver {}
@@ -60,6 +84,12 @@ use std::sync::{Arc, Mutex};
 ///         // Is executed after each iteration of a solver
 ///         Ok(())
 ///     }
+///
+///     fn observe_final(&mut self, state: &I, reason: &TerminationReason) -> Result<(), Error> {
+///         // Do something with the final `state` and/or `reason`
+///         // Is executed once after the optimization terminated
+///         Ok(())
+///     }
 /// }
 /// ```
 pub trait Observe<I> {
@@ -79,19 +109,102 @@ pub trait Observe<I> {
     fn observe_iter(&mut self, _state: &I, _kv: &KV) -> Result<(), Error> {
         Ok(())
     }
+
+    /// Called once after the optimization terminated
+    ///
+    /// Has access to the final `state` and to the `reason` for termination, which is
+    /// [`TerminationReason::Aborted`] if the optimization was cancelled. This is not called if
+    /// the optimization failed with an error.
+    fn observe_final(&mut self, _state: &I, _reason: &TerminationReason) -> Result<(), Error> {
+        Ok(())
+    }
+}
+
+/// Observer stored in [`Observers`] together with the settings and the number of failures
+#[derive(Clone)]
+struct ObserverSlot<I> {
+    /// The observer
+    observer: Arc<Mutex<dyn Observe<I>>>,
+    /// Indicates when to call the observer
+    mode: ObserverMode,
+    /// Indicates how errors of the observer are handled
+    policy: ObserverErrorPolicy,
+    /// Number of errors returned by the observer so far
+    failures: u64,
+    /// Time of the last call of `observe_iter`
+    last_call: Option<instant::Instant>,
+}
+
+impl<I: State> ObserverSlot<I> {
+    /// Returns `true` if the observer needs to be called for `state` according to its
+    /// [`ObserverMode`].
+    // `u64::is_multiple_of` and `Option::is_none_or` require a newer compiler than argmin does
+    #[allow(
+        unknown_lints,
+        clippy::manual_is_multiple_of,
+        clippy::unnecessary_map_or
+    )]
+    fn due(&self, state: &I) -> bool {
+        let iter = state.get_iter();
+        let elapsed = |d| self.last_call.map_or(true, |t| t.elapsed() >= d);
+        match self.mode {
+            ObserverMode::Never => false,
+            ObserverMode::Always => true,
+            ObserverMode::Every(i) => i > 0 && iter % i == 0,
+            ObserverMode::NewBest => state.is_best(),
+            ObserverMode::Interval(d) => elapsed(d),
+            ObserverMode::Logarithmic => iter == 0 || iter.is_power_of_two(),
+            ObserverMode::FirstThenEvery(n, i) => iter < n || (i > 0 && iter % i == 0),
+            ObserverMode::NewBestWithHeartbeat(d) => state.is_best() || elapsed(d),
+        }
+    }
+}
+
+impl<I> ObserverSlot<I> {
+    /// Returns `true` if the observer was disabled because of too many failures.
+    fn disabled(&self) -> bool {
+        matches!(self.policy, ObserverErrorPolicy::DisableAfter(n) if self.failures >= n)
+    }
+
+    /// Handles the result of a call to the observer with index `idx` according to the
+    /// [`ObserverErrorPolicy`]. Errors which are not propagated are added to `errors`.
+    fn handle(
+        &mut self,
+        idx: usize,
+        iter: Option<u64>,
+        result: Result<(), Error>,
+        errors: &mut Vec<ObserverError>,
+    ) -> Result<(), Error> {
+        if let Err(e) = result {
+            if self.policy == ObserverErrorPolicy::Propagate {
+                return Err(e);
+            }
+            self.failures += 1;
+            errors.push(ObserverError {
+                observer: idx,
+                iter,
+                message: format!("{:#}", e),
+                disabled: self.disabled(),
+            });
+        }
+        Ok(())
+    }
 }
 
-type ObserversVec<I> = Vec<(Arc<Mutex<dyn Observe<I>>>, ObserverMode)>;
+type ObserversVec<I> = Vec<ObserverSlot<I>>;
 
 /// Container for observers.
 ///
 /// This tpe also implements [`Observe`] and therefore can be used like a single observer.
 /// Each observer has an [`ObserverMode`] attached which indicates when the observer will be
-/// called.
+/// called and an [`ObserverErrorPolicy`] which indicates how errors returned by the observer are
+/// handled.
 #[derive(Clone, Default)]
 pub struct Observers<I> {
-    /// Vector of `Observe`rs with the corresponding `ObserverMode`
+    /// Vector of `Observe`rs with the corresponding `ObserverMode` and `ObserverErrorPolicy`
     observers: ObserversVec<I>,
+    /// Errors of observers which were not propagated
+    errors: Vec<ObserverError>,
 }
 
 impl<I> Observers<I> {
@@ -107,7 +220,10 @@ impl<I> Observers<I> {
     /// # assert!(observers.is_empty());
     /// ```
     pub fn new() -> Self {
-        Observers { observers: vec![] }
+        Observers {
+            observers: vec![],
+            errors: vec![],
+        }
     }
 
     /// Add another observer with a corresponding [`ObserverMode`].
@@ -134,7 +250,42 @@ impl<I> Observers<I> {
         observer: OBS,
         mode: ObserverMode,
     ) -> &mut Self {
-        self.observers.push((Arc::new(Mutex::new(observer)), mode));
+        self.push_with_policy(observer, mode, ObserverErrorPolicy::default())
+    }
+
+    /// Add another observer with a corresponding [`ObserverMode`] and an
+    /// [`ObserverErrorPolicy`] which indicates how errors returned by the observer are handled.
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// use argmin::core::observers::{Observers, ObserverErrorPolicy, ObserverMode};
+    /// # #[cfg(feature = "slog-logger")]
+    /// use argmin::core::observers::SlogLogger;
+    /// use argmin::core::IterState;
+    ///
+    /// let mut observers: Observers<IterState<Vec<f64>, (), (), (), f64>> = Observers::new();
+    ///
+    /// # #[cfg(feature = "slog-logger")]
+    /// let logger = SlogLogger::term();
+    /// # #[cfg(feature = "slog-logger")]
+    /// observers.push_with_policy(logger, ObserverMode::Always, ObserverErrorPolicy::Continue);
+    /// # #[cfg(feature = "slog-logger")]
+    /// # assert!(!observers.is_empty());
+    /// ```
+    pub fn push_with_policy<OBS: Observe<I> + 'static>(
+        &mut self,
+        observer: OBS,
+        mode: ObserverMode,
+        policy: ObserverErrorPolicy,
+    ) -> &mut Self {
+        self.observers.push(ObserverSlot {
+            observer: Arc::new(Mutex::new(observer)),
+            mode,
+            policy,
+            failures: 0,
+            last_call: None,
+        });
         self
     }
 
@@ -152,6 +303,21 @@ impl<I> Observers<I> {
     pub fn is_empty(&self) -> bool {
         self.observers.is_empty()
     }
+
+    /// Returns the errors of observers which were recorded instead of propagated and clears them.
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// use argmin::core::observers::Observers;
+    /// use argmin::core::IterState;
+    ///
+    /// let mut observers: Observers<IterState<Vec<f64>, (), (), (), f64>> = Observers::new();
+    /// assert!(observers.take_errors().is_empty());
+    /// ```
+    pub fn take_errors(&mut self) -> Vec<ObserverError> {
+        std::mem::take(&mut self.errors)
+    }
 }
 
 /// Implementing [`Observe`] for [`Observers`] allows to use it like a single observer. In its
@@ -159,9 +325,16 @@ impl<I> Observers<I> {
 /// are met and calls the actual observers if required.
 impl<I: State> Observe<I> for Observers<I> {
     /// After initialization of the solver, this loops over all stored observers and calls them.
+    ///
+    /// Errors are handled according to the [`ObserverErrorPolicy`] of each observer. Disabled
+    /// observers are skipped.
     fn observe_init(&mut self, name: &str, kv: &KV) -> Result<(), Error> {
-        for l in self.observers.iter() {
-            l.0.lock().unwrap().observe_init(name, kv)?
+        for (idx, l) in self.observers.iter_mut().enumerate() {
+            if l.disabled() {
+                continue;
+            }
+            let result = l.observer.lock().unwrap().observe_init(name, kv);
+            l.handle(idx, None, result, &mut self.errors)?
         }
         Ok(())
     }
@@ -169,37 +342,94 @@ impl<I: State> Observe<I> for Observers<I> {
     /// Called after each iteration.
     ///
     /// Loops over all observers, and based on whether the condition for calling the observers are
-    /// met, calls them.
+    /// met, calls them. Errors are handled according to the [`ObserverErrorPolicy`] of each
+    /// observer. Disabled observers are skipped.
     fn observe_iter(&mut self, state: &I, kv: &KV) -> Result<(), Error> {
-        for l in self.observers.iter_mut() {
-            let iter = state.get_iter();
-            let observer = &mut l.0.lock().unwrap();
-            match l.1 {
-                ObserverMode::Always => observer.observe_iter(state, kv),
-                ObserverMode::Every(i) if iter % i == 0 => observer.observe_iter(state, kv),
-                ObserverMode::NewBest if state.is_best() => observer.observe_iter(state, kv),
-                ObserverMode::Never | ObserverMode::Every(_) | ObserverMode::NewBest => Ok(()),
-            }?
+        for (idx, l) in self.observers.iter_mut().enumerate() {
+            if l.disabled() || !l.due(state) {
+                continue;
+            }
+            l.last_call = Some(instant::Instant::now());
+            let result = l.observer.lock().unwrap().observe_iter(state, kv);
+            l.handle(idx, Some(state.get_iter()), result, &mut self.errors)?
+        }
+        Ok(())
+    }
+
+    /// Called after the optimization terminated.
+    ///
+    /// Calls all observers except those with mode [`ObserverMode::Never`] and disabled ones.
+    /// Errors are handled according to the [`ObserverErrorPolicy`] of each observer.
+    fn observe_final(&mut self, state: &I, reason: &TerminationReason) -> Result<(), Error> {
+        for (idx, l) in self.observers.iter_mut().enumerate() {
+            if l.mode == ObserverMode::Never || l.disabled() {
+                continue;
+            }
+            let result = l.observer.lock().unwrap().observe_final(state, reason);
+            l.handle(idx, Some(state.get_iter()), result, &mut self.errors)?
         }
         Ok(())
     }
 }
 
+/// Allows one to share an observer between several executors, for instance between the stages
+/// of a [`Chain`](`crate::core::Chain`).
+impl<I, OBS: Observe<I>> Observe<I> for Arc<Mutex<OBS>> {
+    fn observe_init(&mut self, name: &str, kv: &KV) -> Result<(), Error> {
+        self.lock().unwrap().observe_init(name, kv)
+    }
+
+    fn observe_iter(&mut self, state: &I, kv: &KV) -> Result<(), Error> {
+        self.lock().unwrap().observe_iter(state, kv)
+    }
+
+    fn observe_final(&mut self, state: &I, reason: &TerminationReason) -> Result<(), Error> {
+        self.lock().unwrap().observe_final(state, reason)
+    }
+}
+
 /// Indicates when to call an observer.
 ///
 /// `Always` calls the observer in every iteration, `Every(X)` calls the observer every X
 /// iterations, `NewBest` calls the observer only when a new best parameter vector is found and
 /// `Never` deactivates the observer.
+///
+/// For long runs with many cheap iterations, `Interval(D)` calls the observer at most once within
+/// the duration `D`, `Logarithmic` calls it in the iterations 0, 1, 2, 4, 8, ...,
+/// `FirstThenEvery(N, X)` calls it in the first N iterations and every X iterations afterwards
+/// (only in the first N iterations if X is 0) and `NewBestWithHeartbeat(D)` calls it when a new
+/// best parameter vector is found or when it was not called within the duration `D`.
+///
+/// # Example
+///
+/// ```
+/// use argmin::core::observers::ObserverMode;
+/// # use instant::Duration;
+///
+/// // At most once per second
+/// let mode = ObserverMode::Interval(Duration::from_secs(1));
+/// // In the first 100 iterations and every 1000 iterations afterwards
+/// let mode = ObserverMode::FirstThenEvery(100, 1000);
+/// ```
 #[derive(Copy, Clone, Debug, Eq, PartialEq)]
 pub enum ObserverMode {
     /// Never call the observer
     Never,
     /// Call observer in every iteration
     Always,
-    /// Call observer every N iterations
+    /// Call observer every N iterations (never if N is 0)
     Every(u64),
     /// Call observer when new best is found
     NewBest,
+    /// Call observer at most once within the given duration
+    Interval(instant::Duration),
+    /// Call observer in iterations 0, 1, 2, 4, 8, 16, ...
+    Logarithmic,
+    /// Call observer in the first N iterations and every M iterations afterwards (never after the
+    /// first N iterations if M is 0)
+    FirstThenEvery(u64, u64),
+    /// Call observer when new best is found or when it was not called within the given duration
+    NewBestWithHeartbeat(instant::Duration),
 }
 
 impl Default for ObserverMode {
@@ -209,11 +439,186 @@ impl Default for ObserverMode {
     }
 }
 
+/// Indicates how errors returned by an observer are handled.
+///
+/// `Propagate` returns the error, which stops the optimization. `Continue` records the error and
+/// continues the optimization. `DisableAfter(N)` records the error as well, but stops calling the
+/// observer once it failed `N` times. Recorded errors are available in
+/// [`OptimizationResult::observer_errors`](`crate::core::OptimizationResult::observer_errors`).
+#[derive(Copy, Clone, Debug, Eq, PartialEq)]
+pub enum ObserverErrorPolicy {
+    /// Stop the optimization and return the error
+    Propagate,
+    /// Record the error and continue
+    Continue,
+    /// Record the error and continue, disable the observer after N errors
+    DisableAfter(u64),
+}
+
+impl Default for ObserverErrorPolicy {
+    /// The default for `ObserverErrorPolicy` is `Propagate`
+    fn default() -> ObserverErrorPolicy {
+        ObserverErrorPolicy::Propagate
+    }
+}
+
+/// Error returned by an observer which was recorded instead of propagated.
+///
+/// See [`ObserverErrorPolicy`] for details.
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct ObserverError {
+    /// Index of the observer in the order in which the observers were added
+    pub observer: usize,
+    /// Iteration in which the error occurred (`None` if it occurred during initialization)
+    pub iter: Option<u64>,
+    /// Error message
+    pub message: String,
+    /// Whether the observer was disabled after this error
+    pub disabled: bool,
+}
+
+impl std::fmt::Display for ObserverError {
+    fn fmt(&self, f: &mut std::fmt::Formatter) -> std::fmt::Result {
+        write!(f, "Observer {} failed", self.observer)?;
+        if let Some(iter) = self.iter {
+            write!(f, " in iteration {}", iter)?;
+        }
+        write!(f, ": {}", self.message)
+    }
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
 
     send_sync_test!(observermode, ObserverMode);
+    send_sync_test!(observer_error_policy, ObserverErrorPolicy);
+    send_sync_test!(observer_error, ObserverError);
+
+    #[test]
+    fn test_observer_modes() {
+        use crate::core::IterState;
+        use instant::Duration;
+
+        type TState = IterState<Vec<f64>, (), (), (), f64>;
+
+        /// Records the observed iterations
+        struct Iters(Arc<Mutex<Vec<u64>>>);
+
+        impl<I: State> Observe<I> for Iters {
+            fn observe_iter(&mut self, state: &I, _kv: &KV) -> Result<(), Error> {
+                self.0.lock().unwrap().push(state.get_iter());
+                Ok(())
+            }
+        }
+
+        /// Runs 20 iterations in which a new best is found in iterations 3 and 11
+        fn observed(mode: ObserverMode) -> Vec<u64> {
+            let iters = Arc::new(Mutex::new(vec![]));
+            let mut obs: Observers<TState> = Observers::new();
+            obs.push(Iters(iters.clone()), mode);
+            let mut state: TState = IterState::new();
+            for iter in 0..20 {
+                if iter == 3 || iter == 11 {
+                    state.last_best_iter = iter;
+                }
+                obs.observe_iter(&state, &make_kv!()).unwrap();
+                state.increment_iter();
+            }
+            let iters = iters.lock().unwrap().clone();
+            iters
+        }
+
+        assert_eq!(observed(ObserverMode::Logarithmic), vec![0, 1, 2, 4, 8, 16]);
+        assert_eq!(
+            observed(ObserverMode::FirstThenEvery(3, 5)),
+            vec![0, 1, 2, 5, 10, 15]
+        );
+        assert_eq!(observed(ObserverMode::FirstThenEvery(3, 0)), vec![0, 1, 2]);
+        assert!(observed(ObserverMode::FirstThenEvery(0, 0)).is_empty());
+        assert!(observed(ObserverMode::Every(0)).is_empty());
+        assert_eq!(
+            observed(ObserverMode::Interval(Duration::ZERO)),
+            (0..20).collect::<Vec<u64>>()
+        );
+        assert_eq!(
+            observed(ObserverMode::Interval(Duration::from_secs(3600))),
+            vec![0]
+        );
+        assert_eq!(
+            observed(ObserverMode::NewBestWithHeartbeat(Duration::from_secs(
+                3600
+            ))),
+            vec![0, 3, 11]
+        );
+        assert_eq!(
+            observed(ObserverMode::NewBestWithHeartbeat(Duration::ZERO)),
+            (0..20).collect::<Vec<u64>>()
+        );
+    }
+
+    #[test]
+    fn test_observer_error_policy() {
+        use crate::core::IterState;
+
+        /// Fails in every iteration and counts the calls
+        struct Failing(Arc<Mutex<usize>>);
+
+        impl<I: State> Observe<I> for Failing {
+            fn observe_iter(&mut self, state: &I, _kv: &KV) -> Result<(), Error> {
+                *self.0.lock().unwrap() += 1;
+                Err(Error::msg(format!(
+                    "failed in iteration {}",
+                    state.get_iter()
+                )))
+            }
+        }
+
+        type TState = IterState<Vec<f64>, (), (), (), f64>;
+
+        let calls: Vec<Arc<Mutex<usize>>> = (0..3).map(|_| Arc::new(Mutex::new(0))).collect();
+        let mut obs: Observers<TState> = Observers::new();
+        obs.push_with_policy(
+            Failing(calls[0].clone()),
+            ObserverMode::Always,
+            ObserverErrorPolicy::Continue,
+        )
+        .push_with_policy(
+            Failing(calls[1].clone()),
+            ObserverMode::Always,
+            ObserverErrorPolicy::DisableAfter(2),
+        );
+
+        let mut state: TState = IterState::new();
+        for _ in 0..3 {
+            obs.observe_iter(&state, &make_kv!()).unwrap();
+            state.increment_iter();
+        }
+        assert_eq!(*calls[0].lock().unwrap(), 3);
+        assert_eq!(*calls[1].lock().unwrap(), 2);
+
+        let errors = obs.take_errors();
+        assert_eq!(errors.len(), 5);
+        assert_eq!(
+            errors[3],
+            ObserverError {
+                observer: 1,
+                iter: Some(1),
+                message: "failed in iteration 1".to_string(),
+                disabled: true,
+            }
+        );
+        assert!(errors
+            .iter()
+            .filter(|e| e.observer == 0)
+            .all(|e| !e.disabled));
+        assert!(obs.take_errors().is_empty());
+
+        // The default policy propagates the error
+        obs.push(Failing(calls[2].clone()), ObserverMode::Always);
+        assert!(obs.observe_iter(&state, &make_kv!()).is_err());
+        assert_eq!(*calls[2].lock().unwrap(), 1);
+    }
 
     #[test]
     fn test_observers() {
@@ -224,6 +629,8 @@ mod tests {
             pub solver_name: String,
             pub init_called: usize,
             pub iter_called: usize,
+            pub final_called: usize,
+            pub reason: Option<TerminationReason>,
         }
 
         impl TestStor {
@@ -232,6 +639,8 @@ mod tests {
                     solver_name: String::new(),
                     init_called: 0,
                     iter_called: 0,
+                    final_called: 0,
+                    reason: None,
                 }))
             }
         }
@@ -251,6 +660,16 @@ mod tests {
                 self.data.lock().unwrap().iter_called += 1;
                 Ok(())
             }
+
+            fn observe_final(
+                &mut self,
+                _state: &I,
+                reason: &TerminationReason,
+            ) -> Result<(), Error> {
+                self.data.lock().unwrap().final_called += 1;
+                self.data.lock().unwrap().reason = Some(reason.clone());
+                Ok(())
+            }
         }
 
         let test_stor_1 = TestStor::new();
@@ -343,5 +762,17 @@ mod tests {
         assert_eq!(storages[2].lock().unwrap().iter_called, 2);
         assert_eq!(storages[3].lock().unwrap().init_called, 1);
         assert_eq!(storages[3].lock().unwrap().iter_called, 2);
+
+        obs.observe_final(&state, &TerminationReason::Aborted)
+            .unwrap();
+
+        // all observers except those with mode `Never` are called once
+        assert_eq!(storages[0].lock().unwrap().final_called, 0);
+        assert_eq!(storages[0].lock().unwrap().reason, None);
+        for s in storages[1..].iter() {
+            let observer = s.lock().unwrap();
+            assert_eq!(observer.final_called, 1);
+            assert_eq!(observer.reason, Some(TerminationReason::Aborted));
+        }
     }
 }
@@ -0,0 +1,293 @@
+// Copyright 2018-2022 argmin developers
+//
+// Licensed under the Apache License, Version 2.0 <LICENSE-APACHE or
+// http://apache.org/licenses/LICENSE-2.0> or the MIT license <LICENSE-MIT or
+// http://opensource.org/licenses/MIT>, at your option. This file may not be
+// copied, modified, or distributed except according to those terms.
+
+//! # Live progress of the optimization in the terminal.
+//!
+//! See documentation of [`Progress`] for details.
+
+use crate::core::observers::Observe;
+use crate::core::{Error, State, TerminationReason, KV};
+use instant::{Duration, Instant};
+use num_traits::ToPrimitive;
+use std::io::{IsTerminal, Write};
+
+/// Shows the progress of the optimization in the terminal.
+///
+/// If stdout is a terminal, a single status line is updated in place. Otherwise plain lines are
+/// printed periodically. The status line shows
+///
+/// * the number of iterations and the maximum number of iterations,
+/// * the current and the best cost function value,
+/// * the number of iterations since the last new best parameter vector was found,
+/// * the function evaluation counts,
+/// * the elapsed time and an estimate of the remaining time (if the maximum number of
+///   iterations is set).
+///
+/// The output is updated at most every 100 milliseconds on a terminal and at most once per
+/// second otherwise. This can be changed via [`interval`](`Progress::interval`). In addition,
+/// the [`ObserverMode`](`crate::core::observers::ObserverMode`) determines in which iterations
+/// the observer is called at all. Once the optimization terminated, the final state and the
+/// reason for termination are printed.
+///
+/// # Example
+///
+/// ```
+/// # use argmin::core::{Error, Executor};
+/// # use argmin::core::test_utils::{TestSolver, TestProblem};
+/// use argmin::core::observers::{ObserverMode, Progress};
+///
+/// # fn main() -> Result<(), Error> {
+/// let result = Executor::new(TestProblem::new(), TestSolver::new())
+///     .configure(|state| state.param(vec![1.0f64, 0.0]).max_iters(10))
+///     .add_observer(Progress::new(), ObserverMode::Always)
+///     .run()?;
+/// # Ok(())
+/// # }
+/// ```
+pub struct Progress {
+    /// Output
+    writer: Box<dyn Write + Send + Sync>,
+    /// Whether the output is a terminal
+    tty: bool,
+    /// Minimum time between two updates of the output
+    interval: Duration,
+    /// Start of the optimization
+    start: Instant,
+    /// Time of the last update of the output
+    last_update: Option<Instant>,
+}
+
+impl Progress {
+    /// Create a new instance of `Progress` which writes to stdout.
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// use argmin::core::observers::Progress;
+    ///
+    /// let progress = Progress::new();
+    /// ```
+    pub fn new() -> Self {
+        let stdout = std::io::stdout();
+        let tty = stdout.is_terminal();
+        Progress::with_writer(Box::new(stdout), tty)
+    }
+
+    /// Create `Progress` writing to `writer`, updating a single line if `tty` is `true`.
+    fn with_writer(writer: Box<dyn Write + Send + Sync>, tty: bool) -> Self {
+        Progress {
+            writer,
+            tty,
+            interval: if tty {
+                Duration::from_millis(100)
+            } else {
+                Duration::from_secs(1)
+            },
+            start: Instant::now(),
+            last_update: None,
+        }
+    }
+
+    /// Set the minimum time between two updates of the output.
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// use argmin::core::observers::Progress;
+    /// # use instant::Duration;
+    ///
+    /// let progress = Progress::new().interval(Duration::from_secs(5));
+    /// ```
+    #[must_use]
+    pub fn interval(mut self, interval: Duration) -> Self {
+        self.interval = interval;
+        self
+    }
+
+    /// Writes the status for `state` after `iters` iterations.
+    fn write<I: State>(&mut self, state: &I, iters: u64) -> Result<(), Error> {
+        let elapsed = self.start.elapsed();
+        let max_iters = state.get_max_iters();
+
+        let mut line = if max_iters == u64::MAX {
+            format!("iter {}", iters)
+        } else {
+            format!("iter {}/{}", iters, max_iters)
+        };
+        line.push_str(&format!(
+            " | cost {:.6e} | best {:.6e} ({} iters ago)",
+            state.get_cost().to_f64().unwrap_or(f64::NAN),
+            state.get_best_cost().to_f64().unwrap_or(f64::NAN),
+            iters.saturating_sub(state.get_last_best_iter() + 1),
+        ));
+
+        let mut counts: Vec<(&String, &u64)> = state.get_func_counts().iter().collect();
+        counts.sort();
+        if !counts.is_empty() {
+            let counts: Vec<String> = counts
+                .iter()
+                .map(|(k, v)| format!("{} {}", k.trim_end_matches("_count"), v))
+                .collect();
+            line.push_str(&format!(" | evals {}", counts.join(", ")));
+        }
+
+        line.push_str(&format!(" | elapsed {:.1?}", elapsed));
+        if max_iters != u64::MAX && iters > 0 && iters < max_iters {
+            let eta = elapsed.mul_f64((max_iters - iters) as f64 / iters as f64);
+            line.push_str(&format!(" | ETA {:.1?}", eta));
+        }
+
+        if self.tty {
+            // Return to the start of the line and clear it
+            write!(self.writer, "\r\x1b[2K{}", line)?;
+        } else {
+            writeln!(self.writer, "{}", line)?;
+        }
+        self.writer.flush()?;
+        Ok(())
+    }
+}
+
+impl Default for Progress {
+    fn default() -> Self {
+        Progress::new()
+    }
+}
+
+impl<I> Observe<I> for Progress
+where
+    I: State,
+{
+    /// Starts the time measurement.
+    fn observe_init(&mut self, _name: &str, _kv: &KV) -> Result<(), Error> {
+        self.start = Instant::now();
+        self.last_update = None;
+        Ok(())
+    }
+
+    /// Updates the output unless it was updated less than `interval` ago.
+    fn observe_iter(&mut self, state: &I, _kv: &KV) -> Result<(), Error> {
+        let now = Instant::now();
+        if let Some(last_update) = self.last_update {
+            if now.duration_since(last_update) < self.interval {
+                return Ok(());
+            }
+        }
+        self.last_update = Some(now);
+        // `state` refers to the iteration which was just performed
+        self.write(state, state.get_iter() + 1)
+    }
+
+    /// Prints the final state and the reason for termination.
+    fn observe_final(&mut self, state: &I, reason: &TerminationReason) -> Result<(), Error> {
+        self.write(state, state.get_iter())?;
+        if self.tty {
+            writeln!(self.writer)?;
+        }
+        writeln!(self.writer, "{}", reason)?;
+        self.writer.flush()?;
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::core::IterState;
+    use std::sync::{Arc, Mutex};
+
+    send_sync_test!(progress, Progress);
+
+    /// Writer sharing its buffer with the test
+    #[derive(Clone, Default)]
+    struct Buffer(Arc<Mutex<Vec<u8>>>);
+
+    impl Write for Buffer {
+        fn write(&mut self, buf: &[u8]) -> std::io::Result<usize> {
+            self.0.lock().unwrap().write(buf)
+        }
+
+        fn flush(&mut self) -> std::io::Result<()> {
+            Ok(())
+        }
+    }
+
+    impl Buffer {
+        fn contents(&self) -> String {
+            String::from_utf8(self.0.lock().unwrap().clone()).unwrap()
+        }
+    }
+
+    /// Observes three iterations with costs 3, 4 and 2 and returns the output
+    fn observe(mut progress: Progress, buffer: &Buffer) -> String {
+        let mut state: IterState<Vec<f64>, (), (), (), f64> = IterState::new().max_iters(10);
+        Observe::<IterState<Vec<f64>, (), (), (), f64>>::observe_init(
+            &mut progress,
+            "test",
+            &KV::new(),
+        )
+        .unwrap();
+        for cost in [3.0, 4.0, 2.0] {
+            state = state.cost(cost);
+            state.update();
+            state
+                .counts
+                .insert("cost_count".to_string(), state.get_iter() + 1);
+            progress.observe_iter(&state, &KV::new()).unwrap();
+            state.increment_iter();
+        }
+        progress
+            .observe_final(&state, &TerminationReason::TargetCostReached)
+            .unwrap();
+        buffer.contents()
+    }
+
+    #[test]
+    fn test_plain() {
+        let buffer = Buffer::default();
+        let progress =
+            Progress::with_writer(Box::new(buffer.clone()), false).interval(Duration::ZERO);
+        let output = observe(progress, &buffer);
+        let lines: Vec<&str> = output.lines().collect();
+        assert_eq!(lines.len(), 5);
+        assert!(lines[0].starts_with(
+            "iter 1/10 | cost 3.000000e0 | best 3.000000e0 (0 iters ago) | evals cost 1 | elapsed "
+        ));
+        assert!(lines[0].contains(" | ETA "));
+        assert!(lines[1].starts_with(
+            "iter 2/10 | cost 4.000000e0 | best 3.000000e0 (1 iters ago) | evals cost 2 | "
+        ));
+        assert!(lines[2].starts_with("iter 3/10 | cost 2.000000e0 | best 2.000000e0 (0 iters ago)"));
+        assert!(lines[3].starts_with("iter 3/10 | cost 2.000000e0 | best 2.000000e0 (0 iters ago)"));
+        assert_eq!(lines[4], "Target cost value reached");
+    }
+
+    #[test]
+    fn test_interval() {
+        let buffer = Buffer::default();
+        let progress = Progress::with_writer(Box::new(buffer.clone()), false)
+            .interval(Duration::from_secs(3600));
+        let output = observe(progress, &buffer);
+        let lines: Vec<&str> = output.lines().collect();
+        // first iteration and final state
+        assert_eq!(lines.len(), 3);
+        assert!(lines[0].starts_with("iter 1/10"));
+        assert!(lines[1].starts_with("iter 3/10"));
+    }
+
+    #[test]
+    fn test_tty() {
+        let buffer = Buffer::default();
+        let progress =
+            Progress::with_writer(Box::new(buffer.clone()), true).interval(Duration::ZERO);
+        let output = observe(progress, &buffer);
+        // a single line which is updated in place, followed by the termination reason
+        assert_eq!(output.lines().count(), 2);
+        assert_eq!(output.matches("\r\x1b[2K").count(), 4);
+        assert!(output.ends_with("\nTarget cost value reached\n"));
+    }
+}
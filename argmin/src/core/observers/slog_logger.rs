@@ -13,7 +13,8 @@
 //! See [`SlogLogger`] for details regarding usage.
 
 use crate::core::observers::Observe;
-use crate::core::{Error, State, KV};
+use crate::core::{Error, KvValue, State, TerminationReason, KV};
+use num_traits::ToPrimitive;
 use slog;
 use slog::{info, o, Drain, Key, Record, Serializer};
 use slog_async;
@@ -146,8 +147,16 @@ impl SlogLogger {
 
 impl slog::KV for KV {
     fn serialize(&self, _record: &Record, serializer: &mut dyn Serializer) -> slog::Result {
-        for idx in self.kv.iter().rev() {
-            serializer.emit_str(Key::from(idx.0), &idx.1.to_string())?;
+        for (key, val) in self.kv.iter().rev() {
+            let key = Key::from(*key);
+            match val {
+                KvValue::Float(x) => serializer.emit_f64(key, *x),
+                KvValue::Int(x) => serializer.emit_i64(key, *x),
+                KvValue::Uint(x) => serializer.emit_u64(key, *x),
+                KvValue::Bool(x) => serializer.emit_bool(key, *x),
+                KvValue::Str(x) => serializer.emit_str(key, x),
+                KvValue::Vec(_) => serializer.emit_str(key, &val.to_string()),
+            }?;
         }
         Ok(())
     }
@@ -163,8 +172,10 @@ where
         for (k, &v) in self.0.get_func_counts().iter() {
             serializer.emit_u64(Key::from(k.clone()), v)?;
         }
-        serializer.emit_str(Key::from("best_cost"), &self.0.get_best_cost().to_string())?;
-        serializer.emit_str(Key::from("cost"), &self.0.get_cost().to_string())?;
+        let best_cost = self.0.get_best_cost().to_f64().unwrap_or(f64::NAN);
+        let cost = self.0.get_cost().to_f64().unwrap_or(f64::NAN);
+        serializer.emit_f64(Key::from("best_cost"), best_cost)?;
+        serializer.emit_f64(Key::from("cost"), cost)?;
         serializer.emit_u64(Key::from("iter"), self.0.get_iter())?;
         Ok(())
     }
@@ -185,6 +196,12 @@ where
         info!(self.logger, ""; LogState(state), kv);
         Ok(())
     }
+
+    /// Logs the final state and the reason for termination.
+    fn observe_final(&mut self, state: &I, reason: &TerminationReason) -> Result<(), Error> {
+        info!(self.logger, "{}", reason; LogState(state));
+        Ok(())
+    }
 }
 
 #[cfg(test)]
@@ -192,4 +209,65 @@ mod tests {
     use super::*;
 
     send_sync_test!(argmin_slog_loggerv, SlogLogger);
+
+    /// Records the emitted values with their types
+    #[derive(Default)]
+    struct Collect(Vec<String>);
+
+    impl Serializer for Collect {
+        fn emit_arguments(&mut self, key: Key, val: &std::fmt::Arguments) -> slog::Result {
+            self.0.push(format!("{}: str {}", key, val));
+            Ok(())
+        }
+
+        fn emit_f64(&mut self, key: Key, val: f64) -> slog::Result {
+            self.0.push(format!("{}: f64 {}", key, val));
+            Ok(())
+        }
+
+        fn emit_i64(&mut self, key: Key, val: i64) -> slog::Result {
+            self.0.push(format!("{}: i64 {}", key, val));
+            Ok(())
+        }
+
+        fn emit_u64(&mut self, key: Key, val: u64) -> slog::Result {
+            self.0.push(format!("{}: u64 {}", key, val));
+            Ok(())
+        }
+
+        fn emit_bool(&mut self, key: Key, val: bool) -> slog::Result {
+            self.0.push(format!("{}: bool {}", key, val));
+            Ok(())
+        }
+    }
+
+    #[test]
+    fn test_kv_types() {
+        let kv = make_kv!(
+            "float" => 1.5;
+            "int" => -1;
+            "uint" => 2u64;
+            "bool" => true;
+            "str" => "text";
+            "vec" => vec![1.0, 2.0];
+        );
+        let mut collect = Collect::default();
+        slog::KV::serialize(
+            &kv,
+            &slog::record!(slog::Level::Info, "", &format_args!(""), slog::b!()),
+            &mut collect,
+        )
+        .unwrap();
+        assert_eq!(
+            collect.0,
+            vec![
+                "vec: str [1.0, 2.0]",
+                "str: str text",
+                "bool: bool true",
+                "uint: u64 2",
+                "int: i64 -1",
+                "float: f64 1.5",
+            ]
+        );
+    }
 }
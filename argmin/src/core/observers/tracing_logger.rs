@@ -0,0 +1,350 @@
+// Copyright 2018-2022 argmin developers
+//
+// Licensed under the Apache License, Version 2.0 <LICENSE-APACHE or
+// http://apache.org/licenses/LICENSE-2.0> or the MIT license <LICENSE-MIT or
+// http://opensource.org/licenses/MIT>, at your option. This file may not be
+// copied, modified, or distributed except according to those terms.
+
+//! # Logger based on the `tracing` crate
+//!
+//! Reports the progress of the optimization to the subscribers of the
+//! [`tracing`](https://crates.io/crates/tracing) ecosystem.
+//! See [`TracingLogger`] for details regarding usage.
+
+use crate::core::observers::Observe;
+use crate::core::{Error, KvValue, State, TerminationReason, KV};
+use num_traits::ToPrimitive;
+use std::collections::HashMap;
+use std::sync::{Mutex, OnceLock};
+use tracing::field::{DebugValue, Value};
+use tracing::{Event, Level, Metadata, Span};
+use tracing_core::callsite::Callsite;
+use tracing_core::field::FieldSet;
+use tracing_core::metadata::Kind;
+use tracing_core::subscriber::Interest;
+
+/// Maximum number of fields of an iteration event (`iter`, `cost`, `best_cost` and the
+/// key-value pairs of the solver)
+const MAX_FIELDS: usize = 32;
+
+/// Name of the fields which are part of every iteration event
+const STATE_FIELDS: [&str; 3] = ["iter", "cost", "best_cost"];
+
+/// A logger using the [`tracing`](https://crates.io/crates/tracing) crate as backend.
+///
+/// Every optimization run is wrapped in a span named `optimization` with the fields `solver`
+/// (the [`NAME`](`crate::core::Solver::NAME`) of the solver) and `max_iters`. Every observed
+/// iteration emits an event at level `INFO` with target `argmin` within this span. The event
+/// carries `iter`, `cost` and `best_cost` as well as all key-value pairs returned by the solver as
+/// structured fields. At most 32 fields are emitted per event; additional key-value pairs are
+/// omitted. Once the optimization terminated, a final event with the field
+/// `termination_reason` is emitted and the span is closed.
+///
+/// Only available if the `tracing-logger` feature is set.
+///
+/// # Example
+///
+/// ```
+/// # use argmin::core::{Error, Executor};
+/// # use argmin::core::test_utils::{TestSolver, TestProblem};
+/// use argmin::core::observers::{ObserverMode, TracingLogger};
+///
+/// # fn main() -> Result<(), Error> {
+/// // Set up a subscriber, for instance with the `tracing-subscriber` crate.
+/// let result = Executor::new(TestProblem::new(), TestSolver::new())
+///     .configure(|state| state.param(vec![1.0f64, 0.0]).max_iters(10))
+///     .add_observer(TracingLogger::new(), ObserverMode::Always)
+///     .run()?;
+/// # Ok(())
+/// # }
+/// ```
+#[derive(Clone, Debug, Default)]
+pub struct TracingLogger {
+    /// Span of the current optimization run
+    span: Option<Span>,
+}
+
+impl TracingLogger {
+    /// Create a new instance of `TracingLogger`.
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// use argmin::core::observers::TracingLogger;
+    ///
+    /// let logger = TracingLogger::new();
+    /// ```
+    pub fn new() -> Self {
+        TracingLogger { span: None }
+    }
+}
+
+/// Callsite of iteration events with a particular set of fields.
+///
+/// Field names of `tracing` events must be `'static` and known to the callsite. Since the keys of
+/// a [`KV`] are only known at runtime, one callsite is created (and leaked) per distinct set of
+/// keys.
+struct KvCallsite {
+    /// Metadata of the events, set right after the callsite was allocated
+    metadata: OnceLock<Metadata<'static>>,
+}
+
+impl Callsite for KvCallsite {
+    fn set_interest(&self, _interest: Interest) {}
+
+    fn metadata(&self) -> &Metadata<'_> {
+        self.metadata.get().unwrap()
+    }
+}
+
+/// Returns the callsite for events with the fields `names`
+fn callsite(names: Vec<&'static str>) -> &'static KvCallsite {
+    static CALLSITES: OnceLock<Mutex<HashMap<Vec<&'static str>, &'static KvCallsite>>> =
+        OnceLock::new();
+    let mut callsites = CALLSITES
+        .get_or_init(|| Mutex::new(HashMap::new()))
+        .lock()
+        .unwrap();
+    callsites.entry(names).or_insert_with_key(|names| {
+        let callsite: &'static KvCallsite = Box::leak(Box::new(KvCallsite {
+            metadata: OnceLock::new(),
+        }));
+        let names: &'static [&'static str] = Box::leak(names.clone().into_boxed_slice());
+        let _ = callsite.metadata.set(Metadata::new(
+            "iteration",
+            "argmin",
+            Level::INFO,
+            Some(file!()),
+            Some(line!()),
+            Some(module_path!()),
+            FieldSet::new(names, tracing_core::identify_callsite!(callsite)),
+            Kind::EVENT,
+        ));
+        tracing_core::callsite::register(callsite);
+        callsite
+    })
+}
+
+impl<I> Observe<I> for TracingLogger
+where
+    I: State,
+{
+    /// Opens the span of the optimization run.
+    fn observe_init(&mut self, name: &str, kv: &KV) -> Result<(), Error> {
+        let max_iters = kv.get("max_iters").and_then(KvValue::as_u64);
+        self.span = Some(tracing::info_span!(
+            target: "argmin",
+            "optimization",
+            solver = name,
+            max_iters
+        ));
+        Ok(())
+    }
+
+    /// Emits an event with the state and the key-value pairs of the solver.
+    fn observe_iter(&mut self, state: &I, kv: &KV) -> Result<(), Error> {
+        if tracing::level_filters::LevelFilter::current() < Level::INFO {
+            return Ok(());
+        }
+
+        let pairs = &kv.kv[..kv.kv.len().min(MAX_FIELDS - STATE_FIELDS.len())];
+        let names = STATE_FIELDS
+            .iter()
+            .copied()
+            .chain(pairs.iter().map(|(k, _)| *k))
+            .collect();
+        let metadata = callsite(names).metadata();
+        if !tracing::dispatcher::get_default(|dispatch| dispatch.enabled(metadata)) {
+            return Ok(());
+        }
+
+        let iter = state.get_iter();
+        let cost = state.get_cost().to_f64().unwrap_or(f64::NAN);
+        let best_cost = state.get_best_cost().to_f64().unwrap_or(f64::NAN);
+        let vecs: Vec<Option<DebugValue<&Vec<f64>>>> = pairs
+            .iter()
+            .map(|(_, v)| match v {
+                KvValue::Vec(x) => Some(tracing::field::debug(x)),
+                _ => None,
+            })
+            .collect();
+        let values = [&iter as &dyn Value, &cost, &best_cost].into_iter().chain(
+            pairs.iter().zip(vecs.iter()).map(|((_, v), vec)| match v {
+                KvValue::Float(x) => x as &dyn Value,
+                KvValue::Int(x) => x,
+                KvValue::Uint(x) => x,
+                KvValue::Bool(x) => x,
+                KvValue::Str(x) => x,
+                KvValue::Vec(_) => vec.as_ref().unwrap(),
+            }),
+        );
+
+        // `tracing` only accepts arrays of values, hence unused entries are left empty.
+        let fields = metadata.fields();
+        let field_list: Vec<_> = fields.iter().collect();
+        let mut value_array: [_; MAX_FIELDS] = std::array::from_fn(|_| (&field_list[0], None));
+        for (i, value) in values.enumerate() {
+            value_array[i] = (&field_list[i], Some(value));
+        }
+        let value_set = fields.value_set(&value_array);
+
+        match self.span.as_ref().and_then(Span::id) {
+            Some(id) => Event::child_of(id, metadata, &value_set),
+            None => Event::dispatch(metadata, &value_set),
+        }
+        Ok(())
+    }
+
+    /// Emits an event with the reason for termination and closes the span.
+    fn observe_final(&mut self, state: &I, reason: &TerminationReason) -> Result<(), Error> {
+        let span = self.span.take().unwrap_or_else(Span::current);
+        tracing::info!(
+            target: "argmin",
+            parent: &span,
+            iter = state.get_iter(),
+            best_cost = state.get_best_cost().to_f64().unwrap_or(f64::NAN),
+            termination_reason = %reason,
+            "optimization terminated"
+        );
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::core::observers::ObserverMode;
+    use crate::core::test_utils::{TestProblem, TestSolver};
+    use crate::core::{Executor, IterState};
+    use std::fmt::Debug;
+    use std::sync::Arc;
+    use tracing::field::{Field, Visit};
+    use tracing::span::{Attributes, Id, Record};
+    use tracing::Subscriber;
+
+    send_sync_test!(tracing_logger, TracingLogger);
+
+    /// Records the fields of spans and events as strings
+    #[derive(Clone, Default)]
+    struct Collect {
+        spans: Arc<Mutex<Vec<Vec<String>>>>,
+        events: Arc<Mutex<Vec<(Option<u64>, Vec<String>)>>>,
+    }
+
+    struct Fields(Vec<String>);
+
+    impl Visit for Fields {
+        fn record_f64(&mut self, field: &Field, value: f64) {
+            self.0.push(format!("{}: f64 = {}", field, value));
+        }
+
+        fn record_u64(&mut self, field: &Field, value: u64) {
+            self.0.push(format!("{}: u64 = {}", field, value));
+        }
+
+        fn record_str(&mut self, field: &Field, value: &str) {
+            self.0.push(format!("{}: str = {}", field, value));
+        }
+
+        fn record_debug(&mut self, field: &Field, value: &dyn Debug) {
+            self.0.push(format!("{}: debug = {:?}", field, value));
+        }
+    }
+
+    impl Subscriber for Collect {
+        fn enabled(&self, _metadata: &Metadata<'_>) -> bool {
+            true
+        }
+
+        fn new_span(&self, span: &Attributes<'_>) -> Id {
+            let mut fields = Fields(vec![]);
+            span.record(&mut fields);
+            let mut spans = self.spans.lock().unwrap();
+            spans.push(fields.0);
+            Id::from_u64(spans.len() as u64)
+        }
+
+        fn record(&self, _span: &Id, _values: &Record<'_>) {}
+
+        fn record_follows_from(&self, _span: &Id, _follows: &Id) {}
+
+        fn event(&self, event: &Event<'_>) {
+            let mut fields = Fields(vec![]);
+            event.record(&mut fields);
+            let parent = event.parent().map(Id::into_u64);
+            self.events.lock().unwrap().push((parent, fields.0));
+        }
+
+        fn enter(&self, _span: &Id) {}
+
+        fn exit(&self, _span: &Id) {}
+    }
+
+    #[test]
+    fn test_tracing_logger() {
+        let collect = Collect::default();
+        tracing::subscriber::with_default(collect.clone(), || {
+            let mut logger = TracingLogger::new();
+            let mut state: IterState<Vec<f64>, (), (), (), f64> = IterState::new().cost(2.0);
+            state.update();
+            Observe::<IterState<Vec<f64>, (), (), (), f64>>::observe_init(
+                &mut logger,
+                "solver",
+                &make_kv!("max_iters" => 10u64;),
+            )
+            .unwrap();
+            logger
+                .observe_iter(
+                    &state,
+                    &make_kv!("alpha" => 0.5; "action" => "shrink"; "v" => vec![1.0, 2.0];),
+                )
+                .unwrap();
+            logger
+                .observe_final(&state, &TerminationReason::MaxItersReached)
+                .unwrap();
+        });
+
+        assert_eq!(
+            *collect.spans.lock().unwrap(),
+            vec![vec!["solver: str = solver", "max_iters: u64 = 10"]]
+        );
+        let events = collect.events.lock().unwrap();
+        assert_eq!(events.len(), 2);
+        assert_eq!(
+            events[0],
+            (
+                Some(1),
+                vec![
+                    "iter: u64 = 0".to_string(),
+                    "cost: f64 = 2".to_string(),
+                    "best_cost: f64 = 2".to_string(),
+                    "alpha: f64 = 0.5".to_string(),
+                    "action: str = shrink".to_string(),
+                    "v: debug = [1.0, 2.0]".to_string(),
+                ]
+            )
+        );
+        assert_eq!(events[1].0, Some(1));
+        assert!(events[1].1.contains(
+            &"termination_reason: debug = Maximum number of iterations reached".to_string()
+        ));
+    }
+
+    #[test]
+    fn test_executor() {
+        let collect = Collect::default();
+        tracing::subscriber::with_default(collect.clone(), || {
+            Executor::new(TestProblem::new(), TestSolver::new())
+                .configure(|state| state.param(vec![1.0f64, 0.0]).max_iters(5))
+                .add_observer(TracingLogger::new(), ObserverMode::Every(2))
+                .run()
+                .unwrap();
+        });
+        assert_eq!(
+            collect.spans.lock().unwrap()[0],
+            vec!["solver: str = TestSolver", "max_iters: u64 = 5"]
+        );
+        // iterations 0, 2 and 4 plus the final event
+        assert_eq!(collect.events.lock().unwrap().len(), 4);
+    }
+}
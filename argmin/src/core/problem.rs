@@ -5,9 +5,91 @@
 // http://opensource.org/licenses/MIT>, at your option. This file may not be
 // copied, modified, or distributed except according to those terms.
 
-use crate::core::{ArgminFloat, Error};
+use crate::core::{ArgminFloat, Error, SendAlias, SyncAlias};
+use std::borrow::Borrow;
+use std::cell::RefCell;
 use std::collections::HashMap;
 
+thread_local! {
+    /// Function evaluations reported via [`report_evaluations`] and [`report_cache_hit`], one
+    /// entry for every call to [`Problem::problem`] which is currently being evaluated on this
+    /// thread.
+    static NESTED_COUNTS: RefCell<Vec<NestedCounts>> = const { RefCell::new(Vec::new()) };
+}
+
+/// Evaluations reported during a single call to [`Problem::problem`]
+#[derive(Default)]
+pub(crate) struct NestedCounts {
+    /// Evaluations which were performed
+    counts: HashMap<&'static str, u64>,
+    /// Evaluations which were counted but not performed because the result was cached
+    avoided: HashMap<&'static str, u64>,
+}
+
+/// Reports `num` evaluations of the function labelled `counts_string` which were performed
+/// internally while evaluating a method of a problem (for instance the cost function evaluations
+/// of a finite difference gradient). The evaluations are added to the function evaluation counts
+/// of the innermost [`Problem`] which is currently evaluating a method on this thread. Does nothing
+/// if the problem is not evaluated via a `Problem`.
+pub(crate) fn report_evaluations(counts_string: &'static str, num: u64) {
+    NESTED_COUNTS.with(|counts| {
+        if let Some(nested) = counts.borrow_mut().last_mut() {
+            *nested.counts.entry(counts_string).or_insert(0) += num;
+        }
+    });
+}
+
+/// Reports that an evaluation of the function labelled `counts_string` was answered from a cache.
+/// The evaluation is subtracted from `counts_string` and counted as `hits_string` instead in the
+/// function evaluation counts of the innermost [`Problem`] which is currently evaluating a method
+/// on this thread. Does nothing if the problem is not evaluated via a `Problem`.
+pub(crate) fn report_cache_hit(counts_string: &'static str, hits_string: &'static str) {
+    NESTED_COUNTS.with(|counts| {
+        if let Some(nested) = counts.borrow_mut().last_mut() {
+            *nested.counts.entry(hits_string).or_insert(0) += 1;
+            *nested.avoided.entry(counts_string).or_insert(0) += 1;
+        }
+    });
+}
+
+/// Removes the entry of the current call to [`Problem::problem`] from `NESTED_COUNTS`, even if the
+/// evaluation panics.
+struct NestedCountsGuard;
+
+impl Drop for NestedCountsGuard {
+    fn drop(&mut self) {
+        NESTED_COUNTS.with(|counts| counts.borrow_mut().pop());
+    }
+}
+
+/// Calls `func` and returns its result together with all evaluations reported during the call.
+pub(crate) fn collect_evaluations<T, F: FnOnce() -> T>(func: F) -> (T, NestedCounts) {
+    NESTED_COUNTS.with(|counts| counts.borrow_mut().push(NestedCounts::default()));
+    let guard = NestedCountsGuard;
+    let res = func();
+    let nested = NESTED_COUNTS
+        .with(|counts| counts.borrow_mut().last_mut().map(std::mem::take))
+        .unwrap_or_default();
+    drop(guard);
+    (res, nested)
+}
+
+/// Reports evaluations collected via [`collect_evaluations`] (possibly on another thread) to the
+/// innermost [`Problem`] which is currently evaluating a method on this thread.
+#[cfg(feature = "rayon")]
+pub(crate) fn forward_evaluations(nested: NestedCounts) {
+    NESTED_COUNTS.with(|counts| {
+        if let Some(current) = counts.borrow_mut().last_mut() {
+            for (k, v) in nested.counts {
+                *current.counts.entry(k).or_insert(0) += v;
+            }
+            for (k, v) in nested.avoided {
+                *current.avoided.entry(k).or_insert(0) += v;
+            }
+        }
+    });
+}
+
 /// Wrapper around problems defined by users.
 ///
 /// Keeps track of how many times methods such as `apply`, `cost`, `gradient`, `jacobian`,
@@ -122,14 +204,38 @@ impl<O> Problem<O> {
     /// that it is not possible to `impl` a type from another crate. Therefore if one implements a
     /// solver outside of argmin, `.problem(...)` has to be called directly as shown in the first
     /// example.
+    ///
+    /// Evaluations which the stored problem performs internally, such as the cost function
+    /// evaluations of the finite difference adapters [`FiniteDiff`](`crate::core::FiniteDiff`)
+    /// and [`ComplexStep`](`crate::core::ComplexStep`), are added to the counts as well. Calls
+    /// which are answered from the cache of a [`CachedProblem`](`crate::core::CachedProblem`) are
+    /// not counted as evaluations but as cache hits.
     pub fn problem<T, F: FnOnce(&O) -> Result<T, Error>>(
         &mut self,
         counts_string: &'static str,
         func: F,
     ) -> Result<T, Error> {
-        let count = self.counts.entry(counts_string).or_insert(0);
-        *count += 1;
-        func(self.problem.as_ref().unwrap())
+        self.problem_counted(counts_string, 1, func)
+    }
+
+    /// Like [`Problem::problem`], but counts `num` evaluations.
+    fn problem_counted<T, F: FnOnce(&O) -> Result<T, Error>>(
+        &mut self,
+        counts_string: &'static str,
+        num: u64,
+        func: F,
+    ) -> Result<T, Error> {
+        *self.counts.entry(counts_string).or_insert(0) += num;
+        let problem = self.problem.as_ref().unwrap();
+        let (res, nested) = collect_evaluations(|| func(problem));
+        for (k, v) in nested.counts {
+            *self.counts.entry(k).or_insert(0) += v;
+        }
+        for (k, v) in nested.avoided {
+            let count = self.counts.entry(k).or_insert(0);
+            *count = count.saturating_sub(v);
+        }
+        res
     }
 
     /// Returns the internally stored problem and replaces it with `None`.
@@ -356,6 +462,62 @@ pub trait CostFunction {
 
     /// Compute cost function
     fn cost(&self, param: &Self::Param) -> Result<Self::Output, Error>;
+
+    /// Compute cost function for multiple parameter vectors at once
+    ///
+    /// By default, the cost function is evaluated sequentially for all parameter vectors. If the
+    /// `rayon` feature is enabled, the evaluations are performed in parallel, unless
+    /// [`parallelize`](`CostFunction::parallelize`) returns `false`. This method can be
+    /// overwritten in order to provide a more efficient (for instance vectorized) implementation.
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// # use argmin::core::{CostFunction, Error};
+    /// # use argmin::core::test_utils::TestProblem;
+    /// #
+    /// let problem = TestProblem::new();
+    ///
+    /// let costs = problem.bulk_cost(&[vec![1.0, 2.0], vec![3.0, 4.0]])?;
+    /// # assert_eq!(costs.len(), 2);
+    /// # Ok::<(), Error>(())
+    /// ```
+    fn bulk_cost<P>(&self, params: &[P]) -> Result<Vec<Self::Output>, Error>
+    where
+        P: Borrow<Self::Param> + SyncAlias,
+        Self::Output: SendAlias,
+        Self: SyncAlias,
+    {
+        #[cfg(feature = "rayon")]
+        {
+            if self.parallelize() {
+                use rayon::prelude::*;
+                // Evaluations reported by wrapped problems on the worker threads are forwarded
+                // to the calling thread such that they are counted.
+                let results: Vec<_> = params
+                    .par_iter()
+                    .map(|param| collect_evaluations(|| self.cost(param.borrow())))
+                    .collect();
+                return results
+                    .into_iter()
+                    .map(|(res, nested)| {
+                        forward_evaluations(nested);
+                        res
+                    })
+                    .collect();
+            }
+        }
+        params
+            .iter()
+            .map(|param| self.cost(param.borrow()))
+            .collect()
+    }
+
+    /// Indicates whether [`bulk_cost`](`CostFunction::bulk_cost`) evaluates the cost function in
+    /// parallel if the `rayon` feature is enabled. Defaults to `true`.
+    fn parallelize(&self) -> bool {
+        true
+    }
 }
 
 /// Defines the computation of the gradient.
@@ -386,8 +548,105 @@ pub trait Gradient {
 
     /// Compute gradient
     fn gradient(&self, param: &Self::Param) -> Result<Self::Gradient, Error>;
+
+    /// Compute cost function value and gradient at the same point.
+    ///
+    /// Solvers which require both the cost function value and the gradient at the same parameter
+    /// vector call this method (via [`Problem::cost_and_gradient`]). By default, `cost` and
+    /// `gradient` are called one after the other. Problems which can compute both more
+    /// efficiently together (for instance in a single forward/backward pass) should override
+    /// this method.
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// use argmin::core::{CostFunction, Gradient, Error};
+    ///
+    /// struct Paraboloid {}
+    ///
+    /// impl CostFunction for Paraboloid {
+    ///     type Param = Vec<f64>;
+    ///     type Output = f64;
+    ///
+    ///     fn cost(&self, param: &Self::Param) -> Result<Self::Output, Error> {
+    ///         Ok(param.iter().map(|x| x.powi(2)).sum())
+    ///     }
+    /// }
+    ///
+    /// impl Gradient for Paraboloid {
+    ///     type Param = Vec<f64>;
+    ///     type Gradient = Vec<f64>;
+    ///
+    ///     fn gradient(&self, param: &Self::Param) -> Result<Self::Gradient, Error> {
+    ///         Ok(param.iter().map(|x| 2.0 * x).collect())
+    ///     }
+    ///
+    ///     /// Compute both in a single pass over `param`
+    ///     fn cost_and_gradient(&self, param: &Self::Param) -> Result<(f64, Vec<f64>), Error> {
+    ///         Ok(param
+    ///             .iter()
+    ///             .fold((0.0, vec![]), |(cost, mut grad), x| {
+    ///                 grad.push(2.0 * x);
+    ///                 (cost + x.powi(2), grad)
+    ///             }))
+    ///     }
+    /// }
+    /// # let (cost, grad) = Paraboloid {}.cost_and_gradient(&vec![1.0, 2.0]).unwrap();
+    /// # assert_eq!(cost, 5.0);
+    /// # assert_eq!(grad, vec![2.0, 4.0]);
+    /// ```
+    fn cost_and_gradient(
+        &self,
+        param: &<Self as Gradient>::Param,
+    ) -> Result<(<Self as CostFunction>::Output, Self::Gradient), Error>
+    where
+        Self: CostFunction<Param = <Self as Gradient>::Param>,
+    {
+        Ok((self.cost(param)?, self.gradient(param)?))
+    }
 }
 
+/// Problems which provide both a cost function and a gradient
+///
+/// This trait is automatically implemented for all types which implement [`CostFunction`] and
+/// [`Gradient`] with the same `Param` type. It is required by [`Problem::cost_and_gradient`] and
+/// by all solvers which evaluate the cost function value and the gradient at the same point. The
+/// combined evaluation falls back to calling `cost` and `gradient` one after the other and can be
+/// customized by overriding [`Gradient::cost_and_gradient`].
+///
+/// # Example
+///
+/// ```
+/// use argmin::core::{CostFunction, CostGradient, Gradient, Error};
+///
+/// struct Paraboloid {}
+///
+/// impl CostFunction for Paraboloid {
+///     type Param = Vec<f64>;
+///     type Output = f64;
+///
+///     fn cost(&self, param: &Self::Param) -> Result<Self::Output, Error> {
+///         Ok(param.iter().map(|x| x.powi(2)).sum())
+///     }
+/// }
+///
+/// impl Gradient for Paraboloid {
+///     type Param = Vec<f64>;
+///     type Gradient = Vec<f64>;
+///
+///     fn gradient(&self, param: &Self::Param) -> Result<Self::Gradient, Error> {
+///         Ok(param.iter().map(|x| 2.0 * x).collect())
+///     }
+/// }
+///
+/// fn requires_cost_gradient<O: CostGradient>(_problem: &O) {}
+///
+/// requires_cost_gradient(&Paraboloid {});
+/// ```
+pub trait CostGradient: CostFunction + Gradient<Param = <Self as CostFunction>::Param> {}
+
+impl<T> CostGradient for T where T: CostFunction + Gradient<Param = <T as CostFunction>::Param> {}
+
 /// Defines the computation of the Hessian.
 ///
 /// # Example
@@ -544,8 +803,9 @@ impl<O: Operator> Problem<O> {
     }
 }
 
-/// Wraps a call to `cost` defined in the `CostFunction` trait and as such allows to call `cost` on
-/// an instance of `Problem`. Internally, the number of evaluations of `cost` is counted.
+/// Wraps calls to `cost` and `bulk_cost` defined in the `CostFunction` trait and as such allows to
+/// call `cost` and `bulk_cost` on an instance of `Problem`. Internally, the number of evaluations of
+/// `cost` is counted.
 impl<O: CostFunction> Problem<O> {
     /// Calls `cost` defined in the `CostFunction` trait and keeps track of the number of
     /// evaluations.
@@ -579,6 +839,47 @@ impl<O: CostFunction> Problem<O> {
     pub fn cost(&mut self, param: &O::Param) -> Result<O::Output, Error> {
         self.problem("cost_count", |problem| problem.cost(param))
     }
+
+    /// Calls `bulk_cost` defined in the `CostFunction` trait and keeps track of the number of
+    /// evaluations. Each parameter vector counts as one evaluation of the cost function.
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// # use argmin::core::{Problem, CostFunction, Error};
+    /// #
+    /// # #[derive(Eq, PartialEq, Debug, Clone)]
+    /// # struct UserDefinedProblem {};
+    /// #
+    /// # impl CostFunction for UserDefinedProblem {
+    /// #     type Param = Vec<f64>;
+    /// #     type Output = f64;
+    /// #
+    /// #     fn cost(&self, param: &Self::Param) -> Result<Self::Output, Error> {
+    /// #         Ok(param[0] + param[1])
+    /// #     }
+    /// # }
+    /// // `UserDefinedProblem` implements `CostFunction`.
+    /// let mut problem1 = Problem::new(UserDefinedProblem {});
+    ///
+    /// let params = vec![vec![2.0f64, 1.0f64], vec![3.0f64, 4.0f64]];
+    ///
+    /// let costs = problem1.bulk_cost(&params)?;
+    ///
+    /// assert_eq!(problem1.counts["cost_count"], 2);
+    /// # assert_eq!(costs, vec![3.0f64, 7.0f64]);
+    /// # Ok::<(), Error>(())
+    /// ```
+    pub fn bulk_cost<P>(&mut self, params: &[P]) -> Result<Vec<O::Output>, Error>
+    where
+        P: Borrow<O::Param> + SyncAlias,
+        O::Output: SendAlias,
+        O: SyncAlias,
+    {
+        self.problem_counted("cost_count", params.len() as u64, |problem| {
+            problem.bulk_cost(params)
+        })
+    }
 }
 
 /// Wraps a call to `gradient` defined in the `Gradient` trait and as such allows to call `gradient` on
@@ -618,6 +919,63 @@ impl<O: Gradient> Problem<O> {
     }
 }
 
+/// Wraps a call to `cost_and_gradient` defined in the `Gradient` trait and as such allows to call
+/// `cost_and_gradient` on an instance of `Problem`. Internally, the number of evaluations of both
+/// `cost` and `gradient` is counted.
+impl<O: CostGradient> Problem<O> {
+    /// Calls `cost_and_gradient` defined in the `Gradient` trait and keeps track of the number of
+    /// evaluations. Each call counts as one evaluation of both the cost function and the
+    /// gradient.
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// # use argmin::core::{Problem, CostFunction, Gradient, Error};
+    /// #
+    /// # #[derive(Eq, PartialEq, Debug, Clone)]
+    /// # struct UserDefinedProblem {};
+    /// #
+    /// # impl CostFunction for UserDefinedProblem {
+    /// #     type Param = Vec<f64>;
+    /// #     type Output = f64;
+    /// #
+    /// #     fn cost(&self, param: &Self::Param) -> Result<Self::Output, Error> {
+    /// #         Ok(param[0] + param[1])
+    /// #     }
+    /// # }
+    /// #
+    /// # impl Gradient for UserDefinedProblem {
+    /// #     type Param = Vec<f64>;
+    /// #     type Gradient = Vec<f64>;
+    /// #
+    /// #     fn gradient(&self, param: &Self::Param) -> Result<Self::Gradient, Error> {
+    /// #         Ok(vec![1.0f64, 1.0f64])
+    /// #     }
+    /// # }
+    /// // `UserDefinedProblem` implements `CostFunction` and `Gradient`.
+    /// let mut problem1 = Problem::new(UserDefinedProblem {});
+    ///
+    /// let param = vec![2.0f64, 1.0f64];
+    ///
+    /// let (cost, grad) = problem1.cost_and_gradient(&param)?;
+    ///
+    /// assert_eq!(problem1.counts["cost_count"], 1);
+    /// assert_eq!(problem1.counts["gradient_count"], 1);
+    /// # assert_eq!(cost, 3.0f64);
+    /// # assert_eq!(grad, vec![1.0f64, 1.0f64]);
+    /// # Ok::<(), Error>(())
+    /// ```
+    pub fn cost_and_gradient(
+        &mut self,
+        param: &<O as CostFunction>::Param,
+    ) -> Result<(<O as CostFunction>::Output, <O as Gradient>::Gradient), Error> {
+        self.problem("cost_count", |problem| {
+            report_evaluations("gradient_count", 1);
+            problem.cost_and_gradient(param)
+        })
+    }
+}
+
 /// Wraps a call to `hessian` defined in the `Hessian` trait and as such allows to call `hessian` on
 /// an instance of `Problem`. Internally, the number of evaluations of `hessian` is counted.
 impl<O: Hessian> Problem<O> {
@@ -5,7 +5,8 @@
 // http://opensource.org/licenses/MIT>, at your option. This file may not be
 // copied, modified, or distributed except according to those terms.
 
-use crate::core::{ArgminFloat, Problem, Solver, State};
+use crate::core::observers::ObserverError;
+use crate::core::{ArgminFloat, History, Problem, Solver, State};
 use num_traits::{Float, FromPrimitive};
 use std::cmp::Ordering;
 use std::fmt;
@@ -15,17 +16,42 @@ use std::fmt;
 /// Consists of the problem and the final state of the solver.
 /// Both can be accessed via deconstructing or via the methods
 /// [`problem`](`OptimizationResult::problem`) and [`state`](`OptimizationResult::state`).
-#[derive(Clone)]
-pub struct OptimizationResult<O, S, I> {
+/// If enabled, the convergence history is available via [`history`](`OptimizationResult::history`).
+/// Errors of observers which were recorded instead of propagated (see
+/// [`ObserverErrorPolicy`](`crate::core::observers::ObserverErrorPolicy`)) are available via
+/// [`observer_errors`](`OptimizationResult::observer_errors`).
+pub struct OptimizationResult<O, S, I: State> {
     /// Problem
     pub problem: Problem<O>,
     /// Solver
     pub solver: S,
     /// Iteration state
     pub state: I,
+    /// Convergence history (only if recorded)
+    pub history: Option<History<I::Param, I::Float>>,
+    /// Errors of observers which were recorded instead of propagated
+    pub observer_errors: Vec<ObserverError>,
 }
 
-impl<O, S, I> OptimizationResult<O, S, I> {
+impl<O, S, I> Clone for OptimizationResult<O, S, I>
+where
+    O: Clone,
+    S: Clone,
+    I: State + Clone,
+    I::Param: Clone,
+{
+    fn clone(&self) -> Self {
+        OptimizationResult {
+            problem: self.problem.clone(),
+            solver: self.solver.clone(),
+            state: self.state.clone(),
+            history: self.history.clone(),
+            observer_errors: self.observer_errors.clone(),
+        }
+    }
+}
+
+impl<O, S, I: State> OptimizationResult<O, S, I> {
     /// Constructs a new instance of `OptimizationResult` from a `problem` and a `state`.
     ///
     /// # Example
@@ -43,7 +69,7 @@ impl<O, S, I> OptimizationResult<O, S, I> {
     /// let solver = SomeSolver {};
     ///
     /// let result = OptimizationResult::new(Problem::new(rosenbrock), solver, state);
-    /// # let OptimizationResult { mut problem, solver, state } = result;
+    /// # let OptimizationResult { mut problem, solver, state, .. } = result;
     /// # assert_eq!(problem.take_problem().unwrap(), TestProblem::new());
     /// # assert_eq!(solver, SomeSolver {});
     /// ```
@@ -52,6 +78,8 @@ impl<O, S, I> OptimizationResult<O, S, I> {
             problem,
             solver,
             state,
+            history: None,
+            observer_errors: vec![],
         }
     }
 
@@ -114,6 +142,48 @@ impl<O, S, I> OptimizationResult<O, S, I> {
     pub fn state(&self) -> &I {
         &self.state
     }
+
+    /// Returns a reference to the convergence history if it was recorded.
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// # use argmin::core::{Problem, OptimizationResult, IterState, State};
+    /// #
+    /// # struct Rosenbrock {}
+    /// # let solver = ();
+    /// #
+    /// # let state: IterState<Vec<f64>, (), (), (), f64> = IterState::new();
+    /// #
+    /// # let result = OptimizationResult::new(Problem::new(Rosenbrock {}), solver, state);
+    /// #
+    /// let history = result.history();
+    /// # assert!(history.is_none());
+    /// ```
+    pub fn history(&self) -> Option<&History<I::Param, I::Float>> {
+        self.history.as_ref()
+    }
+
+    /// Returns the errors of observers which were recorded instead of propagated.
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// # use argmin::core::{Problem, OptimizationResult, IterState, State};
+    /// #
+    /// # struct Rosenbrock {}
+    /// # let solver = ();
+    /// #
+    /// # let state: IterState<Vec<f64>, (), (), (), f64> = IterState::new();
+    /// #
+    /// # let result = OptimizationResult::new(Problem::new(Rosenbrock {}), solver, state);
+    /// #
+    /// let errors = result.observer_errors();
+    /// # assert!(errors.is_empty());
+    /// ```
+    pub fn observer_errors(&self) -> &[ObserverError] {
+        &self.observer_errors
+    }
 }
 
 impl<O, S, I> std::fmt::Display for OptimizationResult<O, S, I>
@@ -448,7 +448,7 @@ where
     /// # assert_eq!(termination_reason, TerminationReason::NotTerminated);
     /// ```
     fn get_termination_reason(&self) -> TerminationReason {
-        self.termination_reason
+        self.termination_reason.clone()
     }
 
     /// Returns the time elapsed since the start of the optimization.
@@ -9,7 +9,7 @@
 use serde::{Deserialize, Serialize};
 
 /// Reasons for optimization algorithms to stop
-#[derive(Debug, Clone, Copy, Eq, PartialEq, Hash)]
+#[derive(Debug, Clone, Eq, PartialEq, Hash)]
 #[cfg_attr(feature = "serde1", derive(Serialize, Deserialize))]
 pub enum TerminationReason {
     /// The optimization algorithm is not terminated
@@ -30,6 +30,13 @@ pub enum TerminationReason {
     LineSearchConditionMet,
     /// Reached target tolerance
     TargetToleranceReached,
+    /// Maximum wall-clock time exceeded
+    Timeout,
+    /// Maximum number of evaluations of a function of the problem reached
+    MaxFuncCountsReached,
+    /// Terminated for a reason defined by a user, for instance in a
+    /// [`TerminationCriterion`](`crate::core::termination_criteria::TerminationCriterion`)
+    Custom(String),
     /// Algorithm aborted
     Aborted,
 }
@@ -50,10 +57,13 @@ impl TerminationReason {
     /// assert!(TerminationReason::BestStallIterExceeded.terminated());
     /// assert!(TerminationReason::LineSearchConditionMet.terminated());
     /// assert!(TerminationReason::TargetToleranceReached.terminated());
+    /// assert!(TerminationReason::Timeout.terminated());
+    /// assert!(TerminationReason::MaxFuncCountsReached.terminated());
+    /// assert!(TerminationReason::Custom("reason".to_string()).terminated());
     /// assert!(TerminationReason::Aborted.terminated());
     /// assert!(!TerminationReason::NotTerminated.terminated());
     /// ```
-    pub fn terminated(self) -> bool {
+    pub fn terminated(&self) -> bool {
         !matches!(self, TerminationReason::NotTerminated)
     }
 
@@ -97,6 +107,18 @@ impl TerminationReason {
     ///     "Target tolerance reached"
     /// );
     /// assert_eq!(
+    ///     TerminationReason::Timeout.text(),
+    ///     "Timeout reached"
+    /// );
+    /// assert_eq!(
+    ///     TerminationReason::MaxFuncCountsReached.text(),
+    ///     "Maximum number of function evaluations reached"
+    /// );
+    /// assert_eq!(
+    ///     TerminationReason::Custom("Some reason".to_string()).text(),
+    ///     "Some reason"
+    /// );
+    /// assert_eq!(
     ///     TerminationReason::Aborted.text(),
     ///     "Optimization aborted"
     /// );
@@ -106,7 +128,7 @@ impl TerminationReason {
     /// );
     /// ```
     pub fn text(&self) -> &str {
-        match *self {
+        match self {
             TerminationReason::NotTerminated => "Not terminated",
             TerminationReason::MaxItersReached => "Maximum number of iterations reached",
             TerminationReason::TargetCostReached => "Target cost value reached",
@@ -116,6 +138,11 @@ impl TerminationReason {
             TerminationReason::BestStallIterExceeded => "Best stall iterations exceeded",
             TerminationReason::LineSearchConditionMet => "Line search condition met",
             TerminationReason::TargetToleranceReached => "Target tolerance reached",
+            TerminationReason::Timeout => "Timeout reached",
+            TerminationReason::MaxFuncCountsReached => {
+                "Maximum number of function evaluations reached"
+            }
+            TerminationReason::Custom(reason) => reason,
             TerminationReason::Aborted => "Optimization aborted",
         }
     }
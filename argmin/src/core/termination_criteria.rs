@@ -0,0 +1,456 @@
+// Copyright 2018-2022 argmin developers
+//
+// Licensed under the Apache License, Version 2.0 <LICENSE-APACHE or
+// http://apache.org/licenses/LICENSE-2.0> or the MIT license <LICENSE-MIT or
+// http://opensource.org/licenses/MIT>, at your option. This file may not be
+// copied, modified, or distributed except according to those terms.
+
+//! # Termination criteria
+//!
+//! Termination criteria are evaluated by the [`Executor`](`crate::core::Executor`) before every
+//! iteration, in addition to the stopping criteria of the solver itself (see
+//! [`Solver::terminate`](`crate::core::Solver::terminate`)). They can be attached to an
+//! `Executor` via [`add_termination`](`crate::core::Executor::add_termination`).
+//!
+//! The following criteria are available:
+//!
+//! * [`CostChange`]: Relative change of the cost function value over a number of iterations
+//! * [`ParamChange`]: Norm of the change of the parameter vector between two iterations
+//! * [`GradientNorm`]: Norm of the gradient
+//!
+//! Criteria can be combined via [`Any`] and [`All`]. Custom criteria can be defined by
+//! implementing the [`TerminationCriterion`] trait.
+
+use crate::core::{ArgminFloat, IterState, State, TerminationReason};
+use argmin_math::{ArgminNorm, ArgminSub};
+use std::collections::VecDeque;
+
+/// An interface which every termination criterion is required to implement
+///
+/// # Example
+///
+/// ```
+/// use argmin::core::{State, TerminationReason};
+/// use argmin::core::termination_criteria::TerminationCriterion;
+///
+/// struct MyCriterion {}
+///
+/// impl<I> TerminationCriterion<I> for MyCriterion
+/// where
+///     I: State,
+/// {
+///     fn terminate(&mut self, state: &I) -> TerminationReason {
+///         // Check whether the optimization should stop based on `state`
+///         if state.get_iter() > 42 {
+///             TerminationReason::Custom("Reached iteration 42".to_string())
+///         } else {
+///             TerminationReason::NotTerminated
+///         }
+///     }
+/// }
+/// ```
+pub trait TerminationCriterion<I> {
+    /// Called once before every iteration (including the first one).
+    ///
+    /// Returns [`TerminationReason::NotTerminated`] if the optimization should continue and any
+    /// other `TerminationReason` otherwise. Custom reasons can be reported via
+    /// [`TerminationReason::Custom`].
+    fn terminate(&mut self, state: &I) -> TerminationReason;
+}
+
+/// Terminates when the relative change of the cost function value over a window of iterations is
+/// below a tolerance.
+///
+/// The current cost function value is compared to the cost function value `window` iterations
+/// earlier. If `|c_old - c_new| <= tol * max(|c_old|, |c_new|)`, the optimization terminates with
+/// [`TerminationReason::NoChangeInCost`]. Pairs of cost function values which are not both finite
+/// (such as the initial cost of infinity of solvers without initialization) never terminate the
+/// optimization.
+///
+/// # Example
+///
+/// ```
+/// use argmin::core::termination_criteria::CostChange;
+///
+/// // Terminate if the cost improved by less than 0.01% within the last 10 iterations
+/// let criterion: CostChange<f64> = CostChange::new(1e-4, 10);
+/// ```
+#[derive(Clone, Debug)]
+pub struct CostChange<F> {
+    /// Tolerance
+    tol: F,
+    /// Number of iterations
+    window: usize,
+    /// Cost function values of the most recent iterations
+    costs: VecDeque<F>,
+}
+
+impl<F> CostChange<F> {
+    /// Constructs a new `CostChange` criterion with tolerance `tol` comparing cost function values
+    /// which are `window` iterations apart. A `window` of `0` is treated as `1`.
+    pub fn new(tol: F, window: usize) -> Self {
+        let window = window.max(1);
+        CostChange {
+            tol,
+            window,
+            costs: VecDeque::with_capacity(window + 1),
+        }
+    }
+}
+
+impl<I, F> TerminationCriterion<I> for CostChange<F>
+where
+    I: State<Float = F>,
+    F: ArgminFloat,
+{
+    fn terminate(&mut self, state: &I) -> TerminationReason {
+        self.costs.push_back(state.get_cost());
+        if self.costs.len() <= self.window {
+            return TerminationReason::NotTerminated;
+        }
+        let c_old = self.costs.pop_front().unwrap();
+        let c_new = *self.costs.back().unwrap();
+        if c_old.is_finite()
+            && c_new.is_finite()
+            && (c_old - c_new).abs() <= self.tol * c_old.abs().max(c_new.abs())
+        {
+            TerminationReason::NoChangeInCost
+        } else {
+            TerminationReason::NotTerminated
+        }
+    }
+}
+
+/// Terminates when the norm of the step between the parameter vectors of two consecutive
+/// iterations is below a tolerance.
+///
+/// Terminates with [`TerminationReason::TargetToleranceReached`].
+///
+/// # Example
+///
+/// ```
+/// use argmin::core::termination_criteria::ParamChange;
+///
+/// let criterion: ParamChange<Vec<f64>, f64> = ParamChange::new(1e-8);
+/// ```
+#[derive(Clone, Debug)]
+pub struct ParamChange<P, F> {
+    /// Tolerance
+    tol: F,
+    /// Parameter vector of the previous iteration
+    prev_param: Option<P>,
+}
+
+impl<P, F> ParamChange<P, F> {
+    /// Constructs a new `ParamChange` criterion with tolerance `tol`.
+    pub fn new(tol: F) -> Self {
+        ParamChange {
+            tol,
+            prev_param: None,
+        }
+    }
+}
+
+impl<I, P, F> TerminationCriterion<I> for ParamChange<P, F>
+where
+    I: State<Param = P, Float = F>,
+    P: Clone + ArgminSub<P, P> + ArgminNorm<F>,
+    F: ArgminFloat,
+{
+    fn terminate(&mut self, state: &I) -> TerminationReason {
+        let param = match state.get_param() {
+            Some(param) => param,
+            None => return TerminationReason::NotTerminated,
+        };
+        let reason = match self.prev_param.as_ref() {
+            Some(prev_param) if param.sub(prev_param).norm() < self.tol => {
+                TerminationReason::TargetToleranceReached
+            }
+            _ => TerminationReason::NotTerminated,
+        };
+        self.prev_param = Some(param.clone());
+        reason
+    }
+}
+
+/// Terminates when the norm of the gradient is below a tolerance.
+///
+/// Only available for solvers using [`IterState`]. Terminates with
+/// [`TerminationReason::TargetPrecisionReached`].
+///
+/// # Example
+///
+/// ```
+/// use argmin::core::termination_criteria::GradientNorm;
+///
+/// let criterion = GradientNorm::new(1e-6f64);
+/// ```
+#[derive(Clone, Debug)]
+pub struct GradientNorm<F> {
+    /// Tolerance
+    tol: F,
+}
+
+impl<F> GradientNorm<F> {
+    /// Constructs a new `GradientNorm` criterion with tolerance `tol`.
+    pub fn new(tol: F) -> Self {
+        GradientNorm { tol }
+    }
+}
+
+impl<P, G, J, H, F> TerminationCriterion<IterState<P, G, J, H, F>> for GradientNorm<F>
+where
+    IterState<P, G, J, H, F>: State<Float = F>,
+    G: ArgminNorm<F>,
+    F: ArgminFloat,
+{
+    fn terminate(&mut self, state: &IterState<P, G, J, H, F>) -> TerminationReason {
+        match state.get_grad() {
+            Some(grad) if grad.norm() < self.tol => TerminationReason::TargetPrecisionReached,
+            _ => TerminationReason::NotTerminated,
+        }
+    }
+}
+
+/// Terminates as soon as any of the contained criteria terminates.
+///
+/// All criteria are evaluated in every iteration, such that criteria which keep track of previous
+/// iterations remain up to date. The reason of the first criterion which terminates is returned.
+///
+/// # Example
+///
+/// ```
+/// use argmin::core::IterState;
+/// use argmin::core::termination_criteria::{Any, CostChange, GradientNorm};
+///
+/// let criterion: Any<IterState<Vec<f64>, Vec<f64>, (), (), f64>> = Any::new()
+///     .add_criterion(CostChange::new(1e-6, 5))
+///     .add_criterion(GradientNorm::new(1e-8));
+/// ```
+pub struct Any<I> {
+    /// Combined criteria
+    criteria: Vec<Box<dyn TerminationCriterion<I>>>,
+}
+
+impl<I: State> Any<I> {
+    /// Constructs an empty `Any` criterion which never terminates.
+    pub fn new() -> Self {
+        Any { criteria: vec![] }
+    }
+
+    /// Adds a criterion.
+    #[must_use]
+    pub fn add_criterion<C: TerminationCriterion<I> + 'static>(mut self, criterion: C) -> Self {
+        self.criteria.push(Box::new(criterion));
+        self
+    }
+}
+
+impl<I: State> Default for Any<I> {
+    fn default() -> Self {
+        Any::new()
+    }
+}
+
+impl<I: State> TerminationCriterion<I> for Any<I> {
+    fn terminate(&mut self, state: &I) -> TerminationReason {
+        self.criteria
+            .iter_mut()
+            .map(|criterion| criterion.terminate(state))
+            .fold(TerminationReason::NotTerminated, |acc, reason| {
+                if acc.terminated() {
+                    acc
+                } else {
+                    reason
+                }
+            })
+    }
+}
+
+/// Terminates when all of the contained criteria terminate in the same iteration.
+///
+/// All criteria are evaluated in every iteration. The reason of the first criterion is returned.
+/// An empty `All` never terminates.
+///
+/// # Example
+///
+/// ```
+/// use argmin::core::IterState;
+/// use argmin::core::termination_criteria::{All, CostChange, GradientNorm};
+///
+/// let criterion: All<IterState<Vec<f64>, Vec<f64>, (), (), f64>> = All::new()
+///     .add_criterion(CostChange::new(1e-6, 5))
+///     .add_criterion(GradientNorm::new(1e-8));
+/// ```
+pub struct All<I> {
+    /// Combined criteria
+    criteria: Vec<Box<dyn TerminationCriterion<I>>>,
+}
+
+impl<I: State> All<I> {
+    /// Constructs an empty `All` criterion which never terminates.
+    pub fn new() -> Self {
+        All { criteria: vec![] }
+    }
+
+    /// Adds a criterion.
+    #[must_use]
+    pub fn add_criterion<C: TerminationCriterion<I> + 'static>(mut self, criterion: C) -> Self {
+        self.criteria.push(Box::new(criterion));
+        self
+    }
+}
+
+impl<I: State> Default for All<I> {
+    fn default() -> Self {
+        All::new()
+    }
+}
+
+impl<I: State> TerminationCriterion<I> for All<I> {
+    fn terminate(&mut self, state: &I) -> TerminationReason {
+        let reasons: Vec<TerminationReason> = self
+            .criteria
+            .iter_mut()
+            .map(|criterion| criterion.terminate(state))
+            .collect();
+        if !reasons.is_empty() && reasons.iter().all(|reason| reason.terminated()) {
+            reasons.into_iter().next().unwrap()
+        } else {
+            TerminationReason::NotTerminated
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    type TState = IterState<Vec<f64>, Vec<f64>, (), (), f64>;
+
+    send_sync_test!(cost_change, CostChange<f64>);
+    send_sync_test!(param_change, ParamChange<Vec<f64>, f64>);
+    send_sync_test!(gradient_norm, GradientNorm<f64>);
+
+    #[test]
+    fn test_cost_change() {
+        let mut criterion = CostChange::new(0.1, 2);
+        let mut state: TState = IterState::new();
+
+        for (cost, expected) in [
+            (10.0, false),
+            (5.0, false),
+            (4.0, false),
+            (3.8, false),
+            (3.7, true),
+        ] {
+            state = state.cost(cost);
+            let reason = criterion.terminate(&state);
+            assert_eq!(reason.terminated(), expected);
+            if expected {
+                assert_eq!(reason, TerminationReason::NoChangeInCost);
+            }
+        }
+    }
+
+    #[test]
+    fn test_cost_change_infinite() {
+        // The initial cost of solvers without initialization is infinity
+        let mut criterion = CostChange::new(0.1, 2);
+        let mut state: TState = IterState::new();
+
+        for cost in [
+            f64::INFINITY,
+            100.0,
+            50.0,
+            f64::INFINITY,
+            f64::INFINITY,
+            f64::INFINITY,
+        ] {
+            state = state.cost(cost);
+            assert_eq!(
+                criterion.terminate(&state),
+                TerminationReason::NotTerminated
+            );
+        }
+    }
+
+    #[test]
+    fn test_param_change() {
+        let mut criterion = ParamChange::new(0.5);
+        let mut state: TState = IterState::new();
+
+        assert!(!criterion.terminate(&state).terminated());
+        state = state.param(vec![0.0, 0.0]);
+        assert!(!criterion.terminate(&state).terminated());
+        state = state.param(vec![1.0, 0.0]);
+        assert!(!criterion.terminate(&state).terminated());
+        state = state.param(vec![1.1, 0.1]);
+        assert_eq!(
+            criterion.terminate(&state),
+            TerminationReason::TargetToleranceReached
+        );
+    }
+
+    #[test]
+    fn test_gradient_norm() {
+        let mut criterion = GradientNorm::new(1e-3);
+        let mut state: TState = IterState::new();
+
+        assert!(!criterion.terminate(&state).terminated());
+        state = state.grad(vec![1.0, 0.0]);
+        assert!(!criterion.terminate(&state).terminated());
+        state = state.grad(vec![1e-4, 1e-4]);
+        assert_eq!(
+            criterion.terminate(&state),
+            TerminationReason::TargetPrecisionReached
+        );
+    }
+
+    #[test]
+    fn test_any_all() {
+        struct AfterIter(u64, &'static str);
+
+        impl TerminationCriterion<TState> for AfterIter {
+            fn terminate(&mut self, state: &TState) -> TerminationReason {
+                if state.get_iter() >= self.0 {
+                    TerminationReason::Custom(self.1.to_string())
+                } else {
+                    TerminationReason::NotTerminated
+                }
+            }
+        }
+
+        let mut any: Any<TState> = Any::new()
+            .add_criterion(AfterIter(3, "a"))
+            .add_criterion(AfterIter(2, "b"));
+        let mut all: All<TState> = All::new()
+            .add_criterion(AfterIter(3, "a"))
+            .add_criterion(AfterIter(2, "b"));
+        let mut empty_any: Any<TState> = Any::new();
+        let mut empty_all: All<TState> = All::new();
+        let mut state: TState = IterState::new();
+
+        for iter in 0..5 {
+            let any_reason = any.terminate(&state);
+            let all_reason = all.terminate(&state);
+            match iter {
+                0 | 1 => {
+                    assert!(!any_reason.terminated());
+                    assert!(!all_reason.terminated());
+                }
+                2 => {
+                    assert_eq!(any_reason, TerminationReason::Custom("b".to_string()));
+                    assert!(!all_reason.terminated());
+                }
+                _ => {
+                    assert_eq!(any_reason, TerminationReason::Custom("a".to_string()));
+                    assert_eq!(all_reason, TerminationReason::Custom("a".to_string()));
+                }
+            }
+            assert!(!empty_any.terminate(&state).terminated());
+            assert!(!empty_all.terminate(&state).terminated());
+            state.increment_iter();
+        }
+    }
+}
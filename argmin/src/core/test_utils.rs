@@ -5,13 +5,22 @@
 // http://opensource.org/licenses/MIT>, at your option. This file may not be
 // copied, modified, or distributed except according to those terms.
 
+#[cfg(feature = "serde1")]
+use crate::core::{
+    checkpointing::{CheckpointingFrequency, MemoryCheckpoint},
+    DeserializeOwnedAlias, Executor, SerializeAlias, State,
+};
 use crate::core::{
     CostFunction, Error, Gradient, Hessian, IterState, Jacobian, Operator, Problem, Solver, KV,
 };
 use crate::solver::simulatedannealing::Anneal;
 #[cfg(feature = "serde1")]
+use num_traits::Float;
+#[cfg(feature = "serde1")]
 use serde::{Deserialize, Serialize};
 use std::fmt::Debug;
+use std::sync::atomic::{AtomicU64, Ordering};
+use std::sync::Arc;
 
 /// Pseudo problem useful for testing
 ///
@@ -207,6 +216,166 @@ impl Anneal for TestProblem {
     }
 }
 
+/// Two-dimensional Rosenbrock function (`a = 1`, `b = 100`) useful for testing
+///
+/// Implements [`CostFunction`] and [`Gradient`], including a combined
+/// [`cost_and_gradient`](`Gradient::cost_and_gradient`). Counts how often each of these methods
+/// is called. Clones share their counters, therefore the counts can still be read after the
+/// problem was moved into an [`Executor`](`crate::core::Executor`).
+#[derive(Clone, Default, Debug)]
+pub struct Rosenbrock {
+    cost_count: Arc<AtomicU64>,
+    gradient_count: Arc<AtomicU64>,
+    cost_and_gradient_count: Arc<AtomicU64>,
+}
+
+impl Rosenbrock {
+    /// Create an instance of `Rosenbrock`.
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// use argmin::core::test_utils::Rosenbrock;
+    ///
+    /// let problem = Rosenbrock::new();
+    /// # assert_eq!(problem.cost_count(), 0);
+    /// ```
+    pub fn new() -> Self {
+        Rosenbrock::default()
+    }
+
+    /// Returns how often [`cost`](`CostFunction::cost`) was called.
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// use argmin::core::test_utils::Rosenbrock;
+    /// use argmin::core::CostFunction;
+    /// # use argmin::core::Error;
+    ///
+    /// # fn main() -> Result<(), Error> {
+    /// let problem = Rosenbrock::new();
+    /// let counter = problem.clone();
+    ///
+    /// problem.cost(&vec![1.0, 1.0])?;
+    /// assert_eq!(counter.cost_count(), 1);
+    /// # Ok(())
+    /// # }
+    /// ```
+    pub fn cost_count(&self) -> u64 {
+        self.cost_count.load(Ordering::SeqCst)
+    }
+
+    /// Returns how often [`gradient`](`Gradient::gradient`) was called.
+    pub fn gradient_count(&self) -> u64 {
+        self.gradient_count.load(Ordering::SeqCst)
+    }
+
+    /// Returns how often [`cost_and_gradient`](`Gradient::cost_and_gradient`) was called.
+    ///
+    /// Calls of `cost_and_gradient` do not count as calls of `cost` or `gradient`.
+    pub fn cost_and_gradient_count(&self) -> u64 {
+        self.cost_and_gradient_count.load(Ordering::SeqCst)
+    }
+
+    fn rosenbrock(p: &[f64]) -> f64 {
+        (1.0 - p[0]).powi(2) + 100.0 * (p[1] - p[0].powi(2)).powi(2)
+    }
+
+    fn rosenbrock_derivative(p: &[f64]) -> Vec<f64> {
+        let (x, y) = (p[0], p[1]);
+        vec![
+            -2.0 + 400.0 * x.powi(3) - 400.0 * x * y + 2.0 * x,
+            200.0 * (y - x.powi(2)),
+        ]
+    }
+}
+
+impl CostFunction for Rosenbrock {
+    type Param = Vec<f64>;
+    type Output = f64;
+
+    /// Returns the Rosenbrock function value at `p`.
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// use argmin::core::test_utils::Rosenbrock;
+    /// use argmin::core::CostFunction;
+    /// # use argmin::core::Error;
+    ///
+    /// # fn main() -> Result<(), Error> {
+    /// let problem = Rosenbrock::new();
+    ///
+    /// let res = problem.cost(&vec![1.0, 1.0])?;
+    /// # assert_eq!(res, 0.0);
+    /// # Ok(())
+    /// # }
+    /// ```
+    fn cost(&self, p: &Self::Param) -> Result<Self::Output, Error> {
+        self.cost_count.fetch_add(1, Ordering::SeqCst);
+        Ok(Rosenbrock::rosenbrock(p))
+    }
+}
+
+impl Gradient for Rosenbrock {
+    type Param = Vec<f64>;
+    type Gradient = Vec<f64>;
+
+    /// Returns the gradient of the Rosenbrock function at `p`.
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// use argmin::core::test_utils::Rosenbrock;
+    /// use argmin::core::Gradient;
+    /// # use argmin::core::Error;
+    ///
+    /// # fn main() -> Result<(), Error> {
+    /// let problem = Rosenbrock::new();
+    ///
+    /// let res = problem.gradient(&vec![1.0, 1.0])?;
+    /// # assert_eq!(res, vec![0.0, 0.0]);
+    /// # Ok(())
+    /// # }
+    /// ```
+    fn gradient(&self, p: &Self::Param) -> Result<Self::Gradient, Error> {
+        self.gradient_count.fetch_add(1, Ordering::SeqCst);
+        Ok(Rosenbrock::rosenbrock_derivative(p))
+    }
+
+    /// Returns the Rosenbrock function value and gradient at `p`.
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// use argmin::core::test_utils::Rosenbrock;
+    /// use argmin::core::Gradient;
+    /// # use argmin::core::Error;
+    ///
+    /// # fn main() -> Result<(), Error> {
+    /// let problem = Rosenbrock::new();
+    ///
+    /// let (cost, grad) = problem.cost_and_gradient(&vec![1.0, 1.0])?;
+    /// # assert_eq!(cost, 0.0);
+    /// # assert_eq!(grad, vec![0.0, 0.0]);
+    /// # assert_eq!(problem.cost_and_gradient_count(), 1);
+    /// # assert_eq!(problem.cost_count(), 0);
+    /// # Ok(())
+    /// # }
+    /// ```
+    fn cost_and_gradient(
+        &self,
+        p: &Self::Param,
+    ) -> Result<(<Self as CostFunction>::Output, Self::Gradient), Error> {
+        self.cost_and_gradient_count.fetch_add(1, Ordering::SeqCst);
+        Ok((
+            Rosenbrock::rosenbrock(p),
+            Rosenbrock::rosenbrock_derivative(p),
+        ))
+    }
+}
+
 /// A (non-working) solver useful for testing
 ///
 /// Implements the [`Solver`] trait.
@@ -241,3 +410,136 @@ impl<O> Solver<O, IterState<Vec<f64>, (), (), (), f64>> for TestSolver {
         Ok((state, None))
     }
 }
+
+/// Verifies that an optimization which is interrupted and resumed from a checkpoint ends in the
+/// same final state as an uninterrupted optimization.
+///
+/// The optimization of `problem` with `solver` is run three times, each time with the initial
+/// state configured by `configure`:
+///
+/// 1. without interruption,
+/// 2. interrupted after `interrupt` iterations while saving a
+///    [`MemoryCheckpoint`](`crate::core::checkpointing::MemoryCheckpoint`) in every iteration,
+/// 3. resumed from this checkpoint with fresh instances of the problem and the solver.
+///
+/// The final states of the first and the last run are compared with respect to the iteration
+/// number, the current and best parameter vectors, the current and best cost function values,
+/// the iteration in which the best parameter vector was found and the termination reason. The
+/// function evaluation counts are not compared because they are not part of the checkpoint.
+/// Timing is disabled in all runs.
+///
+/// Returns an error describing the first difference found.
+///
+/// # Example
+///
+/// ```
+/// use argmin::core::test_utils::{check_checkpoint_resume, TestProblem, TestSolver};
+/// # use argmin::core::Error;
+///
+/// # fn main() -> Result<(), Error> {
+/// check_checkpoint_resume(
+///     TestProblem::new(),
+///     TestSolver::new(),
+///     |state| state.param(vec![1.0f64, 0.0]).max_iters(10),
+///     4,
+/// )?;
+/// # Ok(())
+/// # }
+/// ```
+#[cfg(feature = "serde1")]
+pub fn check_checkpoint_resume<O, S, I, F>(
+    problem: O,
+    solver: S,
+    configure: F,
+    interrupt: u64,
+) -> Result<(), Error>
+where
+    O: Clone,
+    S: Solver<O, I> + Clone + SerializeAlias + DeserializeOwnedAlias + 'static,
+    I: State + SerializeAlias + DeserializeOwnedAlias + 'static,
+    I::Param: Clone + PartialEq + Debug,
+    F: Fn(I) -> I,
+{
+    let expected = Executor::new(problem.clone(), solver.clone())
+        .configure(&configure)
+        .timer(false)
+        .run()?;
+
+    let checkpoint = MemoryCheckpoint::new(CheckpointingFrequency::Always);
+    let mut stepper = Executor::new(problem.clone(), solver.clone())
+        .configure(&configure)
+        .timer(false)
+        .checkpointing(checkpoint.clone())
+        .stepper()?;
+    for _ in 0..interrupt {
+        if !stepper.step()? {
+            break;
+        }
+    }
+    drop(stepper);
+
+    let resumed = Executor::new(problem, solver)
+        .configure(&configure)
+        .timer(false)
+        .checkpointing(checkpoint)
+        .run()?;
+
+    let (expected, resumed) = (expected.state(), resumed.state());
+    let same_float = |a: I::Float, b: I::Float| a == b || (Float::is_nan(a) && Float::is_nan(b));
+    macro_rules! compare {
+        ($name:expr, $equal:expr, $a:expr, $b:expr) => {
+            if !$equal {
+                return Err(argmin_error!(
+                    PotentialBug,
+                    format!(
+                        "Resumed optimization differs in {}: expected {:?}, got {:?}",
+                        $name, $a, $b
+                    )
+                ));
+            }
+        };
+    }
+    compare!(
+        "iteration number",
+        expected.get_iter() == resumed.get_iter(),
+        expected.get_iter(),
+        resumed.get_iter()
+    );
+    compare!(
+        "parameter vector",
+        expected.get_param() == resumed.get_param(),
+        expected.get_param(),
+        resumed.get_param()
+    );
+    compare!(
+        "best parameter vector",
+        expected.get_best_param() == resumed.get_best_param(),
+        expected.get_best_param(),
+        resumed.get_best_param()
+    );
+    compare!(
+        "cost function value",
+        same_float(expected.get_cost(), resumed.get_cost()),
+        expected.get_cost(),
+        resumed.get_cost()
+    );
+    compare!(
+        "best cost function value",
+        same_float(expected.get_best_cost(), resumed.get_best_cost()),
+        expected.get_best_cost(),
+        resumed.get_best_cost()
+    );
+    compare!(
+        "iteration of the best parameter vector",
+        expected.get_last_best_iter() == resumed.get_last_best_iter(),
+        expected.get_last_best_iter(),
+        resumed.get_last_best_iter()
+    );
+    compare!(
+        "termination reason",
+        expected.get_termination_reason() == resumed.get_termination_reason(),
+        expected.get_termination_reason(),
+        resumed.get_termination_reason()
+    );
+    Ok(())
+}
@@ -132,6 +132,16 @@
 #![doc = concat!(" argmin = { version = \"", env!("CARGO_PKG_VERSION"), "\", features = [\"ctrlc\"] }")]
 //! ```
 //!
+//! The `rayon` feature enables parallel evaluation of the cost function for multiple parameter
+//! vectors via [`bulk_cost`](`crate::core::CostFunction::bulk_cost`), which is used by
+//! population-based solvers such as Particle Swarm Optimization and Nelder-Mead. This requires
+//! problems, parameter vectors and cost function values to be `Send` and `Sync`.
+//!
+//! ```toml
+//! [dependencies]
+#![doc = concat!(" argmin = { version = \"", env!("CARGO_PKG_VERSION"), "\", features = [\"rayon\"] }")]
+//! ```
+//!
 //! ### Experimental support for compiling to WebAssembly
 //!
 //! When compiling to WASM, the feature `wasm-bindgen` must be used.
@@ -447,6 +457,8 @@
 //! method `checkpoint_mode` of `Executor`.
 //! In addition, the directory where the checkpoints and a prefix for every file can be set via
 //! `checkpoint_dir` and `checkpoint_name`, respectively.
+//! `FileCheckpoint` writes each checkpoint atomically to a file named after the iteration, keeps
+//! the newest three and, when loading, skips checkpoints whose checksum does not match.
 //!
 //! The following example shows how the `from_checkpoint` method can be used to resume from a
 //! checkpoint. In case this fails (for instance because the file does not exist, which could mean
@@ -6,8 +6,9 @@
 // copied, modified, or distributed except according to those terms.
 
 use crate::core::{
-    ArgminFloat, CostFunction, DeserializeOwnedAlias, Error, Executor, Gradient, IterState,
-    LineSearch, NLCGBetaUpdate, OptimizationResult, Problem, SerializeAlias, Solver, State, KV,
+    ArgminFloat, CostFunction, CostGradient, DeserializeOwnedAlias, Error, Executor, Gradient,
+    IterState, LineSearch, NLCGBetaUpdate, OptimizationResult, Problem, SerializeAlias, Solver,
+    State, KV,
 };
 use argmin_math::{ArgminAdd, ArgminDot, ArgminMul, ArgminNorm};
 #[cfg(feature = "serde1")]
@@ -119,7 +120,7 @@ where
 impl<O, P, G, L, B, F> Solver<O, IterState<P, G, (), (), F>>
     for NonlinearConjugateGradient<P, L, B, F>
 where
-    O: CostFunction<Param = P, Output = F> + Gradient<Param = P, Gradient = G>,
+    O: CostGradient + CostFunction<Param = P, Output = F> + Gradient<Param = P, Gradient = G>,
     P: Clone + SerializeAlias + DeserializeOwnedAlias + ArgminAdd<P, P> + ArgminMul<F, P>,
     G: Clone
         + SerializeAlias
@@ -145,8 +146,7 @@ where
                 "Please provide an initial guess via `Executor`s `configure` method."
             )
         ))?;
-        let cost = problem.cost(param)?;
-        let grad = problem.gradient(param)?;
+        let (cost, grad) = problem.cost_and_gradient(param)?;
         self.p = Some(grad.mul(&(F::from_f64(-1.0).unwrap())));
         Ok((state.cost(cost).grad(grad), None))
     }
@@ -197,9 +197,9 @@ where
             "`NonlinearConjugateGradient`: No `param` returned by line search"
         ))?;
 
-        // Update of beta
-        let new_grad = problem.gradient(&xk1)?;
+        let (cost, new_grad) = problem.cost_and_gradient(&xk1)?;
 
+        // Update of beta
         let restart_orthogonality = match self.restart_orthogonality {
             Some(v) => new_grad.dot(&grad).abs() / new_grad.norm().powi(2) >= v,
             None => false,
@@ -221,9 +221,6 @@ where
                 .add(&p.mul(&self.beta)),
         );
 
-        // Housekeeping
-        let cost = problem.cost(&xk1)?;
-
         Ok((
             state.param(xk1).cost(cost).grad(new_grad),
             Some(make_kv!("beta" => self.beta;
@@ -452,11 +449,9 @@ mod tests {
         assert!(kv.is_none());
         let (mut state, kv) = nlcg.next_iter(&mut problem, state).unwrap();
         state.update();
-        let kv2 = make_kv!("beta" => 0; "restart_iter" => false; "restart_orthogonality" => false;);
-        for ((k1, v1), (k2, v2)) in kv.unwrap().kv.iter().zip(kv2.kv.iter()) {
-            assert_eq!(k1, k2);
-            assert_eq!(format!("{}", v1), format!("{}", v2));
-        }
+        let kv2 =
+            make_kv!("beta" => 0.0; "restart_iter" => false; "restart_orthogonality" => false;);
+        assert_eq!(kv.unwrap(), kv2);
         assert_relative_eq!(
             state.param.as_ref().unwrap()[0],
             1.0f64,
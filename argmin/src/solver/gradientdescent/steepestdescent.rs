@@ -6,8 +6,8 @@
 // copied, modified, or distributed except according to those terms.
 
 use crate::core::{
-    ArgminFloat, CostFunction, DeserializeOwnedAlias, Error, Executor, Gradient, IterState,
-    LineSearch, OptimizationResult, Problem, SerializeAlias, Solver, KV,
+    ArgminFloat, CostFunction, CostGradient, DeserializeOwnedAlias, Error, Executor, Gradient,
+    IterState, LineSearch, OptimizationResult, Problem, SerializeAlias, Solver, KV,
 };
 use argmin_math::ArgminMul;
 #[cfg(feature = "serde1")]
@@ -48,7 +48,7 @@ impl<L> SteepestDescent<L> {
 
 impl<O, L, P, G, F> Solver<O, IterState<P, G, (), (), F>> for SteepestDescent<L>
 where
-    O: CostFunction<Param = P, Output = F> + Gradient<Param = P, Gradient = G>,
+    O: CostGradient + CostFunction<Param = P, Output = F> + Gradient<Param = P, Gradient = G>,
     P: Clone + SerializeAlias + DeserializeOwnedAlias,
     G: Clone + SerializeAlias + DeserializeOwnedAlias + ArgminMul<F, P>,
     L: Clone + LineSearch<P, F> + Solver<O, IterState<P, G, (), (), F>>,
@@ -68,8 +68,7 @@ where
                 "Please provide an initial guess via `Executor`s `configure` method."
             )
         ))?;
-        let new_cost = problem.cost(&param_new)?;
-        let new_grad = problem.gradient(&param_new)?;
+        let (new_cost, new_grad) = problem.cost_and_gradient(&param_new)?;
 
         self.linesearch
             .set_search_direction(new_grad.mul(&(F::from_f64(-1.0).unwrap())));
@@ -8,8 +8,8 @@
 //! * [Backtracking line search](struct.BacktrackingLineSearch.html)
 
 use crate::core::{
-    ArgminFloat, CostFunction, Error, Gradient, IterState, LineSearch, Problem, SerializeAlias,
-    Solver, State, TerminationReason, KV,
+    ArgminFloat, CostFunction, CostGradient, Error, Gradient, IterState, LineSearch, Problem,
+    SerializeAlias, Solver, State, TerminationReason, KV,
 };
 use crate::solver::linesearch::condition::*;
 use argmin_math::ArgminScaledAdd;
@@ -94,6 +94,11 @@ where
         self.alpha = alpha;
         Ok(())
     }
+
+    /// The gradient is only stored together with the parameter vector it was computed at
+    fn gradient_at_param(&self) -> bool {
+        true
+    }
 }
 
 impl<P, G, L, F> BacktrackingLineSearch<P, G, L, F>
@@ -109,7 +114,7 @@ where
         state: IterState<P, G, (), (), F>,
     ) -> Result<IterState<P, G, (), (), F>, Error>
     where
-        O: CostFunction<Param = P, Output = F> + Gradient<Param = P, Gradient = G>,
+        O: CostGradient + CostFunction<Param = P, Output = F> + Gradient<Param = P, Gradient = G>,
     {
         let new_param = self
             .init_param
@@ -117,14 +122,11 @@ where
             .unwrap()
             .scaled_add(&self.alpha, self.search_direction.as_ref().unwrap());
 
-        let cur_cost = problem.cost(&new_param)?;
-
         let out = if self.condition.requires_cur_grad() {
-            state
-                .grad(problem.gradient(&new_param)?)
-                .param(new_param)
-                .cost(cur_cost)
+            let (cur_cost, cur_grad) = problem.cost_and_gradient(&new_param)?;
+            state.grad(cur_grad).param(new_param).cost(cur_cost)
         } else {
+            let cur_cost = problem.cost(&new_param)?;
             state.param(new_param).cost(cur_cost)
         };
 
@@ -136,7 +138,7 @@ impl<O, P, G, L, F> Solver<O, IterState<P, G, (), (), F>> for BacktrackingLineSe
 where
     P: Clone + SerializeAlias + ArgminScaledAdd<P, F, P>,
     G: SerializeAlias + ArgminScaledAdd<P, F, P>,
-    O: CostFunction<Param = P, Output = F> + Gradient<Param = P, Gradient = G>,
+    O: CostGradient + CostFunction<Param = P, Output = F> + Gradient<Param = P, Gradient = G>,
     L: LineSearchCondition<P, G, F>,
     F: ArgminFloat,
 {
@@ -149,16 +151,14 @@ where
     ) -> Result<(IterState<P, G, (), (), F>, Option<KV>), Error> {
         let init_param = state.param.clone().unwrap();
         let cost = state.cost;
-        self.init_cost = if cost == F::infinity() {
-            problem.cost(&init_param)?
-        } else {
-            cost
+        // Evaluate cost function and gradient together if neither is known
+        let (init_cost, init_grad) = match (cost == F::infinity(), state.take_grad()) {
+            (true, None) => problem.cost_and_gradient(&init_param)?,
+            (true, Some(grad)) => (problem.cost(&init_param)?, grad),
+            (false, None) => (cost, problem.gradient(&init_param)?),
+            (false, Some(grad)) => (cost, grad),
         };
-
-        let init_grad = state
-            .take_grad()
-            .map(Result::Ok)
-            .unwrap_or_else(|| problem.gradient(&init_param))?;
+        self.init_cost = init_cost;
 
         if self.search_direction.is_none() {
             return Err(argmin_error!(
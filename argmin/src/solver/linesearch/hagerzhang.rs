@@ -14,8 +14,8 @@
 //! DOI: <https://doi.org/10.1137/030601880>
 
 use crate::core::{
-    ArgminFloat, CostFunction, Error, Gradient, IterState, LineSearch, Problem, SerializeAlias,
-    Solver, TerminationReason, KV,
+    ArgminFloat, CostFunction, CostGradient, Error, Gradient, IterState, LineSearch, Problem,
+    SerializeAlias, Solver, TerminationReason, KV,
 };
 use argmin_math::{ArgminDot, ArgminScaledAdd};
 #[cfg(feature = "serde1")]
@@ -283,8 +283,7 @@ where
             let mut bh_x = c_x;
             loop {
                 let d_x = (F::from_f64(1.0).unwrap() - self.theta) * ah_x + self.theta * bh_x;
-                let d_f = self.calc(problem, d_x)?;
-                let d_g = self.calc_grad(problem, d_x)?;
+                let (d_f, d_g) = self.calc(problem, d_x)?;
                 if d_g >= F::from_f64(0.0).unwrap() {
                     return Ok(((ah_x, ah_f, ah_g), (d_x, d_f, d_g)));
                 }
@@ -323,8 +322,7 @@ where
     {
         // S1
         let c_x = self.secant(a_x, a_g, b_x, b_g);
-        let c_f = self.calc(problem, c_x)?;
-        let c_g = self.calc_grad(problem, c_x)?;
+        let (c_f, c_g) = self.calc(problem, c_x)?;
         let mut c_bar_x: F = F::from_f64(0.0).unwrap();
 
         let ((aa_x, aa_f, aa_g), (bb_x, bb_f, bb_g)) =
@@ -342,8 +340,7 @@ where
 
         // S4
         if (c_x - aa_x).abs() < F::epsilon() || (c_x - bb_x).abs() < F::epsilon() {
-            let c_bar_f = self.calc(problem, c_bar_x)?;
-            let c_bar_g = self.calc_grad(problem, c_bar_x)?;
+            let (c_bar_f, c_bar_g) = self.calc(problem, c_bar_x)?;
 
             let (a_bar, b_bar) = self.update(
                 problem,
@@ -357,29 +354,18 @@ where
         }
     }
 
-    fn calc<O>(&mut self, problem: &mut Problem<O>, alpha: F) -> Result<F, Error>
+    /// Computes the cost function value and the directional derivative at step length `alpha`.
+    fn calc<O>(&mut self, problem: &mut Problem<O>, alpha: F) -> Result<(F, F), Error>
     where
-        O: CostFunction<Param = P, Output = F> + Gradient<Param = P, Gradient = G>,
-    {
-        let tmp = self
-            .init_param
-            .as_ref()
-            .unwrap()
-            .scaled_add(&alpha, self.search_direction.as_ref().unwrap());
-        problem.cost(&tmp)
-    }
-
-    fn calc_grad<O>(&mut self, problem: &mut Problem<O>, alpha: F) -> Result<F, Error>
-    where
-        O: CostFunction<Param = P, Output = F> + Gradient<Param = P, Gradient = G>,
+        O: CostGradient + CostFunction<Param = P, Output = F> + Gradient<Param = P, Gradient = G>,
     {
         let tmp = self
             .init_param
             .as_ref()
             .unwrap()
             .scaled_add(&alpha, self.search_direction.as_ref().unwrap());
-        let grad = problem.gradient(&tmp)?;
-        Ok(self.search_direction.as_ref().unwrap().dot(&grad))
+        let (cost, grad) = problem.cost_and_gradient(&tmp)?;
+        Ok((cost, self.search_direction.as_ref().unwrap().dot(&grad)))
     }
 
     fn set_best(&mut self) {
@@ -427,7 +413,7 @@ impl<P, G, F> LineSearch<P, F> for HagerZhangLineSearch<P, G, F> {
 
 impl<P, G, O, F> Solver<O, IterState<P, G, (), (), F>> for HagerZhangLineSearch<P, G, F>
 where
-    O: CostFunction<Param = P, Output = F> + Gradient<Param = P, Gradient = G>,
+    O: CostGradient + CostFunction<Param = P, Output = F> + Gradient<Param = P, Gradient = G>,
     P: Clone + SerializeAlias + ArgminDot<G, F> + ArgminScaledAdd<P, F, P>,
     G: Clone + SerializeAlias + ArgminDot<P, F>,
     F: ArgminFloat,
@@ -454,32 +440,27 @@ where
         self.init_param = state.param.clone();
 
         let cost = state.cost;
-        self.finit = if cost.is_infinite() {
-            problem.cost(self.init_param.as_ref().unwrap())?
-        } else {
-            cost
+        let init_param = self.init_param.as_ref().unwrap();
+        // Evaluate cost function and gradient together if neither is known
+        let (finit, init_grad) = match (cost.is_infinite(), state.take_grad()) {
+            (true, None) => problem.cost_and_gradient(init_param)?,
+            (true, Some(grad)) => (problem.cost(init_param)?, grad),
+            (false, None) => (cost, problem.gradient(init_param)?),
+            (false, Some(grad)) => (cost, grad),
         };
-
-        self.init_grad = Some(
-            state
-                .take_grad()
-                .map(Result::Ok)
-                .unwrap_or_else(|| problem.gradient(self.init_param.as_ref().unwrap()))?,
-        );
+        self.finit = finit;
+        self.init_grad = Some(init_grad);
 
         self.a_x = self.a_x_init;
         self.b_x = self.b_x_init;
         self.c_x = self.c_x_init;
 
         let at = self.a_x;
-        self.a_f = self.calc(problem, at)?;
-        self.a_g = self.calc_grad(problem, at)?;
+        (self.a_f, self.a_g) = self.calc(problem, at)?;
         let bt = self.b_x;
-        self.b_f = self.calc(problem, bt)?;
-        self.b_g = self.calc_grad(problem, bt)?;
+        (self.b_f, self.b_g) = self.calc(problem, bt)?;
         let ct = self.c_x;
-        self.c_f = self.calc(problem, ct)?;
-        self.c_g = self.calc_grad(problem, ct)?;
+        (self.c_f, self.c_g) = self.calc(problem, ct)?;
 
         self.epsilon_k = self.epsilon * self.finit.abs();
 
@@ -519,8 +500,7 @@ where
                 .as_ref()
                 .unwrap()
                 .scaled_add(&c_x, self.search_direction.as_ref().unwrap());
-            let c_f = problem.cost(&tmp)?;
-            let grad = problem.gradient(&tmp)?;
+            let (c_f, grad) = problem.cost_and_gradient(&tmp)?;
             let c_g = self.search_direction.as_ref().unwrap().dot(&grad);
             let ((an_x, an_f, an_g), (bn_x, bn_f, bn_g)) = self.update(
                 problem,
@@ -47,4 +47,15 @@ pub trait LineSearch<P, F> {
 
     /// Set the initial step length
     fn set_init_alpha(&mut self, step_length: F) -> Result<(), Error>;
+
+    /// Indicates whether a gradient in the state returned by the line search always belongs to
+    /// the returned parameter vector.
+    ///
+    /// If this returns `true`, solvers such as [`LBFGS`](`crate::solver::quasinewton::LBFGS`)
+    /// reuse this gradient instead of evaluating the gradient again. A line search which returns
+    /// `true` must therefore never leave a gradient of a different parameter vector (for instance
+    /// of an earlier trial point) in the returned state. Defaults to `false`.
+    fn gradient_at_param(&self) -> bool {
+        false
+    }
 }
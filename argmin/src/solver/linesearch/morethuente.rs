@@ -22,8 +22,8 @@
 #![allow(clippy::nonminimal_bool)]
 
 use crate::core::{
-    ArgminFloat, CostFunction, Error, Gradient, IterState, LineSearch, Problem, SerializeAlias,
-    Solver, State, TerminationReason, KV,
+    ArgminFloat, CostFunction, CostGradient, Error, Gradient, IterState, LineSearch, Problem,
+    SerializeAlias, Solver, State, TerminationReason, KV,
 };
 use argmin_math::{ArgminDot, ArgminScaledAdd};
 #[cfg(feature = "serde1")]
@@ -217,11 +217,16 @@ where
         self.alpha = alpha;
         Ok(())
     }
+
+    /// The gradient is only stored together with the accepted parameter vector
+    fn gradient_at_param(&self) -> bool {
+        true
+    }
 }
 
 impl<P, G, O, F> Solver<O, IterState<P, G, (), (), F>> for MoreThuenteLineSearch<P, G, F>
 where
-    O: CostFunction<Param = P, Output = F> + Gradient<Param = P, Gradient = G>,
+    O: CostGradient + CostFunction<Param = P, Output = F> + Gradient<Param = P, Gradient = G>,
     P: Clone + SerializeAlias + ArgminDot<G, F> + ArgminScaledAdd<P, F, P>,
     G: Clone + SerializeAlias + ArgminDot<P, F>,
     F: ArgminFloat,
@@ -241,18 +246,16 @@ where
         self.init_param = state.param.clone();
 
         let cost = state.cost;
-        self.finit = if cost.is_infinite() {
-            problem.cost(self.init_param.as_ref().unwrap())?
-        } else {
-            cost
+        let init_param = self.init_param.as_ref().unwrap();
+        // Evaluate cost function and gradient together if neither is known
+        let (finit, init_grad) = match (cost.is_infinite(), state.take_grad()) {
+            (true, None) => problem.cost_and_gradient(init_param)?,
+            (true, Some(grad)) => (problem.cost(init_param)?, grad),
+            (false, None) => (cost, problem.gradient(init_param)?),
+            (false, Some(grad)) => (cost, grad),
         };
-
-        self.init_grad = Some(
-            state
-                .take_grad()
-                .map(Result::Ok)
-                .unwrap_or_else(|| problem.gradient(self.init_param.as_ref().unwrap()))?,
-        );
+        self.finit = finit;
+        self.init_grad = Some(init_grad);
 
         self.dginit = self
             .init_grad
@@ -318,8 +321,8 @@ where
             .as_ref()
             .unwrap()
             .scaled_add(&self.stp.x, self.search_direction.as_ref().unwrap());
-        self.f = problem.cost(&new_param)?;
-        let new_grad = problem.gradient(&new_param)?;
+        let (f, new_grad) = problem.cost_and_gradient(&new_param)?;
+        self.f = f;
         let cur_cost = self.f;
         let cur_param = new_param;
         let cur_grad = new_grad.clone();
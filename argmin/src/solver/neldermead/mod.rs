@@ -12,8 +12,8 @@
 //! [Wikipedia](https://en.wikipedia.org/wiki/Nelder%E2%80%93Mead_method)
 
 use crate::core::{
-    ArgminFloat, CostFunction, Error, IterState, Problem, SerializeAlias, Solver,
-    TerminationReason, KV,
+    ArgminFloat, CostFunction, Error, IterState, Problem, SendAlias, SerializeAlias, Solver,
+    SyncAlias, TerminationReason, KV,
 };
 use argmin_math::{ArgminAdd, ArgminMul, ArgminSub};
 #[cfg(feature = "serde1")]
@@ -169,21 +169,19 @@ where
     }
 
     /// Shrink
-    fn shrink<S>(&mut self, mut cost: S) -> Result<(), Error>
+    fn shrink<S>(&mut self, bulk_cost: S) -> Result<(), Error>
     where
-        S: FnMut(&P) -> Result<F, Error>,
+        S: FnOnce(&[P]) -> Result<Vec<F>, Error>,
     {
-        let mut out = Vec::with_capacity(self.params.len());
-        out.push(self.params[0].clone());
-
-        for idx in 1..self.params.len() {
-            let xi = out[0]
-                .0
-                .add(&self.params[idx].0.sub(&out[0].0).mul(&self.sigma));
-            let ci = (cost)(&xi)?;
-            out.push((xi, ci));
-        }
-        self.params = out;
+        let x0 = &self.params[0].0;
+        let xs: Vec<P> = self.params[1..]
+            .iter()
+            .map(|(x, _)| x0.add(&x.sub(x0).mul(&self.sigma)))
+            .collect();
+        let costs = (bulk_cost)(&xs)?;
+
+        self.params.truncate(1);
+        self.params.extend(xs.into_iter().zip(costs));
         Ok(())
     }
 }
@@ -219,9 +217,9 @@ impl fmt::Display for Action {
 
 impl<O, P, F> Solver<O, IterState<P, (), (), (), F>> for NelderMead<P, F>
 where
-    O: CostFunction<Param = P, Output = F>,
-    P: Clone + SerializeAlias + ArgminSub<P, P> + ArgminAdd<P, P> + ArgminMul<F, P>,
-    F: ArgminFloat + std::iter::Sum<F>,
+    O: CostFunction<Param = P, Output = F> + SyncAlias,
+    P: Clone + SerializeAlias + SyncAlias + ArgminSub<P, P> + ArgminAdd<P, P> + ArgminMul<F, P>,
+    F: ArgminFloat + SendAlias + std::iter::Sum<F>,
 {
     const NAME: &'static str = "Nelder-Mead method";
 
@@ -230,15 +228,9 @@ where
         problem: &mut Problem<O>,
         state: IterState<P, (), (), (), F>,
     ) -> Result<(IterState<P, (), (), (), F>, Option<KV>), Error> {
-        self.params = self
-            .params
-            .iter()
-            .cloned()
-            .map(|(p, _)| {
-                let c = problem.cost(&p).unwrap();
-                (p, c)
-            })
-            .collect();
+        let params: Vec<P> = self.params.drain(..).map(|(p, _)| p).collect();
+        let costs = problem.bulk_cost(&params)?;
+        self.params = params.into_iter().zip(costs).collect();
         self.sort_param_vecs();
 
         Ok((
@@ -287,7 +279,7 @@ where
             Action::Contraction
         } else {
             // shrink
-            self.shrink(|x| problem.cost(x))?;
+            self.shrink(|xs| problem.bulk_cost(xs))?;
             Action::Shrink
         };
 
@@ -295,7 +287,7 @@ where
 
         Ok((
             state.param(self.params[0].0.clone()).cost(self.params[0].1),
-            Some(make_kv!("action" => action;)),
+            Some(make_kv!("action" => action.to_string();)),
         ))
     }
 
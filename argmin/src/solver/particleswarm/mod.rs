@@ -12,7 +12,8 @@
 //! TODO
 
 use crate::core::{
-    ArgminFloat, CostFunction, Error, IterState, Problem, SerializeAlias, Solver, KV,
+    ArgminFloat, CostFunction, Error, IterState, Problem, SendAlias, SerializeAlias, Solver,
+    SyncAlias, KV,
 };
 use argmin_math::{ArgminAdd, ArgminMinMax, ArgminMul, ArgminRandom, ArgminSub, ArgminZeroLike};
 #[cfg(feature = "serde1")]
@@ -20,6 +21,12 @@ use serde::{Deserialize, Serialize};
 
 /// Particle Swarm Optimization (PSO)
 ///
+/// In each iteration, all particles are moved first and the cost function is then evaluated at
+/// all new positions at once via [`Problem::bulk_cost`] (in parallel if the `rayon` feature is
+/// enabled and the problem allows it). The global best position is therefore updated
+/// synchronously: all particles are moved towards the global best position of the previous
+/// iteration, even if another particle found a better position earlier in the same iteration.
+///
 /// # References:
 ///
 /// TODO
@@ -78,40 +85,40 @@ where
         Ok(particle_swarm)
     }
 
-    fn initialize_particles<O: CostFunction<Param = P, Output = F>>(
-        &mut self,
-        problem: &mut Problem<O>,
-    ) {
-        self.particles = (0..self.num_particles)
-            .map(|_| self.initialize_particle(problem))
-            .collect();
-
-        self.best_position = self.get_best_position();
-        self.best_cost = problem.cost(&self.best_position).unwrap();
-        // TODO unwrap evil
-    }
-
-    fn initialize_particle<O: CostFunction<Param = P, Output = F>>(
-        &mut self,
-        problem: &mut Problem<O>,
-    ) -> Particle<P, F> {
+    fn initialize_particles<O>(&mut self, problem: &mut Problem<O>) -> Result<(), Error>
+    where
+        O: CostFunction<Param = P, Output = F> + SyncAlias,
+        P: SyncAlias,
+        F: SendAlias,
+    {
         let (min, max) = &self.search_region;
         let delta = max.sub(min);
         let delta_neg = delta.mul(&F::from_f64(-1.0).unwrap());
 
-        let initial_position = O::Param::rand_from_range(min, max);
-        let initial_cost = problem.cost(&initial_position).unwrap(); // FIXME do not unwrap
+        let positions: Vec<P> = (0..self.num_particles)
+            .map(|_| P::rand_from_range(min, max))
+            .collect();
+        let costs = problem.bulk_cost(&positions)?;
 
-        Particle {
-            position: initial_position.clone(),
-            velocity: O::Param::rand_from_range(&delta_neg, &delta),
-            cost: initial_cost,
-            best_position: initial_position,
-            best_cost: initial_cost,
-        }
+        self.particles = positions
+            .into_iter()
+            .zip(costs)
+            .map(|(position, cost)| Particle {
+                position: position.clone(),
+                velocity: P::rand_from_range(&delta_neg, &delta),
+                cost,
+                best_position: position,
+                best_cost: cost,
+            })
+            .collect();
+
+        let (best_position, best_cost) = self.get_best_position();
+        self.best_position = best_position;
+        self.best_cost = best_cost;
+        Ok(())
     }
 
-    fn get_best_position(&self) -> P {
+    fn get_best_position(&self) -> (P, F) {
         let mut best: Option<(&P, F)> = None;
 
         for p in &self.particles {
@@ -126,7 +133,7 @@ where
         }
 
         match best {
-            Some(best_sofar) => best_sofar.0.clone(),
+            Some(best_sofar) => (best_sofar.0.clone(), best_sofar.1),
             None => panic!("Particles not initialized"),
         }
     }
@@ -134,9 +141,9 @@ where
 
 impl<O, P, F> Solver<O, IterState<P, (), (), (), F>> for ParticleSwarm<P, F>
 where
-    O: CostFunction<Param = P, Output = F>,
-    P: SerializeAlias + Position<F>,
-    F: ArgminFloat,
+    O: CostFunction<Param = P, Output = F> + SyncAlias,
+    P: SerializeAlias + SyncAlias + Position<F>,
+    F: ArgminFloat + SendAlias,
 {
     const NAME: &'static str = "Particle Swarm Optimization";
 
@@ -145,7 +152,7 @@ where
         problem: &mut Problem<O>,
         state: IterState<P, (), (), (), F>,
     ) -> Result<(IterState<P, (), (), (), F>, Option<KV>), Error> {
-        self.initialize_particles(problem);
+        self.initialize_particles(problem)?;
 
         Ok((state, None))
     }
@@ -185,8 +192,14 @@ where
                 &P::max(&new_position, &self.search_region.0),
                 &self.search_region.1,
             );
+        }
+
+        // Evaluate the new positions of all particles at once
+        let positions: Vec<&P> = self.particles.iter().map(|p| &p.position).collect();
+        let costs = problem.bulk_cost(&positions)?;
 
-            p.cost = problem.cost(&p.position)?;
+        for (p, cost) in self.particles.iter_mut().zip(costs) {
+            p.cost = cost;
             if p.cost < p.best_cost {
                 p.best_position = p.position.clone();
                 p.best_cost = p.cost;
@@ -11,8 +11,9 @@
 //! Springer. ISBN 0-387-30303-0.
 
 use crate::core::{
-    ArgminFloat, CostFunction, DeserializeOwnedAlias, Error, Executor, Gradient, IterState,
-    LineSearch, OptimizationResult, Problem, SerializeAlias, Solver, State, TerminationReason, KV,
+    ArgminFloat, CostFunction, CostGradient, DeserializeOwnedAlias, Error, Executor, Gradient,
+    IterState, LineSearch, OptimizationResult, Problem, SerializeAlias, Solver, State,
+    TerminationReason, KV,
 };
 use argmin_math::{ArgminAdd, ArgminDot, ArgminMul, ArgminNorm, ArgminSub};
 #[cfg(feature = "serde1")]
@@ -77,7 +78,7 @@ where
 
 impl<O, L, P, G, F> Solver<O, IterState<P, G, (), (), F>> for LBFGS<L, P, G, F>
 where
-    O: CostFunction<Param = P, Output = F> + Gradient<Param = P, Gradient = G>,
+    O: CostGradient + CostFunction<Param = P, Output = F> + Gradient<Param = P, Gradient = G>,
     P: Clone
         + SerializeAlias
         + DeserializeOwnedAlias
@@ -105,8 +106,7 @@ where
         mut state: IterState<P, G, (), (), F>,
     ) -> Result<(IterState<P, G, (), (), F>, Option<KV>), Error> {
         let param = state.take_param().unwrap();
-        let cost = problem.cost(&param)?;
-        let grad = problem.gradient(&param)?;
+        let (cost, grad) = problem.cost_and_gradient(&param)?;
         Ok((state.param(param).cost(cost).grad(grad), None))
     }
 
@@ -175,7 +175,11 @@ where
             self.y.pop_front();
         }
 
-        let grad = problem.gradient(&xk1)?;
+        // Reuse the gradient at `xk1` if the line search already computed it
+        let grad = match linesearch_state.take_grad() {
+            Some(grad) if self.linesearch.gradient_at_param() => grad,
+            _ => problem.gradient(&xk1)?,
+        };
 
         self.s.push_back(xk1.sub(&param));
         self.y.push_back(grad.sub(&prev_grad));
@@ -227,4 +231,95 @@ mod tests {
         assert!((t1 - tol1).abs() < std::f64::EPSILON);
         assert!((t2 - tol2).abs() < std::f64::EPSILON);
     }
+
+    #[test]
+    fn test_cost_and_gradient() {
+        use crate::core::test_utils::Rosenbrock;
+        use crate::core::Executor;
+
+        let problem = Rosenbrock::new();
+        let counter = problem.clone();
+        let solver: LBFGS<_, Vec<f64>, Vec<f64>, f64> = LBFGS::new(MoreThuenteLineSearch::new(), 5);
+        let result = Executor::new(problem, solver)
+            .configure(|state| state.param(vec![-1.2, 1.0]).max_iters(100))
+            .run()
+            .unwrap();
+
+        assert!(result.state.get_best_cost() < 1e-8);
+        assert_eq!(counter.cost_count(), 0);
+        assert_eq!(counter.gradient_count(), 0);
+        let combined = counter.cost_and_gradient_count();
+        assert!(combined > 0);
+        assert_eq!(result.state.get_func_counts()["cost_count"], combined);
+        assert_eq!(result.state.get_func_counts()["gradient_count"], combined);
+    }
+
+    #[test]
+    fn test_stale_linesearch_gradient() {
+        use crate::core::test_utils::TestProblem;
+        use crate::core::{CostFunction, Executor, LineSearch, Problem, Solver, TerminationReason};
+        #[cfg(feature = "serde1")]
+        use serde::{Deserialize, Serialize};
+
+        // Takes a fixed step and leaves a gradient which does not belong to the returned
+        // parameter vector in the state
+        #[derive(Clone)]
+        #[cfg_attr(feature = "serde1", derive(Serialize, Deserialize))]
+        struct StaleGradient {
+            direction: Vec<f64>,
+        }
+
+        impl LineSearch<Vec<f64>, f64> for StaleGradient {
+            fn set_search_direction(&mut self, direction: Vec<f64>) {
+                self.direction = direction;
+            }
+
+            fn set_init_alpha(&mut self, _alpha: f64) -> Result<(), Error> {
+                Ok(())
+            }
+        }
+
+        impl<O> Solver<O, IterState<Vec<f64>, Vec<f64>, (), (), f64>> for StaleGradient
+        where
+            O: CostFunction<Param = Vec<f64>, Output = f64>,
+        {
+            const NAME: &'static str = "StaleGradient";
+
+            fn next_iter(
+                &mut self,
+                problem: &mut Problem<O>,
+                mut state: IterState<Vec<f64>, Vec<f64>, (), (), f64>,
+            ) -> Result<(IterState<Vec<f64>, Vec<f64>, (), (), f64>, Option<KV>), Error>
+            {
+                let param = state.take_param().unwrap();
+                let new_param: Vec<f64> = param
+                    .iter()
+                    .zip(self.direction.iter())
+                    .map(|(p, d)| p + 0.1 * d)
+                    .collect();
+                let cost = problem.cost(&new_param)?;
+                Ok((
+                    state
+                        .param(new_param)
+                        .cost(cost)
+                        .grad(vec![f64::NAN; 2])
+                        .termination_reason(TerminationReason::LineSearchConditionMet),
+                    None,
+                ))
+            }
+        }
+
+        let linesearch = StaleGradient { direction: vec![] };
+        let solver: LBFGS<_, Vec<f64>, Vec<f64>, f64> = LBFGS::new(linesearch, 5);
+        let result = Executor::new(TestProblem::new(), solver)
+            .configure(|state| state.param(vec![1.0, 2.0]).max_iters(1))
+            .run()
+            .unwrap();
+
+        // The gradient of `TestProblem` equals the parameter vector
+        let state = result.state();
+        assert_eq!(state.get_iter(), 1);
+        assert_eq!(state.get_grad().unwrap(), state.get_param().unwrap());
+        assert_eq!(state.get_func_counts()["gradient_count"], 2);
+    }
 }
@@ -11,8 +11,9 @@
 //! Springer. ISBN 0-387-30303-0.
 
 use crate::core::{
-    ArgminFloat, CostFunction, DeserializeOwnedAlias, Error, Executor, Gradient, IterState,
-    LineSearch, OptimizationResult, Problem, SerializeAlias, Solver, TerminationReason, KV,
+    ArgminFloat, CostFunction, CostGradient, DeserializeOwnedAlias, Error, Executor, Gradient,
+    IterState, LineSearch, OptimizationResult, Problem, SerializeAlias, Solver, TerminationReason,
+    KV,
 };
 use argmin_math::{ArgminAdd, ArgminDot, ArgminMul, ArgminNorm, ArgminSub};
 #[cfg(feature = "serde1")]
@@ -84,7 +85,7 @@ where
 
 impl<O, L, P, G, H, F> Solver<O, IterState<P, G, (), H, F>> for SR1<L, H, F>
 where
-    O: CostFunction<Param = P, Output = F> + Gradient<Param = P, Gradient = G>,
+    O: CostGradient + CostFunction<Param = P, Output = F> + Gradient<Param = P, Gradient = G>,
     P: Clone
         + SerializeAlias
         + DeserializeOwnedAlias
@@ -106,8 +107,7 @@ where
         mut state: IterState<P, G, (), H, F>,
     ) -> Result<(IterState<P, G, (), H, F>, Option<KV>), Error> {
         let param = state.take_param().unwrap();
-        let cost = problem.cost(&param)?;
-        let grad = problem.gradient(&param)?;
+        let (cost, grad) = problem.cost_and_gradient(&param)?;
         Ok((
             state
                 .param(param)
@@ -156,7 +156,11 @@ where
         // take care of function eval counts
         problem.consume_problem(line_problem);
 
-        let grad = problem.gradient(&xk1)?;
+        // Reuse the gradient at `xk1` if the line search already computed it
+        let grad = match linesearch_state.take_grad() {
+            Some(grad) if self.linesearch.gradient_at_param() => grad,
+            _ => problem.gradient(&xk1)?,
+        };
         let yk = grad.sub(&prev_grad);
 
         let sk = xk1.sub(&param);
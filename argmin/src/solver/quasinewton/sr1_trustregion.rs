@@ -11,8 +11,8 @@
 //! Springer. ISBN 0-387-30303-0.
 
 use crate::core::{
-    ArgminFloat, CostFunction, DeserializeOwnedAlias, Error, Executor, Gradient, Hessian,
-    IterState, OptimizationResult, Problem, SerializeAlias, Solver, TerminationReason,
+    ArgminFloat, CostFunction, CostGradient, DeserializeOwnedAlias, Error, Executor, Gradient,
+    Hessian, IterState, OptimizationResult, Problem, SerializeAlias, Solver, TerminationReason,
     TrustRegionRadius, KV,
 };
 use argmin_math::{
@@ -109,7 +109,8 @@ where
 
 impl<O, R, P, G, B, F> Solver<O, IterState<P, G, (), B, F>> for SR1TrustRegion<B, R, F>
 where
-    O: CostFunction<Param = P, Output = F>
+    O: CostGradient
+        + CostFunction<Param = P, Output = F>
         + Gradient<Param = P, Gradient = G>
         + Hessian<Param = P, Hessian = B>,
     P: Clone
@@ -144,8 +145,7 @@ where
         mut state: IterState<P, G, (), B, F>,
     ) -> Result<(IterState<P, G, (), B, F>, Option<KV>), Error> {
         let param = state.take_param().unwrap();
-        let cost = problem.cost(&param)?;
-        let grad = problem.gradient(&param)?;
+        let (cost, grad) = problem.cost_and_gradient(&param)?;
         let hessian = state
             .take_hessian()
             .map(Result::Ok)
@@ -191,9 +191,8 @@ where
         problem.consume_problem(sub_problem);
 
         let xksk = xk.add(&sk);
-        let dfk1 = problem.gradient(&xksk)?;
+        let (fk1, dfk1) = problem.cost_and_gradient(&xksk)?;
         let yk = dfk1.sub(&prev_grad);
-        let fk1 = problem.cost(&xksk)?;
 
         let ared = cost - fk1;
         let tmp1: F = prev_grad.dot(&sk);
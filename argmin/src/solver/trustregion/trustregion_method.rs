@@ -11,8 +11,8 @@
 //! Springer. ISBN 0-387-30303-0.
 
 use crate::core::{
-    ArgminFloat, CostFunction, DeserializeOwnedAlias, Error, Executor, Gradient, Hessian,
-    IterState, OptimizationResult, Problem, SerializeAlias, Solver, TerminationReason,
+    ArgminFloat, CostFunction, CostGradient, DeserializeOwnedAlias, Error, Executor, Gradient,
+    Hessian, IterState, OptimizationResult, Problem, SerializeAlias, Solver, TerminationReason,
     TrustRegionRadius, KV,
 };
 use crate::solver::trustregion::reduction_ratio;
@@ -100,7 +100,8 @@ where
 
 impl<O, R, F, P, G, H> Solver<O, IterState<P, G, (), H, F>> for TrustRegion<R, F>
 where
-    O: CostFunction<Param = P, Output = F>
+    O: CostGradient
+        + CostFunction<Param = P, Output = F>
         + Gradient<Param = P, Gradient = G>
         + Hessian<Param = P, Hessian = H>,
     P: Clone
@@ -123,9 +124,9 @@ where
         mut state: IterState<P, G, (), H, F>,
     ) -> Result<(IterState<P, G, (), H, F>, Option<KV>), Error> {
         let param = state.take_param().unwrap();
-        let grad = problem.gradient(&param)?;
+        let (fxk, grad) = problem.cost_and_gradient(&param)?;
         let hessian = problem.hessian(&param)?;
-        self.fxk = problem.cost(&param)?;
+        self.fxk = fxk;
         self.mk0 = self.fxk;
         Ok((
             state